@@ -0,0 +1,67 @@
+//! Context extraction on the consumer hot path.
+//!
+//! Compares the propagator-based paths with the W3C-only [`sqs::extract_w3c`] fast
+//! path, for a message carrying `traceparent`, `tracestate`, and a few business
+//! attributes. Run with `cargo bench -p opentelemetry-aws-messaging`.
+
+use aws_sdk_sqs::types::{Message, MessageAttributeValue};
+use criterion::{criterion_group, criterion_main, Criterion};
+use opentelemetry::propagation::TextMapCompositePropagator;
+use opentelemetry::{global, Context};
+use opentelemetry_aws_messaging::sqs::{self, MessageAttributesExtractor};
+use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
+use std::hint::black_box;
+
+fn attribute(value: &str) -> MessageAttributeValue {
+    MessageAttributeValue::builder()
+        .data_type("String")
+        .string_value(value)
+        .build()
+        .unwrap()
+}
+
+fn message() -> Message {
+    Message::builder()
+        .message_id("059f36b4-87a3-44ab-83d2-661975830a7d")
+        .body(r#"{"order_id":42}"#)
+        .message_attributes(
+            "traceparent",
+            attribute("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+        )
+        .message_attributes("tracestate", attribute("dd=s:1;t.dm:-0"))
+        .message_attributes("tenant_id", attribute("acme"))
+        .message_attributes("event_type", attribute("order.created"))
+        .message_attributes("priority", attribute("high"))
+        .build()
+}
+
+fn extract(c: &mut Criterion) {
+    global::set_text_map_propagator(TextMapCompositePropagator::new(vec![
+        Box::new(TraceContextPropagator::new()),
+        Box::new(BaggagePropagator::new()),
+    ]));
+    let msg = message();
+    let attributes = msg.message_attributes().unwrap();
+
+    let mut group = c.benchmark_group("extract");
+    group.bench_function("extract_isolated", |b| {
+        b.iter(|| sqs::extract_isolated(black_box(&msg)))
+    });
+    group.bench_function("global_propagator", |b| {
+        b.iter(|| {
+            global::get_text_map_propagator(|propagator| {
+                propagator.extract_with_context(
+                    &Context::new(),
+                    &MessageAttributesExtractor(black_box(attributes)),
+                )
+            })
+        })
+    });
+    group.bench_function("extract_w3c", |b| {
+        b.iter(|| sqs::extract_w3c(black_box(&msg)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, extract);
+criterion_main!(benches);
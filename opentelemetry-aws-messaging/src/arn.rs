@@ -0,0 +1,137 @@
+//! Cloud attributes derived from AWS ARNs and SQS queue URLs.
+//!
+//! Spans for multi-account, multi-region deployments are easier to filter when they
+//! carry the `cloud.provider`, `cloud.region`, and `cloud.account.id` semantic
+//! convention attributes. Topic ARNs and queue ARNs carry the region and account:
+//!
+//! ```text
+//! arn:aws:sns:us-east-1:123456789012:orders
+//! arn:aws:sqs:us-east-1:123456789012:orders
+//! ```
+//!
+//! and so do SQS queue URLs (`https://sqs.us-east-1.amazonaws.com/123456789012/orders`),
+//! which is all the SQS APIs hand a consumer.
+
+use opentelemetry::KeyValue;
+
+/// The parts of an ARN (`arn:partition:service:region:account-id:resource`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arn<'a> {
+    /// The partition, e.g. `aws` or `aws-cn`.
+    pub partition: &'a str,
+    /// The service namespace, e.g. `sns`.
+    pub service: &'a str,
+    /// The region; empty for global resources.
+    pub region: &'a str,
+    /// The account id; empty for some resource types.
+    pub account_id: &'a str,
+    /// The resource, e.g. the topic name.
+    pub resource: &'a str,
+}
+
+/// Parses `arn`, or returns `None` if it does not have the six ARN fields.
+pub fn parse_arn(arn: &str) -> Option<Arn<'_>> {
+    let mut parts = arn.splitn(6, ':');
+    if parts.next()? != "arn" {
+        return None;
+    }
+    Some(Arn {
+        partition: parts.next()?,
+        service: parts.next()?,
+        region: parts.next()?,
+        account_id: parts.next()?,
+        resource: parts.next()?,
+    })
+}
+
+/// Returns the cloud attributes for the resource identified by `arn`.
+///
+/// `cloud.region` and `cloud.account.id` are omitted when the ARN leaves them
+/// empty; nothing is returned for a malformed ARN.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::arn::cloud_attributes;
+///
+/// let span = tracer
+///     .span_builder("sns.publish")
+///     .with_attributes(cloud_attributes(&topic_arn))
+///     .start(&tracer);
+/// ```
+pub fn cloud_attributes(arn: &str) -> Vec<KeyValue> {
+    match parse_arn(arn) {
+        Some(arn) => attributes(arn.region, arn.account_id),
+        None => Vec::new(),
+    }
+}
+
+/// Returns the cloud attributes for the queue at `queue_url`, in the
+/// `https://sqs.<region>.amazonaws.com/<account-id>/<queue>` form.
+///
+/// Nothing is returned for other URLs, e.g. a LocalStack endpoint without a region.
+pub fn queue_url_cloud_attributes(queue_url: &str) -> Vec<KeyValue> {
+    let Some(rest) = queue_url
+        .strip_prefix("https://")
+        .or_else(|| queue_url.strip_prefix("http://"))
+    else {
+        return Vec::new();
+    };
+    let mut segments = rest.split('/');
+    let host = segments.next().unwrap_or_default();
+    let account_id = segments.next().unwrap_or_default();
+    let region = host
+        .strip_prefix("sqs.")
+        .and_then(|host| host.split('.').next())
+        .unwrap_or_default();
+    if region.is_empty() || account_id.is_empty() {
+        return Vec::new();
+    }
+    attributes(region, account_id)
+}
+
+fn attributes(region: &str, account_id: &str) -> Vec<KeyValue> {
+    let mut attributes = vec![KeyValue::new("cloud.provider", "aws")];
+    if !region.is_empty() {
+        attributes.push(KeyValue::new("cloud.region", region.to_string()));
+    }
+    if !account_id.is_empty() {
+        attributes.push(KeyValue::new("cloud.account.id", account_id.to_string()));
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected() -> Vec<KeyValue> {
+        vec![
+            KeyValue::new("cloud.provider", "aws"),
+            KeyValue::new("cloud.region", "us-east-1"),
+            KeyValue::new("cloud.account.id", "123456789012"),
+        ]
+    }
+
+    #[test]
+    fn test_cloud_attributes_from_topic_and_queue_arns() {
+        assert_eq!(
+            cloud_attributes("arn:aws:sns:us-east-1:123456789012:orders"),
+            expected()
+        );
+        assert_eq!(
+            cloud_attributes("arn:aws:sqs:us-east-1:123456789012:orders"),
+            expected()
+        );
+        assert!(cloud_attributes("orders").is_empty());
+    }
+
+    #[test]
+    fn test_cloud_attributes_from_queue_url() {
+        assert_eq!(
+            queue_url_cloud_attributes("https://sqs.us-east-1.amazonaws.com/123456789012/orders"),
+            expected()
+        );
+        assert!(queue_url_cloud_attributes("http://localhost:4566/000000000000/orders").is_empty());
+    }
+}
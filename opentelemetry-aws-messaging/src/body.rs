@@ -0,0 +1,217 @@
+//! Trace context carried inside a JSON message body.
+//!
+//! Some queues receive messages through raw delivery with an SNS filter policy that
+//! strips every message attribute, leaving no attribute channel for trace context.
+//! As a fallback, the context can travel in the body itself, as an object under the
+//! reserved `_otel` field:
+//!
+//! ```text
+//! {"id": 1, "_otel": {"traceparent": "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"}}
+//! ```
+//!
+//! Teams that reserve every message attribute for business routing can use the body
+//! as the only trace channel. The field name is configurable through
+//! [`inject_into_body_field`] and [`BodyExtractor::with_field`], e.g. for a `_trace`
+//! convention, provided both sides agree on it.
+//!
+//! To send the context in both the message attributes and the body, use
+//! [`inject_redundant`].
+//!
+//! Both sides are no-ops when the body is not a JSON object, so plain-text and array
+//! bodies pass through unchanged. Consumers that deserialize the body into their own
+//! types are unaffected as long as unknown fields are ignored (serde's default).
+
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::{global, Context};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// The body field that holds the trace context.
+pub const CONTEXT_FIELD: &str = "_otel";
+
+/// Returns `body` with the context from `cx` added under [`CONTEXT_FIELD`], using the
+/// global propagator.
+///
+/// A body that is not a JSON object is returned unchanged.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::body;
+///
+/// let message_body = body::inject_into_body(&cx, &serde_json::to_string(&message)?);
+/// client.publish().topic_arn(&topic_arn).message(message_body).send().await?;
+/// ```
+pub fn inject_into_body(cx: &Context, body: &str) -> String {
+    inject_into_body_field(cx, body, CONTEXT_FIELD)
+}
+
+/// Returns `body` with the context from `cx` added under `field`, using the global
+/// propagator.
+///
+/// An existing `field` is replaced. A body that is not a JSON object is returned
+/// unchanged.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::body;
+///
+/// let message_body = body::inject_into_body_field(&cx, &payload, "_trace");
+/// ```
+pub fn inject_into_body_field(cx: &Context, body: &str, field: &str) -> String {
+    let Ok(Value::Object(mut object)) = serde_json::from_str::<Value>(body) else {
+        return body.to_string();
+    };
+
+    let mut fields = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(cx, &mut fields));
+    object.insert(
+        field.to_string(),
+        Value::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k, Value::String(v)))
+                .collect(),
+        ),
+    );
+    Value::Object(object).to_string()
+}
+
+/// Injects `cx` with the global propagator into both `attributes` and the body, and
+/// returns the body to send.
+///
+/// This is the redundant mode for pipelines whose delivery configuration is uncertain:
+/// the context survives a subscription that strips message attributes as well as one
+/// that rewrites the body, and
+/// [`CompositeExtractor::from_message_redundant`](crate::sqs::CompositeExtractor::from_message_redundant)
+/// reads whichever channel is left. The cost is sending the trace fields twice: a
+/// `traceparent` alone adds about 80 bytes to the body on top of its attribute, and a
+/// Datadog `tracestate` about as much again, all counted toward the message size limit
+/// (256 KiB for SNS, 1 MiB for SQS). A body that is not a JSON object only gets the
+/// attributes.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::body;
+///
+/// let mut attributes = HashMap::new();
+/// let message_body = body::inject_redundant(
+///     &cx,
+///     &mut SnsMessageAttributesInjector(&mut attributes),
+///     &serde_json::to_string(&message)?,
+/// );
+/// client
+///     .publish()
+///     .topic_arn(&topic_arn)
+///     .message(message_body)
+///     .set_message_attributes(Some(attributes))
+///     .send()
+///     .await?;
+/// ```
+pub fn inject_redundant(cx: &Context, attributes: &mut dyn Injector, body: &str) -> String {
+    global::get_text_map_propagator(|propagator| propagator.inject_context(cx, attributes));
+    inject_into_body(cx, body)
+}
+
+/// An [`Extractor`] over the trace fields stored under [`CONTEXT_FIELD`] in a JSON body.
+///
+/// A body that is not a JSON object, or has no context field, yields no keys.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_aws_messaging::body::BodyExtractor;
+///
+/// let parent_cx = global::get_text_map_propagator(|propagator| {
+///     propagator.extract(&BodyExtractor::new(msg.body().unwrap_or_default()))
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct BodyExtractor {
+    fields: Map<String, Value>,
+}
+
+impl BodyExtractor {
+    /// Parses the trace fields stored under [`CONTEXT_FIELD`] out of `body`.
+    pub fn new(body: &str) -> Self {
+        Self::with_field(body, CONTEXT_FIELD)
+    }
+
+    /// Parses the trace fields stored under `field` out of `body`.
+    pub fn with_field(body: &str, field: &str) -> Self {
+        let fields = match serde_json::from_str::<Value>(body) {
+            Ok(Value::Object(mut object)) => match object.remove(field) {
+                Some(Value::Object(fields)) => fields,
+                _ => Map::new(),
+            },
+            _ => Map::new(),
+        };
+        Self { fields }
+    }
+}
+
+impl Extractor for BodyExtractor {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).and_then(Value::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.fields.keys().map(|s| s.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, traceparent};
+
+    #[test]
+    fn test_inject_into_body_round_trips() {
+        init_propagator();
+
+        let body = inject_into_body(&sampled_context(), r#"{"id":1}"#);
+        let extractor = BodyExtractor::new(&body);
+
+        let value: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["id"], 1);
+        assert_eq!(
+            extractor.get("traceparent"),
+            Some(traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7").as_str())
+        );
+    }
+
+    #[test]
+    fn test_inject_into_non_json_body_is_noop() {
+        init_propagator();
+
+        assert_eq!(
+            inject_into_body(&sampled_context(), "plain text"),
+            "plain text"
+        );
+        assert_eq!(inject_into_body(&sampled_context(), "[1,2]"), "[1,2]");
+    }
+
+    #[test]
+    fn test_extractor_without_context_field_is_empty() {
+        assert!(BodyExtractor::new(r#"{"id":1}"#).keys().is_empty());
+        assert!(BodyExtractor::new("plain text").keys().is_empty());
+        assert!(BodyExtractor::new(r#"{"_otel":"oops"}"#).keys().is_empty());
+    }
+
+    #[test]
+    fn test_custom_field_round_trips() {
+        init_propagator();
+
+        let body = inject_into_body_field(&sampled_context(), r#"{"id":1}"#, "_trace");
+        let value: Value = serde_json::from_str(&body).unwrap();
+
+        assert!(value.get(CONTEXT_FIELD).is_none());
+        assert!(BodyExtractor::with_field(&body, "_trace")
+            .get("traceparent")
+            .is_some());
+        assert!(BodyExtractor::new(&body).keys().is_empty());
+    }
+}
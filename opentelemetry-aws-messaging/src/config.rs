@@ -0,0 +1,307 @@
+//! Configuration shared by the injector and extractor sides.
+//!
+//! A classic propagation bug is a publisher writing trace fields as `Binary` attributes
+//! while the consumer only reads `String` ones. Building both carriers from the same
+//! [`CarrierConfig`] keeps the two sides in agreement.
+//!
+//! # Dotted keys
+//!
+//! SNS and SQS accept `.` in attribute names, and the SDKs pass names through
+//! unchanged, so a propagator field such as `vendor.trace-id` round-trips as is with
+//! the default config. The services do reject names that start or end with a `.` or
+//! contain `..`, though, and some intermediaries normalize dots. Set
+//! [`CarrierConfig::escape_dots`] to write each `.` as `__` instead; the configured
+//! extractor reverses the escaping, so a field that legitimately contains `__` must
+//! not be used with this option.
+//!
+//! # Reserved-safe names
+//!
+//! Some endpoints validate attribute names more strictly than documented. Set
+//! [`CarrierConfig::safe_names`] to write the well-known fields listed in
+//! [`SAFE_NAMES`] under names made only of lowercase letters, digits, and underscores
+//! (e.g. `traceparent` is written as `otel_traceparent`). Other fields only get the
+//! [`SAFE_NAME_PREFIX`] prefix and keep their own characters, so `vendor-id` is
+//! written as `otel_vendor-id`. The configured extractor reverses the mapping; both
+//! sides must enable it.
+
+use std::borrow::Cow;
+
+/// The default [`CarrierConfig::max_body_size`]: 1.25 MiB, above both the 256 KiB SNS
+/// and the 1 MiB SQS message size limits.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1280 * 1024;
+
+/// The sequence a `.` in a field name is written as when [`CarrierConfig::escape_dots`]
+/// is set.
+const ESCAPED_DOT: &str = "__";
+
+/// The prefix of every attribute name written when [`CarrierConfig::safe_names`] is
+/// set.
+pub const SAFE_NAME_PREFIX: &str = "otel_";
+
+/// The attribute names used for well-known propagator fields when
+/// [`CarrierConfig::safe_names`] is set, as `(field, attribute name)` pairs.
+///
+/// Fields are matched case-insensitively, since propagators differ in the casing they
+/// use for the same header.
+pub const SAFE_NAMES: [(&str, &str); 9] = [
+    ("traceparent", "otel_traceparent"),
+    ("tracestate", "otel_tracestate"),
+    ("baggage", "otel_baggage"),
+    ("b3", "otel_b3"),
+    ("x-b3-traceid", "otel_x_b3_traceid"),
+    ("x-b3-spanid", "otel_x_b3_spanid"),
+    ("x-b3-sampled", "otel_x_b3_sampled"),
+    ("x-b3-flags", "otel_x_b3_flags"),
+    ("x-amzn-trace-id", "otel_x_amzn_trace_id"),
+];
+
+/// How trace fields are encoded as message attribute values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// `data_type = "String"` with the value in `string_value` (the default).
+    #[default]
+    String,
+    /// `data_type = "Binary"` with the UTF-8 bytes of the value in `binary_value`.
+    Binary,
+}
+
+impl Encoding {
+    /// Returns the AWS message attribute data type for this encoding.
+    pub fn data_type(&self) -> &'static str {
+        match self {
+            Encoding::String => "String",
+            Encoding::Binary => "Binary",
+        }
+    }
+}
+
+/// Carrier settings accepted by both the configured injectors and extractors.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::config::{CarrierConfig, Encoding};
+/// use opentelemetry_aws_messaging::{sns, sqs};
+///
+/// let config = CarrierConfig {
+///     encoding: Encoding::Binary,
+///     ..Default::default()
+/// };
+///
+/// // Publisher
+/// let mut injector = sns::ConfiguredInjector::new(&mut attributes, &config);
+///
+/// // Consumer
+/// let extractor = sqs::ConfiguredExtractor::new(attrs, &config);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CarrierConfig {
+    /// Encoding used for trace attribute values.
+    pub encoding: Encoding,
+    /// Writes each `.` in a field name as `__`, for attribute names the services or an
+    /// intermediary would reject or rewrite.
+    pub escape_dots: bool,
+    /// Writes well-known fields under reserved-safe names (see [`SAFE_NAMES`]) and
+    /// prefixes the others with [`SAFE_NAME_PREFIX`], for endpoints that validate
+    /// attribute names strictly.
+    pub safe_names: bool,
+    /// The largest message body, in bytes, that body-parsing extractors (such as the
+    /// SNS envelope extractor) will parse. Larger bodies yield no keys. Defaults to
+    /// [`DEFAULT_MAX_BODY_SIZE`].
+    pub max_body_size: usize,
+}
+
+impl Default for CarrierConfig {
+    fn default() -> Self {
+        Self {
+            encoding: Encoding::default(),
+            escape_dots: false,
+            safe_names: false,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+}
+
+impl CarrierConfig {
+    /// Returns whether attribute names differ from the propagator field names.
+    pub(crate) fn rewrites_keys(&self) -> bool {
+        self.escape_dots || self.safe_names
+    }
+
+    /// Returns the attribute name used for propagator field `key`.
+    pub(crate) fn encode_key<'k>(&self, key: &'k str) -> Cow<'k, str> {
+        let key = if self.safe_names {
+            Cow::Owned(safe_name(key))
+        } else {
+            Cow::Borrowed(key)
+        };
+        if self.escape_dots && key.contains('.') {
+            Cow::Owned(key.replace('.', ESCAPED_DOT))
+        } else {
+            key
+        }
+    }
+
+    /// Returns the propagator field name for attribute name `name`.
+    pub(crate) fn decode_key<'k>(&self, name: &'k str) -> Cow<'k, str> {
+        let name = if self.escape_dots && name.contains(ESCAPED_DOT) {
+            Cow::Owned(name.replace(ESCAPED_DOT, "."))
+        } else {
+            Cow::Borrowed(name)
+        };
+        if !self.safe_names {
+            return name;
+        }
+        if let Some((field, _)) = SAFE_NAMES.iter().find(|(_, safe)| *safe == name) {
+            return Cow::Borrowed(field);
+        }
+        match name.strip_prefix(SAFE_NAME_PREFIX) {
+            Some(field) => Cow::Owned(field.to_string()),
+            None => name,
+        }
+    }
+}
+
+fn safe_name(field: &str) -> String {
+    SAFE_NAMES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(field))
+        .map(|(_, safe)| safe.to_string())
+        .unwrap_or_else(|| format!("{SAFE_NAME_PREFIX}{field}"))
+}
+
+#[cfg(all(test, feature = "sns", feature = "sqs"))]
+mod tests {
+    use super::*;
+    use crate::{sns, sqs};
+    use opentelemetry::propagation::{Extractor, Injector};
+    use std::collections::HashMap;
+
+    /// Simulates SNS raw delivery promoting SNS attributes to SQS attributes.
+    fn deliver(
+        attrs: HashMap<String, aws_sdk_sns::types::MessageAttributeValue>,
+    ) -> HashMap<String, aws_sdk_sqs::types::MessageAttributeValue> {
+        attrs
+            .into_iter()
+            .map(|(k, v)| {
+                let mut builder = aws_sdk_sqs::types::MessageAttributeValue::builder()
+                    .data_type(v.data_type())
+                    .set_string_value(v.string_value().map(str::to_string));
+                if let Some(blob) = v.binary_value() {
+                    builder =
+                        builder.binary_value(aws_sdk_sqs::primitives::Blob::new(blob.as_ref()));
+                }
+                (k, builder.build().unwrap())
+            })
+            .collect()
+    }
+
+    fn round_trip(config: &CarrierConfig) -> Option<String> {
+        let mut attrs = HashMap::new();
+        sns::ConfiguredInjector::new(&mut attrs, config)
+            .set("traceparent", "00-abc123-def456-01".to_string());
+
+        let received = deliver(attrs);
+        let extractor = sqs::ConfiguredExtractor::new(&received, config);
+        extractor.get("traceparent").map(str::to_string)
+    }
+
+    #[test]
+    fn test_string_encoding_round_trips() {
+        let config = CarrierConfig {
+            encoding: Encoding::String,
+            ..Default::default()
+        };
+
+        assert_eq!(round_trip(&config).as_deref(), Some("00-abc123-def456-01"));
+    }
+
+    #[test]
+    fn test_binary_encoding_round_trips() {
+        let config = CarrierConfig {
+            encoding: Encoding::Binary,
+            ..Default::default()
+        };
+
+        assert_eq!(round_trip(&config).as_deref(), Some("00-abc123-def456-01"));
+    }
+
+    #[test]
+    fn test_mismatched_encoding_does_not_extract() {
+        let mut attrs = HashMap::new();
+        let binary = CarrierConfig {
+            encoding: Encoding::Binary,
+            ..Default::default()
+        };
+        sns::ConfiguredInjector::new(&mut attrs, &binary)
+            .set("traceparent", "00-abc123-def456-01".to_string());
+
+        let received = deliver(attrs);
+        let string = CarrierConfig::default();
+        let extractor = sqs::ConfiguredExtractor::new(&received, &string);
+
+        assert_eq!(extractor.get("traceparent"), None);
+    }
+
+    #[test]
+    fn test_dotted_key_round_trips_unescaped() {
+        let mut attrs = HashMap::new();
+        let config = CarrierConfig::default();
+        sns::ConfiguredInjector::new(&mut attrs, &config)
+            .set("vendor.trace-id", "abc123".to_string());
+
+        let received = deliver(attrs);
+        let extractor = sqs::ConfiguredExtractor::new(&received, &config);
+
+        assert!(received.contains_key("vendor.trace-id"));
+        assert_eq!(extractor.get("vendor.trace-id"), Some("abc123"));
+        assert_eq!(extractor.keys(), vec!["vendor.trace-id"]);
+    }
+
+    #[test]
+    fn test_escaped_dotted_key_round_trips() {
+        let mut attrs = HashMap::new();
+        let config = CarrierConfig {
+            escape_dots: true,
+            ..Default::default()
+        };
+        sns::ConfiguredInjector::new(&mut attrs, &config)
+            .set("vendor.trace-id", "abc123".to_string());
+
+        let received = deliver(attrs);
+        let extractor = sqs::ConfiguredExtractor::new(&received, &config);
+
+        assert!(received.contains_key("vendor__trace-id"));
+        assert_eq!(extractor.get("vendor.trace-id"), Some("abc123"));
+        assert_eq!(extractor.keys(), vec!["vendor.trace-id"]);
+    }
+
+    #[test]
+    fn test_safe_names_round_trip() {
+        let mut attrs = HashMap::new();
+        let config = CarrierConfig {
+            safe_names: true,
+            ..Default::default()
+        };
+        let mut injector = sns::ConfiguredInjector::new(&mut attrs, &config);
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+        injector.set("X-B3-TraceId", "abc123".to_string());
+        injector.set("vendor-id", "v1".to_string());
+
+        let received = deliver(attrs);
+        let extractor = sqs::ConfiguredExtractor::new(&received, &config);
+
+        let mut names: Vec<_> = received.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            vec!["otel_traceparent", "otel_vendor-id", "otel_x_b3_traceid"]
+        );
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+        assert_eq!(extractor.get("x-b3-traceid"), Some("abc123"));
+        assert_eq!(extractor.get("vendor-id"), Some("v1"));
+        let mut keys = extractor.keys();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["traceparent", "vendor-id", "x-b3-traceid"]);
+    }
+}
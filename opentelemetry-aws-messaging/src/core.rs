@@ -0,0 +1,848 @@
+//! SDK-independent carrier logic.
+//!
+//! The SNS and SQS message attribute types are structurally identical but distinct
+//! types. This module implements the injector/extractor logic once, over any type
+//! implementing [`AttributeValue`]; the `sns` and `sqs` modules only provide the
+//! SDK-specific implementations of that trait. It is always available, so crates that
+//! need the propagation carriers for their own attribute type can depend on this crate
+//! with `default-features = false`.
+
+use crate::config::{CarrierConfig, Encoding};
+use crate::error::{InjectError, PropagationError};
+use opentelemetry::baggage::BaggageExt;
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::{global, Context, KeyValue};
+use std::collections::HashMap;
+
+/// A message attribute value that can carry a trace field.
+pub trait AttributeValue: Sized {
+    /// Builds a `String`-typed attribute value.
+    fn from_string(value: String) -> Self;
+
+    /// Builds a `Binary`-typed attribute value.
+    fn from_binary(value: Vec<u8>) -> Self;
+
+    /// Returns the string value, if any.
+    fn string_value(&self) -> Option<&str>;
+
+    /// Returns the binary value, if any.
+    fn binary_value(&self) -> Option<&[u8]>;
+
+    /// Returns `true` if the value is a list, which never carries a trace field.
+    fn is_list(&self) -> bool {
+        false
+    }
+
+    /// Builds a `String` value replacing this one, keeping its data type, e.g. a custom
+    /// `String.<label>` type.
+    ///
+    /// Defaults to [`from_string`](Self::from_string), for types without custom data
+    /// types.
+    fn with_string_value(&self, value: String) -> Self {
+        Self::from_string(value)
+    }
+
+    /// Builds a value using `encoding`.
+    fn encode(value: String, encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::String => Self::from_string(value),
+            Encoding::Binary => Self::from_binary(value.into_bytes()),
+        }
+    }
+
+    /// Reads the value written with `encoding`, decoding binary values as UTF-8.
+    ///
+    /// List values are never read.
+    fn decode(&self, encoding: Encoding) -> Option<&str> {
+        if self.is_list() {
+            return None;
+        }
+        match encoding {
+            Encoding::String => self.string_value(),
+            Encoding::Binary => self
+                .binary_value()
+                .and_then(|bytes| std::str::from_utf8(bytes).ok()),
+        }
+    }
+
+    /// Reads the string value, falling back to the binary value decoded as UTF-8.
+    ///
+    /// The bytes are validated in place, so the result borrows from the value like a
+    /// string value does. List values are never read.
+    fn decode_lenient(&self) -> Option<&str> {
+        self.decode(Encoding::String)
+            .or_else(|| self.decode(Encoding::Binary))
+    }
+}
+
+/// An [`Injector`] writing `String` attributes into a map of any [`AttributeValue`].
+///
+/// Like the SNS and SQS message attribute injectors, it logs and skips empty values,
+/// which AWS rejects, e.g. the `tracestate` of a context without trace state.
+pub struct AttributeInjector<'a, V>(pub &'a mut HashMap<String, V>);
+
+impl<V: AttributeValue> Injector for AttributeInjector<'_, V> {
+    fn set(&mut self, key: &str, value: String) {
+        if let Err(e) = try_insert_string(self.0, key, value) {
+            tracing::warn!(error = %e, "skipping trace context attribute");
+        }
+    }
+}
+
+/// Inserts `value` as a `String` attribute, or returns an error without inserting when
+/// it is empty, which AWS rejects.
+///
+/// The fallible path shared by every message attribute injector, [`AttributeInjector`]
+/// included.
+pub(crate) fn try_insert_string<V: AttributeValue>(
+    attributes: &mut HashMap<String, V>,
+    key: &str,
+    value: String,
+) -> Result<(), InjectError> {
+    try_insert_encoded(attributes, key, value, Encoding::String)
+}
+
+/// Inserts `value` as an attribute built with `encoding`, or returns an error without
+/// inserting when it is empty.
+pub(crate) fn try_insert_encoded<V: AttributeValue>(
+    attributes: &mut HashMap<String, V>,
+    key: &str,
+    value: String,
+    encoding: Encoding,
+) -> Result<(), InjectError> {
+    try_insert_with(attributes, key, value, |value| {
+        Ok(V::encode(value, encoding))
+    })
+}
+
+/// Inserts the attribute `build` makes from `value`, or returns an error without
+/// inserting when `value` is empty or `build` fails.
+pub(crate) fn try_insert_with<V>(
+    attributes: &mut HashMap<String, V>,
+    key: &str,
+    value: String,
+    build: impl FnOnce(String) -> Result<V, InjectError>,
+) -> Result<(), InjectError> {
+    if value.is_empty() {
+        return Err(InjectError::EmptyValue {
+            key: key.to_string(),
+        });
+    }
+    attributes.insert(key.to_string(), build(value)?);
+    Ok(())
+}
+
+/// An [`Extractor`] reading `String` attributes from a map of any [`AttributeValue`].
+pub struct AttributeExtractor<'a, V>(pub &'a HashMap<String, V>);
+
+impl<V: AttributeValue> Extractor for AttributeExtractor<'_, V> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.decode(Encoding::String))
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|s| s.as_str()).collect()
+    }
+}
+
+/// An [`Extractor`] over a map of any [`AttributeValue`] that reads values according
+/// to a [`CarrierConfig`].
+///
+/// Only attributes written with the configured encoding are visible: with
+/// [`Encoding::Binary`], `get` decodes the binary value as UTF-8 and ignores values
+/// that are not valid UTF-8.
+pub struct ConfiguredAttributeExtractor<'a, V> {
+    attributes: &'a HashMap<String, V>,
+    config: &'a CarrierConfig,
+    /// Field names with the attribute name rewriting reversed, when `config` rewrites
+    /// names.
+    decoded_keys: Option<Vec<String>>,
+}
+
+impl<'a, V> ConfiguredAttributeExtractor<'a, V> {
+    /// Creates an extractor reading `attributes` with the encoding and key escaping
+    /// from `config`.
+    pub fn new(attributes: &'a HashMap<String, V>, config: &'a CarrierConfig) -> Self {
+        let decoded_keys = config.rewrites_keys().then(|| {
+            attributes
+                .keys()
+                .map(|name| config.decode_key(name).into_owned())
+                .collect()
+        });
+        Self {
+            attributes,
+            config,
+            decoded_keys,
+        }
+    }
+}
+
+impl<V: AttributeValue> Extractor for ConfiguredAttributeExtractor<'_, V> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .get(self.config.encode_key(key).as_ref())?
+            .decode(self.config.encoding)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        match &self.decoded_keys {
+            Some(keys) => keys.iter().map(|s| s.as_str()).collect(),
+            None => self.attributes.keys().map(|s| s.as_str()).collect(),
+        }
+    }
+}
+
+/// An [`Injector`] forwarding only the listed fields to another injector.
+///
+/// Some propagators write more fields than the transport needs, e.g. the
+/// `opentelemetry-zipkin` single-header B3 propagator also writes the `X-B3-*`
+/// fields. Wrapping the carrier keeps only the fields the consumer reads, which
+/// matters with SQS's limit of ten message attributes.
+pub struct FilteredInjector<'a> {
+    inner: &'a mut dyn Injector,
+    fields: &'a [&'a str],
+}
+
+impl<'a> FilteredInjector<'a> {
+    /// Creates an injector writing only `fields` into `inner`.
+    pub fn new(inner: &'a mut dyn Injector, fields: &'a [&'a str]) -> Self {
+        Self { inner, fields }
+    }
+}
+
+impl Injector for FilteredInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if self.fields.contains(&key) {
+            self.inner.set(key, value);
+        }
+    }
+}
+
+/// An [`Injector`] that passes every W3C `baggage` entry through a redaction callback
+/// before forwarding the field to another injector.
+///
+/// The callback receives each entry's key and value as they appear on the wire
+/// (percent-encoded, without properties) and returns the value to send, or `None` to
+/// drop the entry. A valid replacement must itself be percent-encoded, which a hex
+/// digest already is. Entry properties are kept for entries that are not dropped, and
+/// the `baggage` field is not written at all when every entry is dropped. Other fields
+/// are forwarded unchanged.
+///
+/// Use this at the boundary with a less-trusted topic, e.g. one with external
+/// subscribers, so baggage meant for internal services does not leave the system.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::core::RedactingInjector;
+///
+/// let mut carrier = MessageAttributesInjector(&mut attributes);
+/// let mut injector = RedactingInjector::new(&mut carrier, |key, value| {
+///     (!key.starts_with("user.")).then(|| value.to_string())
+/// });
+/// propagator.inject_context(&cx, &mut injector);
+/// ```
+pub struct RedactingInjector<'a, F> {
+    inner: &'a mut dyn Injector,
+    redact: F,
+}
+
+impl<'a, F> RedactingInjector<'a, F>
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    /// Creates an injector writing into `inner` with baggage entries passed through
+    /// `redact`.
+    pub fn new(inner: &'a mut dyn Injector, redact: F) -> Self {
+        Self { inner, redact }
+    }
+}
+
+impl<F> Injector for RedactingInjector<'_, F>
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    fn set(&mut self, key: &str, value: String) {
+        if key != "baggage" {
+            self.inner.set(key, value);
+            return;
+        }
+        let entries: Vec<String> = value
+            .split(',')
+            .filter_map(|entry| {
+                let (pair, properties) = match entry.split_once(';') {
+                    Some((pair, properties)) => (pair, Some(properties)),
+                    None => (entry, None),
+                };
+                let (name, value) = pair.split_once('=')?;
+                let name = name.trim();
+                let value = (self.redact)(name, value.trim())?;
+                Some(match properties {
+                    Some(properties) => format!("{name}={value};{properties}"),
+                    None => format!("{name}={value}"),
+                })
+            })
+            .collect();
+        if !entries.is_empty() {
+            self.inner.set(key, entries.join(","));
+        }
+    }
+}
+
+/// The longest field value, in bytes, that [`ChunkingInjector::new`] writes as a
+/// single attribute.
+///
+/// SNS and SQS only bound attribute values by the message size (256 KiB for SNS, 1 MiB
+/// for SQS), but W3C limits a propagated `tracestate` to 512 characters and some
+/// intermediaries cap individual values at a few kilobytes. Values within this size are
+/// never split.
+pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// An [`Injector`] that splits oversized field values across numbered attributes.
+///
+/// A value longer than the chunk size is written as `<key>.0`, `<key>.1`, ... instead
+/// of `<key>`, each holding at most chunk-size bytes; shorter values are forwarded
+/// unchanged. Chunks are cut on UTF-8 character boundaries.
+///
+/// This is a defensive mode for pathological `tracestate` growth, and it needs both
+/// sides to opt in: only a [`ChunkedExtractor`] reassembles the chunks. A consumer
+/// reading the attributes directly finds no `tracestate`, which drops the vendor
+/// state but still continues the trace from `traceparent`. Each chunk is a separate
+/// attribute and counts towards the SQS limit of ten.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::core::ChunkingInjector;
+///
+/// let mut carrier = MessageAttributesInjector(&mut attributes);
+/// propagator.inject_context(&cx, &mut ChunkingInjector::new(&mut carrier));
+/// ```
+pub struct ChunkingInjector<'a> {
+    inner: &'a mut dyn Injector,
+    chunk_size: usize,
+}
+
+impl<'a> ChunkingInjector<'a> {
+    /// Creates an injector writing into `inner` that splits values longer than
+    /// [`DEFAULT_CHUNK_SIZE`].
+    pub fn new(inner: &'a mut dyn Injector) -> Self {
+        Self::with_chunk_size(inner, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates an injector writing into `inner` that splits values longer than
+    /// `chunk_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is smaller than 4 bytes, the longest UTF-8 character.
+    pub fn with_chunk_size(inner: &'a mut dyn Injector, chunk_size: usize) -> Self {
+        assert!(chunk_size >= 4, "chunk size must hold any UTF-8 character");
+        Self { inner, chunk_size }
+    }
+}
+
+impl Injector for ChunkingInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if value.len() <= self.chunk_size {
+            self.inner.set(key, value);
+            return;
+        }
+        let mut rest = value.as_str();
+        let mut index = 0;
+        while !rest.is_empty() {
+            let mut end = self.chunk_size.min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (chunk, tail) = rest.split_at(end);
+            self.inner.set(&format!("{key}.{index}"), chunk.to_string());
+            rest = tail;
+            index += 1;
+        }
+    }
+}
+
+/// An [`Extractor`] that reassembles values split by [`ChunkingInjector`].
+///
+/// Attributes named `<key>.0`, `<key>.1`, ... are joined in order and read as
+/// `<key>`, as long as the numbering starts at 0 and has no gaps; an incomplete set is
+/// ignored. A field present both whole and chunked is read from the whole attribute.
+/// Other fields are read from the inner extractor unchanged.
+pub struct ChunkedExtractor<'a> {
+    inner: &'a dyn Extractor,
+    reassembled: HashMap<String, String>,
+}
+
+impl<'a> ChunkedExtractor<'a> {
+    /// Creates an extractor over `inner`, reassembling its chunked fields.
+    pub fn new(inner: &'a dyn Extractor) -> Self {
+        let mut chunks: HashMap<&str, Vec<(usize, &str)>> = HashMap::new();
+        for name in inner.keys() {
+            let Some((key, index)) = name.rsplit_once('.') else {
+                continue;
+            };
+            // Only plain decimal indices, so `+1` or `01` are not chunk names.
+            if index.is_empty()
+                || !index.bytes().all(|b| b.is_ascii_digit())
+                || (index.len() > 1 && index.starts_with('0'))
+            {
+                continue;
+            }
+            if let (Ok(index), Some(value)) = (index.parse(), inner.get(name)) {
+                chunks.entry(key).or_default().push((index, value));
+            }
+        }
+        let reassembled = chunks
+            .into_iter()
+            .filter(|(key, _)| inner.get(key).is_none())
+            .filter_map(|(key, mut parts)| {
+                parts.sort_unstable_by_key(|(index, _)| *index);
+                let complete = parts.iter().enumerate().all(|(i, (index, _))| i == *index);
+                complete.then(|| {
+                    let value = parts.into_iter().map(|(_, part)| part).collect();
+                    (key.to_string(), value)
+                })
+            })
+            .collect();
+        Self { inner, reassembled }
+    }
+}
+
+impl Extractor for ChunkedExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.inner
+            .get(key)
+            .or_else(|| self.reassembled.get(key).map(String::as_str))
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        let mut keys = self.inner.keys();
+        keys.extend(self.reassembled.keys().map(String::as_str));
+        keys
+    }
+}
+
+/// The prefix of the span attributes written by [`DebugInjector`].
+pub const DEBUG_ATTRIBUTE_PREFIX: &str = "debug.injected.";
+
+/// An [`Injector`] that also records every injected field as an attribute of the span
+/// in a [`Context`].
+///
+/// Each field is recorded as `debug.injected.<field>` (e.g.
+/// `debug.injected.traceparent`), so the trace backend shows exactly what was sent.
+/// The values are written to the span as-is, baggage included; only enable this while
+/// debugging.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::core::DebugInjector;
+///
+/// let mut carrier = MessageAttributesInjector(&mut attributes);
+/// propagator.inject_context(&cx, &mut DebugInjector::new(&mut carrier, &cx));
+/// ```
+pub struct DebugInjector<'a> {
+    inner: &'a mut dyn Injector,
+    cx: &'a Context,
+}
+
+impl<'a> DebugInjector<'a> {
+    /// Creates an injector writing into `inner` and recording on the span in `cx`.
+    pub fn new(inner: &'a mut dyn Injector, cx: &'a Context) -> Self {
+        Self { inner, cx }
+    }
+}
+
+impl Injector for DebugInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.cx.span().set_attribute(KeyValue::new(
+            format!("{DEBUG_ATTRIBUTE_PREFIX}{key}"),
+            value.clone(),
+        ));
+        self.inner.set(key, value);
+    }
+}
+
+/// Trace fields in decreasing priority; fields not listed rank after these.
+#[cfg(any(feature = "sns", feature = "sqs"))]
+const FIELD_PRIORITY: [&str; 3] = ["traceparent", "tracestate", "baggage"];
+
+/// Returns the rank of trace field `key` when attribute space runs out, lower ranks
+/// being kept first: `traceparent`, `tracestate`, `baggage`, then any other field.
+#[cfg(any(feature = "sns", feature = "sqs"))]
+pub(crate) fn field_rank(key: &str) -> usize {
+    FIELD_PRIORITY
+        .iter()
+        .position(|field| *field == key)
+        .unwrap_or(FIELD_PRIORITY.len())
+}
+
+/// Copies the string-valued attributes into an owned `HashMap<String, String>`.
+///
+/// Attributes without a string value are skipped.
+pub fn to_owned_headers<V: AttributeValue>(
+    attributes: &HashMap<String, V>,
+) -> HashMap<String, String> {
+    attributes
+        .iter()
+        .filter_map(|(k, v)| v.string_value().map(|s| (k.clone(), s.to_string())))
+        .collect()
+}
+
+/// Extracts the baggage carried by `extractor` and returns only the entries whose
+/// keys are in `allowlist`.
+///
+/// Upstream services can set any baggage key; reading baggage through an allowlist
+/// keeps a consumer from acting on entries it does not expect. Keys are matched
+/// exactly and entry metadata is dropped. Only the carrier is read, never the baggage
+/// of the context attached on the calling thread. The global propagator is used, so
+/// baggage is only found if it includes a baggage propagator.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::core::allowlisted_baggage;
+///
+/// let baggage = allowlisted_baggage(&SqsMessageAttributesExtractor(attrs), &["tenant", "plan"]);
+/// if baggage.get("plan").map(String::as_str) == Some("enterprise") { /* ... */ }
+/// ```
+pub fn allowlisted_baggage<S: AsRef<str>>(
+    extractor: &dyn Extractor,
+    allowlist: &[S],
+) -> HashMap<String, String> {
+    let cx = global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), extractor)
+    });
+    let baggage = cx.baggage();
+    allowlist
+        .iter()
+        .filter_map(|key| {
+            let key = key.as_ref();
+            Some((key.to_string(), baggage.get(key)?.as_str().to_string()))
+        })
+        .collect()
+}
+
+/// What [`validate_trace_attributes`] does with a value AWS would reject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidValuePolicy {
+    /// Percent-encodes the disallowed characters and keeps the attribute.
+    #[default]
+    Sanitize,
+    /// Fails with [`PropagationError::InvalidAttributeValue`].
+    Reject,
+}
+
+/// Checks the trace fields in `attributes` for characters AWS rejects in `String`
+/// message attribute values, before publishing.
+///
+/// SNS and SQS only accept `#x9 | #xA | #xD | #x20-#xD7FF | #xE000-#xFFFD |
+/// #x10000-#x10FFFF` and fail the whole `Publish` or `SendMessage` call otherwise.
+/// Propagators validate `traceparent`, but a `tracestate` or `baggage` value relayed
+/// from another system can carry control characters. Only the fields of the global
+/// propagator with a string value are checked.
+///
+/// With [`InvalidValuePolicy::Sanitize`], each disallowed character is replaced by the
+/// percent-encoding of its UTF-8 bytes (e.g. `%07`), and `%` itself by `%25` so the
+/// result decodes unambiguously; the attribute keeps its data type. This keeps the
+/// publish from failing but is not reversed on extraction. Returns the names of the
+/// sanitized attributes.
+///
+/// # Errors
+///
+/// With [`InvalidValuePolicy::Reject`], returns
+/// [`PropagationError::InvalidAttributeValue`] for the first invalid field, leaving
+/// `attributes` unchanged.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::core::{validate_trace_attributes, InvalidValuePolicy};
+/// use opentelemetry_aws_messaging::sns;
+///
+/// let mut attributes = sns::inject_context(&cx);
+/// validate_trace_attributes(&mut attributes, InvalidValuePolicy::Reject)?;
+/// ```
+pub fn validate_trace_attributes<V: AttributeValue>(
+    attributes: &mut HashMap<String, V>,
+    policy: InvalidValuePolicy,
+) -> Result<Vec<String>, PropagationError> {
+    let fields: Vec<String> = global::get_text_map_propagator(|propagator| {
+        propagator.fields().map(str::to_string).collect()
+    });
+    let mut invalid: Vec<(String, String)> = Vec::new();
+    for name in fields {
+        let Some(value) = attributes.get(&name).and_then(AttributeValue::string_value) else {
+            continue;
+        };
+        if value.chars().all(is_allowed_char) {
+            continue;
+        }
+        if policy == InvalidValuePolicy::Reject {
+            return Err(PropagationError::InvalidAttributeValue { name });
+        }
+        let mut sanitized = String::with_capacity(value.len());
+        for c in value.chars() {
+            if c == '%' {
+                sanitized.push_str("%25");
+            } else if is_allowed_char(c) {
+                sanitized.push(c);
+            } else {
+                for byte in c.to_string().bytes() {
+                    sanitized.push_str(&format!("%{byte:02X}"));
+                }
+            }
+        }
+        invalid.push((name, sanitized));
+    }
+    Ok(invalid
+        .into_iter()
+        .map(|(name, sanitized)| {
+            if let Some(value) = attributes.get_mut(&name) {
+                *value = value.with_string_value(sanitized);
+            }
+            name
+        })
+        .collect())
+}
+
+fn is_allowed_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal attribute type standing in for a non-AWS transport.
+    #[derive(Debug, PartialEq)]
+    enum Value {
+        Text(String),
+        Bytes(Vec<u8>),
+    }
+
+    impl AttributeValue for Value {
+        fn from_string(value: String) -> Self {
+            Value::Text(value)
+        }
+
+        fn from_binary(value: Vec<u8>) -> Self {
+            Value::Bytes(value)
+        }
+
+        fn string_value(&self) -> Option<&str> {
+            match self {
+                Value::Text(s) => Some(s),
+                Value::Bytes(_) => None,
+            }
+        }
+
+        fn binary_value(&self) -> Option<&[u8]> {
+            match self {
+                Value::Text(_) => None,
+                Value::Bytes(b) => Some(b),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generic_carriers_round_trip() {
+        let mut attrs: HashMap<String, Value> = HashMap::new();
+        AttributeInjector(&mut attrs).set("traceparent", "00-abc123-def456-01".to_string());
+
+        let extractor = AttributeExtractor(&attrs);
+
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+        assert_eq!(extractor.keys(), vec!["traceparent"]);
+    }
+
+    #[test]
+    fn test_generic_injector_skips_empty_value() {
+        use opentelemetry::propagation::TextMapPropagator;
+        use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+        let mut attrs: HashMap<String, Value> = HashMap::new();
+
+        TraceContextPropagator::new().inject_context(
+            &crate::test_util::sampled_context(),
+            &mut AttributeInjector(&mut attrs),
+        );
+
+        assert_eq!(attrs.keys().collect::<Vec<_>>(), ["traceparent"]);
+    }
+
+    #[test]
+    fn test_filtered_injector_drops_other_fields() {
+        let mut attrs: HashMap<String, Value> = HashMap::new();
+        let mut inner = AttributeInjector(&mut attrs);
+        let mut injector = FilteredInjector::new(&mut inner, &["b3"]);
+        injector.set("b3", "abc-def-1".to_string());
+        injector.set("X-B3-TraceId", "abc".to_string());
+
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(attrs["b3"], Value::Text("abc-def-1".to_string()));
+    }
+
+    #[test]
+    fn test_redacting_injector_removes_sensitive_baggage() {
+        use opentelemetry::propagation::TextMapPropagator;
+        use opentelemetry_sdk::propagation::BaggagePropagator;
+
+        let cx = Context::new().with_baggage(vec![
+            KeyValue::new("user.email", "jane@example.com"),
+            KeyValue::new("tenant", "acme"),
+        ]);
+        let mut attrs: HashMap<String, Value> = HashMap::new();
+        let mut inner = AttributeInjector(&mut attrs);
+        let mut injector = RedactingInjector::new(&mut inner, |key, value| {
+            (key != "user.email").then(|| value.to_string())
+        });
+        BaggagePropagator::new().inject_context(&cx, &mut injector);
+
+        assert_eq!(attrs["baggage"], Value::Text("tenant=acme".to_string()));
+
+        let mut attrs: HashMap<String, Value> = HashMap::new();
+        let mut inner = AttributeInjector(&mut attrs);
+        RedactingInjector::new(&mut inner, |_, _| None).set("baggage", "a=1;p".to_string());
+        assert!(attrs.is_empty());
+    }
+
+    #[test]
+    fn test_chunked_tracestate_round_trip() {
+        let tracestate = format!("dd=s:1;o:rum,{}", "vendor=x".repeat(600));
+        let mut attrs: HashMap<String, Value> = HashMap::new();
+        let mut inner = AttributeInjector(&mut attrs);
+        let mut injector = ChunkingInjector::new(&mut inner);
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+        injector.set("tracestate", tracestate.clone());
+
+        let mut names: Vec<&str> = attrs.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["traceparent", "tracestate.0", "tracestate.1"]);
+        assert_eq!(
+            attrs["tracestate.0"],
+            Value::Text(tracestate[..DEFAULT_CHUNK_SIZE].to_string())
+        );
+
+        let plain = AttributeExtractor(&attrs);
+        let extractor = ChunkedExtractor::new(&plain);
+        assert_eq!(extractor.get("tracestate"), Some(tracestate.as_str()));
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+        assert!(extractor.keys().contains(&"tracestate"));
+
+        attrs.remove("tracestate.0");
+        let plain = AttributeExtractor(&attrs);
+        assert_eq!(ChunkedExtractor::new(&plain).get("tracestate"), None);
+    }
+
+    #[test]
+    fn test_allowlisted_baggage_drops_unexpected_keys() {
+        crate::test_util::init_propagator();
+        let mut attrs: HashMap<String, Value> = HashMap::new();
+        attrs.insert(
+            "baggage".to_string(),
+            Value::Text("tenant=acme,plan=enterprise;tier=1,debug=true,admin=yes".to_string()),
+        );
+
+        let baggage =
+            allowlisted_baggage(&AttributeExtractor(&attrs), &["tenant", "plan", "region"]);
+
+        assert_eq!(
+            baggage,
+            HashMap::from([
+                ("tenant".to_string(), "acme".to_string()),
+                ("plan".to_string(), "enterprise".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_allowlisted_baggage_ignores_the_ambient_baggage() {
+        use opentelemetry::baggage::BaggageExt;
+
+        crate::test_util::init_propagator();
+        let _guard = Context::new()
+            .with_baggage(vec![KeyValue::new("tenant", "acme")])
+            .attach();
+        let attrs: HashMap<String, Value> = HashMap::new();
+
+        assert!(allowlisted_baggage(&AttributeExtractor(&attrs), &["tenant"]).is_empty());
+    }
+
+    #[test]
+    fn test_validate_trace_attributes_handles_control_characters() {
+        crate::test_util::init_propagator();
+        let attributes = || {
+            HashMap::from([
+                (
+                    "traceparent".to_string(),
+                    Value::Text("00-abc123-def456-01".to_string()),
+                ),
+                (
+                    "tracestate".to_string(),
+                    Value::Text("vendor=a\u{7}b".to_string()),
+                ),
+                ("order".to_string(), Value::Text("\u{7}".to_string())),
+            ])
+        };
+
+        let mut rejected = attributes();
+        assert_eq!(
+            validate_trace_attributes(&mut rejected, InvalidValuePolicy::Reject),
+            Err(PropagationError::InvalidAttributeValue {
+                name: "tracestate".to_string()
+            })
+        );
+        assert_eq!(rejected, attributes());
+
+        let mut sanitized = attributes();
+        assert_eq!(
+            validate_trace_attributes(&mut sanitized, InvalidValuePolicy::Sanitize).unwrap(),
+            ["tracestate"]
+        );
+        assert_eq!(
+            sanitized["tracestate"],
+            Value::Text("vendor=a%07b".to_string())
+        );
+        assert_eq!(sanitized["order"], Value::Text("\u{7}".to_string()));
+    }
+
+    #[test]
+    fn test_validate_trace_attributes_escapes_percent_when_sanitizing() {
+        crate::test_util::init_propagator();
+        let mut attributes = HashMap::from([
+            (
+                "tracestate".to_string(),
+                Value::Text("vendor=%07\u{7}".to_string()),
+            ),
+            (
+                "baggage".to_string(),
+                Value::Text("ratio=50%25".to_string()),
+            ),
+        ]);
+
+        assert_eq!(
+            validate_trace_attributes(&mut attributes, InvalidValuePolicy::Sanitize).unwrap(),
+            ["tracestate"]
+        );
+        assert_eq!(
+            attributes["tracestate"],
+            Value::Text("vendor=%2507%07".to_string())
+        );
+        assert_eq!(
+            attributes["baggage"],
+            Value::Text("ratio=50%25".to_string())
+        );
+    }
+
+    #[test]
+    fn test_encode_and_decode_binary() {
+        let value = Value::encode("00-abc123-def456-01".to_string(), Encoding::Binary);
+
+        assert_eq!(value, Value::Bytes(b"00-abc123-def456-01".to_vec()));
+        assert_eq!(value.decode(Encoding::Binary), Some("00-abc123-def456-01"));
+        assert_eq!(value.decode(Encoding::String), None);
+    }
+}
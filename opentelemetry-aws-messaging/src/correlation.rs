@@ -0,0 +1,41 @@
+//! Log correlation with the propagated traces.
+//!
+//! Operators correlate logs with traces through the trace id. Recording
+//! [`trace_id_hex`] as a field on the consumer's log span (the `tracing` equivalent
+//! of a logging MDC) makes every log line emitted while processing a message
+//! searchable by the trace it belongs to:
+//!
+//! ```ignore
+//! use opentelemetry_aws_messaging::correlation::trace_id_hex;
+//!
+//! let span = tracing::info_span!(
+//!     "sqs.process",
+//!     trace_id = trace_id_hex(&parent_cx).unwrap_or_default()
+//! );
+//! ```
+
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::Context;
+
+/// Returns the trace id of the span in `cx` as 32 lowercase hex characters, or `None`
+/// if `cx` holds no valid span context.
+pub fn trace_id_hex(cx: &Context) -> Option<String> {
+    let span = cx.span();
+    let span_cx = span.span_context();
+    span_cx.is_valid().then(|| span_cx.trace_id().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sampled_context;
+
+    #[test]
+    fn test_trace_id_hex() {
+        assert_eq!(
+            trace_id_hex(&sampled_context()).as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+        assert_eq!(trace_id_hex(&Context::new()), None);
+    }
+}
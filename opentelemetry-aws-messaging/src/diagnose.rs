@@ -0,0 +1,174 @@
+//! A one-call report of a carrier's propagation state.
+//!
+//! When a trace breaks, the first questions are always the same: which trace fields
+//! arrived, do they parse into a valid context, where did the context come from, and
+//! was it sampled. [`diagnose`] answers them for any [`Extractor`], and
+//! [`sqs::diagnose_message`](crate::sqs::diagnose_message) also tells the SQS sources
+//! apart. The [`PropagationReport`] is `Debug` and, with the `json` feature,
+//! `Serialize`, so it can be printed or logged as one structured record.
+
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::{global, Context};
+
+/// Where the context in a [`PropagationReport`] was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "json",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ContextSource {
+    /// The carrier passed to [`diagnose`], whose origin is not known.
+    Carrier,
+    /// The SQS message attributes, e.g. with raw message delivery.
+    MessageAttributes,
+    /// The SNS envelope in the SQS body, without raw message delivery.
+    SnsEnvelope,
+    /// An X-Ray `AWSTraceHeader`, as a system or message attribute.
+    XRay,
+}
+
+/// The propagation state of a carrier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct PropagationReport {
+    /// The trace fields present, sorted by name.
+    pub fields: Vec<String>,
+    /// Whether the fields parse into a valid span context.
+    pub valid: bool,
+    /// The trace id of the context, when valid.
+    pub trace_id: Option<String>,
+    /// The span id of the context, when valid.
+    pub span_id: Option<String>,
+    /// The sampling decision of the context, when valid.
+    pub sampled: Option<bool>,
+    /// Where the context was found, when valid.
+    pub source: Option<ContextSource>,
+}
+
+impl PropagationReport {
+    pub(crate) fn new(mut fields: Vec<String>, cx: &Context, source: ContextSource) -> Self {
+        fields.sort_unstable();
+        fields.dedup();
+        let span = cx.span();
+        let span_cx = span.span_context();
+        let valid = span_cx.is_valid();
+        Self {
+            fields,
+            valid,
+            trace_id: valid.then(|| span_cx.trace_id().to_string()),
+            span_id: valid.then(|| span_cx.span_id().to_string()),
+            sampled: valid.then(|| span_cx.is_sampled()),
+            source: valid.then_some(source),
+        }
+    }
+}
+
+/// Returns the fields of the global propagator that `extractor` carries.
+pub(crate) fn present_fields(extractor: &dyn Extractor) -> Vec<String> {
+    global::get_text_map_propagator(|propagator| {
+        propagator
+            .fields()
+            .filter(|field| extractor.get(field).is_some())
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+/// Reports the propagation state of `extractor` with the global propagator.
+///
+/// Only the carrier is inspected; a span active on the calling thread is never
+/// reported as its context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::diagnose::diagnose;
+///
+/// let report = diagnose(&SqsMessageAttributesExtractor(attrs));
+/// tracing::debug!(?report, "propagation state");
+/// ```
+pub fn diagnose(extractor: &dyn Extractor) -> PropagationReport {
+    let cx = global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), extractor)
+    });
+    PropagationReport::new(present_fields(extractor), &cx, ContextSource::Carrier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, traceparent};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_diagnose_reports_fields_and_sampling() {
+        init_propagator();
+        let carrier = HashMap::from([
+            (
+                "traceparent".to_string(),
+                traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+            ),
+            ("baggage".to_string(), "tenant=acme".to_string()),
+            ("order-id".to_string(), "42".to_string()),
+        ]);
+
+        let report = diagnose(&carrier);
+
+        assert_eq!(
+            report,
+            PropagationReport {
+                fields: vec!["baggage".to_string(), "traceparent".to_string()],
+                valid: true,
+                trace_id: Some("4bf92f3577b34da6a3ce929d0e0e4736".to_string()),
+                span_id: Some("00f067aa0ba902b7".to_string()),
+                sampled: Some(true),
+                source: Some(ContextSource::Carrier),
+            }
+        );
+    }
+
+    #[test]
+    fn test_diagnose_invalid_context() {
+        init_propagator();
+        let carrier = HashMap::from([("traceparent".to_string(), "garbage".to_string())]);
+
+        let report = diagnose(&carrier);
+
+        assert_eq!(report.fields, ["traceparent"]);
+        assert!(!report.valid);
+        assert_eq!(report.trace_id, None);
+        assert_eq!(report.source, None);
+    }
+
+    #[test]
+    fn test_diagnose_ignores_the_ambient_context() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+
+        let report = diagnose(&HashMap::<String, String>::new());
+
+        assert!(!report.valid);
+        assert_eq!(report.trace_id, None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_report_serializes() {
+        init_propagator();
+        let report = diagnose(&HashMap::<String, String>::new());
+
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "fields": [],
+                "valid": false,
+                "trace_id": null,
+                "span_id": null,
+                "sampled": null,
+                "source": null,
+            })
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Diagnostics for traces that break between publish and consume.
+//!
+//! Comparing what the producer injected with what the consumer received turns "the
+//! trace is broken" into a concrete finding such as "`tracestate` was dropped between
+//! hops". Only the fields of the global propagator are compared, so business
+//! attributes do not add noise.
+
+use crate::core::{to_owned_headers, AttributeValue};
+use opentelemetry::global;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A trace field whose value differs between the two sides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedField {
+    /// The propagator field name.
+    pub key: String,
+    /// The value that was injected.
+    pub injected: String,
+    /// The value that was received.
+    pub received: String,
+}
+
+/// The differences between injected and received trace fields.
+///
+/// Each list is sorted by field name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceAttrDiff {
+    /// Fields that were injected but did not arrive.
+    pub missing: Vec<String>,
+    /// Fields that arrived but were not injected.
+    pub added: Vec<String>,
+    /// Fields present on both sides with different values.
+    pub changed: Vec<ChangedField>,
+}
+
+impl TraceAttrDiff {
+    /// Returns `true` if the trace fields arrived unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for TraceAttrDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "trace attributes unchanged");
+        }
+        let mut parts = Vec::new();
+        if !self.missing.is_empty() {
+            parts.push(format!("missing: {}", self.missing.join(", ")));
+        }
+        if !self.added.is_empty() {
+            parts.push(format!("added: {}", self.added.join(", ")));
+        }
+        for changed in &self.changed {
+            parts.push(format!(
+                "changed: {} ({:?} -> {:?})",
+                changed.key, changed.injected, changed.received
+            ));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// Compares the trace fields `injected` by a producer with those `received` by a
+/// consumer.
+///
+/// The two maps may use different attribute types (e.g. SNS on the producer side and
+/// SQS on the consumer side); both are compared through their string values. Only the
+/// fields reported by the global propagator are considered.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::diff::diff_trace_attributes;
+///
+/// let diff = diff_trace_attributes(&published_attributes, msg.message_attributes().unwrap());
+/// if !diff.is_empty() {
+///     eprintln!("trace propagation issue: {diff}");
+/// }
+/// ```
+pub fn diff_trace_attributes<I, R>(
+    injected: &HashMap<String, I>,
+    received: &HashMap<String, R>,
+) -> TraceAttrDiff
+where
+    I: AttributeValue,
+    R: AttributeValue,
+{
+    let injected = to_owned_headers(injected);
+    let received = to_owned_headers(received);
+    let fields: Vec<String> = global::get_text_map_propagator(|propagator| {
+        propagator.fields().map(str::to_string).collect()
+    });
+
+    let mut diff = TraceAttrDiff::default();
+    for key in fields {
+        match (injected.get(&key), received.get(&key)) {
+            (Some(_), None) => diff.missing.push(key),
+            (None, Some(_)) => diff.added.push(key),
+            (Some(injected), Some(received)) if injected != received => {
+                diff.changed.push(ChangedField {
+                    key,
+                    injected: injected.clone(),
+                    received: received.clone(),
+                })
+            }
+            _ => {}
+        }
+    }
+    diff.missing.sort();
+    diff.added.sort();
+    diff.changed.sort_by(|a, b| a.key.cmp(&b.key));
+    diff
+}
+
+#[cfg(all(test, feature = "sns", feature = "sqs"))]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, traceparent};
+    use aws_sdk_sns::types::MessageAttributeValue as SnsValue;
+    use aws_sdk_sqs::types::MessageAttributeValue as SqsValue;
+
+    fn attrs<V: AttributeValue>(fields: &[(&str, String)]) -> HashMap<String, V> {
+        fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), V::from_string(v.clone())))
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_fields_have_no_diff() {
+        init_propagator();
+        let fields = [(
+            "traceparent",
+            traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+        )];
+
+        let diff = diff_trace_attributes(&attrs::<SnsValue>(&fields), &attrs::<SqsValue>(&fields));
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_reports_missing_added_and_changed_fields() {
+        init_propagator();
+        let injected = attrs::<SnsValue>(&[
+            (
+                "traceparent",
+                traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+            ),
+            ("tracestate", "dd=s:1".to_string()),
+            ("event_type", "order.created".to_string()),
+        ]);
+        let received = attrs::<SqsValue>(&[
+            (
+                "traceparent",
+                traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "b7ad6b7169203331"),
+            ),
+            ("baggage", "user=1".to_string()),
+        ]);
+
+        let diff = diff_trace_attributes(&injected, &received);
+
+        assert_eq!(diff.missing, vec!["tracestate"]);
+        assert_eq!(diff.added, vec!["baggage"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].key, "traceparent");
+        assert!(diff
+            .to_string()
+            .starts_with("missing: tracestate; added: baggage; changed: traceparent"));
+    }
+}
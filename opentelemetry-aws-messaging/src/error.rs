@@ -0,0 +1,89 @@
+//! Errors returned by the strict propagation, validation and injection helpers.
+
+use std::fmt;
+
+/// An error from a propagation helper that does not fall back to best-effort behavior.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropagationError {
+    /// The carrier did not contain a valid span context.
+    MissingContext,
+    /// A trace attribute value contains characters AWS rejects in message attributes.
+    InvalidAttributeValue {
+        /// The attribute name.
+        name: String,
+    },
+    /// Injecting every configured format would exceed the message attribute limit.
+    OverBudget {
+        /// The number of attributes the message would have.
+        required: usize,
+        /// The maximum number of attributes.
+        limit: usize,
+        /// The formats to leave out to fit, lowest priority first.
+        drop: Vec<String>,
+    },
+}
+
+impl fmt::Display for PropagationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropagationError::MissingContext => {
+                write!(f, "carrier does not contain a valid span context")
+            }
+            PropagationError::InvalidAttributeValue { name } => {
+                write!(
+                    f,
+                    "attribute `{name}` contains characters AWS does not accept"
+                )
+            }
+            PropagationError::OverBudget {
+                required,
+                limit,
+                drop,
+            } => {
+                write!(
+                    f,
+                    "injecting every format needs {required} attributes, more than the limit of {limit}"
+                )?;
+                if !drop.is_empty() {
+                    write!(f, "; drop {}", drop.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PropagationError {}
+
+/// An error from the fallible injection path, `MessageAttributesInjector::try_set` in
+/// the `sns` and `sqs` modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectError {
+    /// The value is empty, which AWS rejects for message attributes.
+    EmptyValue {
+        /// The attribute name.
+        key: String,
+    },
+    /// The message attribute value could not be built.
+    Build {
+        /// The attribute name.
+        key: String,
+        /// The builder's error message.
+        message: String,
+    },
+}
+
+impl fmt::Display for InjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InjectError::EmptyValue { key } => {
+                write!(f, "attribute `{key}` has an empty value")
+            }
+            InjectError::Build { key, message } => {
+                write!(f, "attribute `{key}` could not be built: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InjectError {}
@@ -0,0 +1,214 @@
+//! Message context attachment and debug-build detection of leaked contexts.
+//!
+//! A classic consumer bug is holding the guard returned by `Context::attach` across
+//! the processing of the next message, so the previous message's span becomes the
+//! current context and contaminates the next trace. Attaching message contexts with
+//! [`attach_message_context`] tracks the live guards in a thread-local counter; in
+//! debug builds, attaching another message context or starting a consumer span while
+//! one is still attached logs a warning. Release builds skip the bookkeeping.
+//!
+//! [`in_consumer_span`] removes the guard from the caller's hands altogether: the
+//! context stays attached exactly for the duration of a closure.
+
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, ContextGuard};
+use std::borrow::Cow;
+use std::fmt::Display;
+
+#[cfg(debug_assertions)]
+thread_local! {
+    static ATTACHED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Attaches `cx` as the current context for the processing of one message.
+///
+/// The previous context is restored when the returned guard is dropped. Drop it
+/// before moving on to the next message.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::guard::attach_message_context;
+///
+/// for msg in messages {
+///     let _guard = attach_message_context(sqs::extract_isolated(&msg));
+///     handle(&msg)?;
+/// } // `_guard` is dropped at the end of each iteration
+/// ```
+pub fn attach_message_context(cx: Context) -> MessageContextGuard {
+    warn_if_leaked("attach_message_context");
+    #[cfg(debug_assertions)]
+    ATTACHED.with(|attached| attached.set(attached.get() + 1));
+    MessageContextGuard {
+        _guard: cx.attach(),
+    }
+}
+
+/// A guard returned by [`attach_message_context`].
+#[must_use = "dropping the guard detaches the context immediately"]
+pub struct MessageContextGuard {
+    _guard: ContextGuard,
+}
+
+impl Drop for MessageContextGuard {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        ATTACHED.with(|attached| attached.set(attached.get().saturating_sub(1)));
+    }
+}
+
+/// The result of message processing, mapped to the status of its consumer span.
+pub trait SpanOutcome {
+    /// Returns the span status for this outcome.
+    fn status(&self) -> Status;
+}
+
+impl SpanOutcome for () {
+    fn status(&self) -> Status {
+        Status::Unset
+    }
+}
+
+impl<T, E: Display> SpanOutcome for Result<T, E> {
+    fn status(&self) -> Status {
+        match self {
+            Ok(_) => Status::Unset,
+            Err(e) => Status::error(e.to_string()),
+        }
+    }
+}
+
+/// Runs `f` inside a consumer span that continues the context read from `extractor`.
+///
+/// The context is extracted with the global propagator, a `SpanKind::Consumer` span
+/// named `name` is started as its child, and the span's context is attached while `f`
+/// runs. Afterwards the span status is set from `f`'s result (an `Err` marks the span
+/// as an error), the span is ended, and the previous context is restored.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::guard::in_consumer_span;
+/// use opentelemetry_aws_messaging::SqsMessageAttributesExtractor;
+///
+/// for msg in messages {
+///     let extractor = SqsMessageAttributesExtractor::from_message(&msg);
+///     in_consumer_span(&tracer, "orders.process", &extractor, || handle(&msg))?;
+/// }
+/// ```
+pub fn in_consumer_span<T, F, R>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    extractor: &dyn Extractor,
+    f: F,
+) -> R
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+    F: FnOnce() -> R,
+    R: SpanOutcome,
+{
+    let parent_cx = global::get_text_map_propagator(|propagator| propagator.extract(extractor));
+    let span = tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Consumer)
+        .start_with_context(tracer, &parent_cx);
+    let cx = parent_cx.with_span(span);
+
+    let result = {
+        let _guard = attach_message_context(cx.clone());
+        f()
+    };
+
+    let span = cx.span();
+    span.set_status(result.status());
+    span.end();
+    result
+}
+
+/// Logs a warning if a message context guard is still attached on this thread.
+///
+/// Returns whether one was; always `false` in release builds.
+pub(crate) fn warn_if_leaked(operation: &str) -> bool {
+    #[cfg(debug_assertions)]
+    {
+        let attached = ATTACHED.with(|attached| attached.get());
+        if attached > 0 {
+            tracing::warn!(
+                attached,
+                operation,
+                "a message context is still attached; drop its guard before processing the next message"
+            );
+            return true;
+        }
+    }
+    let _ = operation;
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use opentelemetry::trace::TracerProvider;
+    use std::collections::HashMap;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const UPSTREAM_SPAN_ID: &str = "00f067aa0ba902b7";
+
+    fn incoming() -> HashMap<String, String> {
+        HashMap::from([(
+            "traceparent".to_string(),
+            traceparent(TRACE_ID, UPSTREAM_SPAN_ID),
+        )])
+    }
+
+    #[test]
+    fn test_in_consumer_span_attaches_context_for_closure() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+
+        let (trace_id, span_id) =
+            in_consumer_span(&provider.tracer("test"), "process", &incoming(), || {
+                let cx = Context::current();
+                let span_cx = cx.span().span_context().clone();
+                Ok::<_, String>((span_cx.trace_id(), span_cx.span_id()))
+            })
+            .unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(trace_id.to_string(), TRACE_ID);
+        assert_eq!(span_id, spans[0].span_context.span_id());
+        assert_eq!(spans[0].span_kind, SpanKind::Consumer);
+        assert_eq!(spans[0].parent_span_id.to_string(), UPSTREAM_SPAN_ID);
+        assert!(!Context::current().span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_in_consumer_span_marks_error_result() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+
+        let result: Result<(), String> =
+            in_consumer_span(&provider.tracer("test"), "process", &incoming(), || {
+                Err("boom".to_string())
+            });
+
+        assert!(result.is_err());
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].status, Status::error("boom"));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_detects_guard_held_across_messages() {
+        assert!(!warn_if_leaked("test"));
+
+        let first = attach_message_context(Context::new());
+        assert!(warn_if_leaked("test"));
+
+        drop(first);
+        assert!(!warn_if_leaked("test"));
+    }
+}
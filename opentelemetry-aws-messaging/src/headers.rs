@@ -0,0 +1,281 @@
+//! Plain string header maps for store-and-resume workflows.
+//!
+//! Message attributes are tied to the SDK types and cannot be persisted directly. When
+//! processing is deferred across process boundaries (e.g. the trace headers are stored
+//! in a database and a later job resumes the work), the trace fields can be kept as a
+//! plain `HashMap<String, String>` and turned back into a parent [`Context`] here.
+//!
+//! The same applies at the boundary with other transports: [`current_traceparent`]
+//! and [`context_from_traceparent`] convert to and from a flat W3C `traceparent`
+//! string for Kafka headers or Redis fields, and [`context_from_w3c`] also keeps the
+//! `tracestate` for callers that read the fields themselves.
+
+use opentelemetry::trace::{
+    Link, SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+};
+use opentelemetry::{global, Context};
+use std::collections::{BTreeMap, HashMap};
+
+/// Reconstructs a parent [`Context`] from persisted string headers using the global
+/// propagator.
+///
+/// Returns an empty context when the headers carry no valid trace fields.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::headers::context_from_headers;
+///
+/// // Headers previously stored via `sqs::to_owned_headers`
+/// let headers: HashMap<String, String> = load_headers(job_id)?;
+/// let parent_cx = context_from_headers(&headers);
+/// ```
+pub fn context_from_headers(headers: &HashMap<String, String>) -> Context {
+    global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), headers)
+    })
+}
+
+/// Returns the fields the global propagator would write for `cx`, without touching
+/// any real message.
+///
+/// The fields are sorted by name, for tooling that previews the trace context about
+/// to be attached (e.g. a CLI or dashboard).
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::headers::preview_injection;
+///
+/// for (field, value) in preview_injection(&cx) {
+///     println!("{field}: {value}");
+/// }
+/// ```
+pub fn preview_injection(cx: &Context) -> BTreeMap<String, String> {
+    let mut fields = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(cx, &mut fields));
+    fields.into_iter().collect()
+}
+
+/// Builds a [`Link`] to the span referenced by a raw W3C `traceparent` header.
+///
+/// This stitches in external trace references (from logs, a database, or a header)
+/// without going through a propagator. Only the `traceparent` format is parsed; the
+/// link carries no trace state. Returns `None` for malformed input or all-zero ids.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::headers::link_from_traceparent;
+///
+/// let links = link_from_traceparent(&job.origin_traceparent).into_iter().collect();
+/// let span = tracer.span_builder("job.resume").with_links(links).start(&tracer);
+/// ```
+pub fn link_from_traceparent(traceparent: &str) -> Option<Link> {
+    parse_traceparent(traceparent).map(Link::with_context)
+}
+
+/// Returns the W3C `traceparent` value for the span in the current context.
+///
+/// This is the bridge to transports outside the AWS SDKs, e.g. to set a Kafka header
+/// or a Redis field when forwarding an SQS message. The value is always formatted as
+/// W3C trace context, whatever the global propagator. Returns `None` when no valid
+/// span is active.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::headers::current_traceparent;
+///
+/// if let Some(traceparent) = current_traceparent() {
+///     record = record.header("traceparent", traceparent.as_bytes());
+/// }
+/// ```
+pub fn current_traceparent() -> Option<String> {
+    let cx = Context::current();
+    let span = cx.span();
+    let span_cx = span.span_context();
+    span_cx.is_valid().then(|| {
+        format!(
+            "00-{}-{}-{:02x}",
+            span_cx.trace_id(),
+            span_cx.span_id(),
+            span_cx.trace_flags() & TraceFlags::SAMPLED
+        )
+    })
+}
+
+/// Builds a parent [`Context`] from a raw W3C `traceparent` value, the counterpart of
+/// [`current_traceparent`].
+///
+/// The value is parsed like in [`link_from_traceparent`], without going through a
+/// propagator. Returns an empty context for malformed input.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::headers::context_from_traceparent;
+///
+/// let parent_cx = context_from_traceparent(&redis_fields["traceparent"]);
+/// ```
+pub fn context_from_traceparent(traceparent: &str) -> Context {
+    match parse_traceparent(traceparent) {
+        Some(span_context) => Context::new().with_remote_span_context(span_context),
+        None => Context::new(),
+    }
+}
+
+/// Builds a parent [`Context`] from raw W3C `traceparent` and `tracestate` values.
+///
+/// This is [`context_from_traceparent`] keeping the vendor trace state, e.g.
+/// Datadog's `dd=` entry. A malformed `tracestate` is dropped rather than failing the
+/// whole context, as the W3C specification requires.
+pub fn context_from_w3c(traceparent: &str, tracestate: Option<&str>) -> Context {
+    let Some(span_context) = parse_traceparent(traceparent) else {
+        return Context::new();
+    };
+    let trace_state = tracestate
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default();
+    Context::new().with_remote_span_context(SpanContext::new(
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags(),
+        true,
+        trace_state,
+    ))
+}
+
+fn parse_traceparent(traceparent: &str) -> Option<SpanContext> {
+    let parts: Vec<&str> = traceparent.trim().split('-').collect();
+    let [version, trace_id, span_id, flags, rest @ ..] = parts.as_slice() else {
+        return None;
+    };
+    // Version 00 has exactly four fields; later versions may append more.
+    if !is_lower_hex(version, 2) || *version == "ff" || (*version == "00" && !rest.is_empty()) {
+        return None;
+    }
+    if !is_lower_hex(trace_id, 32) || !is_lower_hex(span_id, 16) || !is_lower_hex(flags, 2) {
+        return None;
+    }
+
+    let trace_id = TraceId::from_hex(trace_id).ok()?;
+    let span_id = SpanId::from_hex(span_id).ok()?;
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+    let span_context = SpanContext::new(
+        trace_id,
+        span_id,
+        TraceFlags::new(flags) & TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    span_context.is_valid().then_some(span_context)
+}
+
+fn is_lower_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, traceparent};
+
+    #[test]
+    fn test_context_from_headers_restores_parent() {
+        init_propagator();
+        let mut headers = HashMap::new();
+        headers.insert(
+            "traceparent".to_string(),
+            traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+        );
+
+        let cx = context_from_headers(&headers);
+
+        let span_cx = cx.span().span_context().clone();
+        assert!(span_cx.is_valid());
+        assert!(span_cx.is_remote());
+        assert_eq!(
+            span_cx.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(span_cx.span_id().to_string(), "00f067aa0ba902b7");
+    }
+
+    #[test]
+    fn test_context_from_empty_headers_is_invalid() {
+        init_propagator();
+
+        let cx = context_from_headers(&HashMap::new());
+
+        assert!(!cx.span().span_context().is_valid());
+
+        let _guard = sampled_context().attach();
+        let cx = context_from_headers(&HashMap::new());
+        assert!(!cx.span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_link_from_traceparent() {
+        let link = link_from_traceparent(&traceparent(
+            "4bf92f3577b34da6a3ce929d0e0e4736",
+            "00f067aa0ba902b7",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            link.span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(link.span_context.span_id().to_string(), "00f067aa0ba902b7");
+        assert!(link.span_context.is_sampled());
+        assert!(link.span_context.is_remote());
+    }
+
+    #[test]
+    fn test_link_from_malformed_traceparent_is_none() {
+        for input in [
+            "",
+            "garbage",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-zz",
+        ] {
+            assert!(link_from_traceparent(input).is_none(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_traceparent_bridge_round_trips() {
+        let value = traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7");
+
+        let cx = context_from_traceparent(&value);
+        let _guard = cx.attach();
+
+        assert_eq!(current_traceparent(), Some(value));
+        assert!(!context_from_traceparent("garbage")
+            .span()
+            .span_context()
+            .is_valid());
+    }
+
+    #[test]
+    fn test_preview_injection_lists_fields() {
+        init_propagator();
+
+        let preview = preview_injection(&sampled_context());
+
+        assert_eq!(
+            preview.get("traceparent"),
+            Some(&traceparent(
+                "4bf92f3577b34da6a3ce929d0e0e4736",
+                "00f067aa0ba902b7"
+            ))
+        );
+        assert!(preview_injection(&Context::new()).is_empty());
+    }
+}
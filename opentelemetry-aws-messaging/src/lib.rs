@@ -7,7 +7,24 @@
 //! # Features
 //!
 //! - `sns` - Enables SNS message attribute injection (enabled by default)
-//! - `sqs` - Enables SQS message attribute extraction (enabled by default)
+//! - `sqs` - Enables SQS message attribute injection and extraction (enabled by default)
+//! - `json` - Enables JSON body carriers (enabled by default through `sqs`)
+//! - `consumer` - Enables `TracedSqsConsumer`, a high-level traced SQS consumer
+//! - `publisher` - Enables `TracedSnsPublisher`, a high-level traced SNS publisher
+//! - `datadog-compat` - Enables writing the legacy Datadog `_datadog` SNS attribute
+//! - `tower` - Enables `TraceExtractionLayer`, a `tower` layer for message handlers
+//! - `gzip` - Enables extraction from base64 gzip-compressed SNS envelopes
+//! - `subject` - Enables carrying the context in the SNS `Subject`, as a last resort
+//! - `fifo-relay` - Enables `relay::start_fifo_relay_span`, for relays that republish to SNS
+//!   FIFO topics
+//! - `metrics` - Enables recording SQS queue dwell time as a histogram
+//! - `signed-context` - Enables signing `traceparent` to detect tampering between hops
+//! - `testing` - Enables assertions for end-to-end propagation tests and in-memory SNS and
+//!   SQS clients
+//!
+//! With `default-features = false` only the SDK-independent modules remain: [`core`]
+//! provides generic carriers over any type implementing [`core::AttributeValue`], for
+//! transports other than the AWS SDKs.
 //!
 //! # Example
 //!
@@ -25,6 +42,8 @@
 //! // Use `attributes` in your SNS publish call
 //! ```
 //!
+//! or, for a new map, `let attributes = sns::inject_context(&cx);`.
+//!
 //! ## Consuming from SQS with trace context
 //!
 //! ```ignore
@@ -32,10 +51,75 @@
 //! use opentelemetry_aws_messaging::sqs::MessageAttributesExtractor;
 //!
 //! let parent_cx = global::get_text_map_propagator(|propagator| {
-//!     propagator.extract(&MessageAttributesExtractor(msg.message_attributes()))
+//!     propagator.extract(&MessageAttributesExtractor::from_message(&msg))
 //! });
 //! // Use `parent_cx` to create child spans
 //! ```
+//!
+//! # Other propagation formats
+//!
+//! The carriers work with any `TextMapPropagator`; the producer and consumer must
+//! install the same one. For interop with B3-instrumented services,
+//! `opentelemetry_zipkin::Propagator::with_encoding(B3Encoding::SingleHeader)` writes
+//! the compact single-header format (`b3: {trace}-{span}-{sampled}`). In
+//! `opentelemetry-zipkin` 0.31 it also writes the `X-B3-*` fields, so wrap the carrier
+//! in a [`core::FilteredInjector`] to send `b3` as the only message attribute:
+//!
+//! ```ignore
+//! use opentelemetry_aws_messaging::core::FilteredInjector;
+//! use opentelemetry_zipkin::{B3Encoding, Propagator};
+//!
+//! let propagator = Propagator::with_encoding(B3Encoding::SingleHeader);
+//! let mut attributes = HashMap::new();
+//! propagator.inject_context(
+//!     &cx,
+//!     &mut FilteredInjector::new(&mut MessageAttributesInjector(&mut attributes), &["b3"]),
+//! );
+//! ```
+//!
+//! The consumer extracts with the same propagator and the usual SQS extractor.
+//!
+//! Legacy Jaeger services use `opentelemetry_jaeger_propagator::Propagator`, from the
+//! separate `opentelemetry-jaeger-propagator` crate, on both sides. It writes a single
+//! `uber-trace-id` attribute (`{trace}:{span}:0:{flags}`); the `:` separators are
+//! valid in attribute values, so no special handling is needed. Jaeger baggage
+//! travels as one `uberctx-<key>` attribute per entry and counts toward the limit of
+//! ten message attributes.
+//!
+//! # Interoperability
+//!
+//! The default carriers write each field as a `String` attribute named exactly after
+//! the propagator field, with the value in `StringValue`. This is the format the
+//! OpenTelemetry Python (`botocore`) and Java AWS SDK instrumentations read, so
+//! consumers written with those SDKs continue the trace without configuration, as
+//! long as they request the attributes (`MessageAttributeNames=["All"]`). The
+//! `Binary` encoding, dot escaping, and reserved-safe names of
+//! [`config::CarrierConfig`] are not understood by those SDKs; leave them off when
+//! the consumers are not written with this crate.
+
+pub mod arn;
+#[cfg(feature = "json")]
+pub mod body;
+pub mod config;
+pub mod core;
+pub mod correlation;
+pub mod diagnose;
+pub mod diff;
+pub mod error;
+pub mod guard;
+pub mod headers;
+pub mod link;
+#[cfg(feature = "json")]
+pub mod logs;
+pub mod negotiate;
+pub mod relay;
+pub mod replay;
+pub mod semconv;
+#[cfg(feature = "signed-context")]
+pub mod signed;
+pub mod strict;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 #[cfg(feature = "sns")]
 pub mod sns;
@@ -43,10 +127,37 @@ pub mod sns;
 #[cfg(feature = "sqs")]
 pub mod sqs;
 
+pub mod xray;
+
+#[cfg(all(test, feature = "sns", feature = "sqs"))]
+mod symmetry_tests;
+#[cfg(test)]
+mod test_util;
+
 // Re-exports for convenience
+pub use config::{CarrierConfig, Encoding, DEFAULT_MAX_BODY_SIZE};
+pub use error::{InjectError, PropagationError};
+
 #[cfg(feature = "sns")]
 pub use sns::MessageAttributesInjector as SnsMessageAttributesInjector;
 
+#[cfg(feature = "sns")]
+pub use sns::MessageAttributesExtractor as SnsMessageAttributesExtractor;
+
 #[cfg(feature = "sqs")]
 pub use sqs::MessageAttributesExtractor as SqsMessageAttributesExtractor;
 
+#[cfg(feature = "sqs")]
+pub use sqs::MessageAttributesInjector as SqsMessageAttributesInjector;
+
+#[cfg(feature = "sqs")]
+pub use sqs::SystemAttributesExtractor as SqsSystemAttributesExtractor;
+
+#[cfg(feature = "sqs")]
+pub use sqs::SystemAttributesInjector as SqsSystemAttributesInjector;
+
+#[cfg(feature = "consumer")]
+pub use sqs::consumer::TracedSqsConsumer;
+
+#[cfg(feature = "publisher")]
+pub use sns::publisher::TracedSnsPublisher;
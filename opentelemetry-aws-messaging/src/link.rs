@@ -0,0 +1,235 @@
+//! Linking, rather than parenting, to the upstream context.
+//!
+//! A consumer span is usually a child of the producer span, which reads well when the
+//! message is processed as soon as it is received. When work is decoupled from receive,
+//! e.g. messages are buffered internally and processed later in batches, that
+//! relationship misleads: the processing span would appear to belong to a trace it
+//! may outlive by minutes, and one span often handles several upstream messages.
+//!
+//! Prefer a link on a fresh root span when:
+//!
+//! - processing is deferred or buffered after the receive,
+//! - one span processes messages from several upstream traces,
+//! - the upstream trace should not grow with long-running downstream work.
+//!
+//! Keep parent-child for inline, one-message-at-a-time processing, where it gives the
+//! most readable end-to-end trace.
+//!
+//! Both can also be combined: [`start_consumer_span`] parents the consumer span to the
+//! producer and, optionally, links it to the producer as well. The link is redundant
+//! for the trace structure but useful with backends that render links more
+//! prominently than parents, e.g. as a "caused by" jump from the consumer span, or
+//! that drop the parent when the producer span arrives late or is sampled out.
+
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::{Link, SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+/// Extracts the upstream span context from `extractor` with the global propagator and
+/// returns it as a [`Link`].
+///
+/// Returns `None` when the carrier holds no valid span context, even if a span is
+/// active on the calling thread. For a raw
+/// `traceparent` string, use
+/// [`link_from_traceparent`](crate::headers::link_from_traceparent) instead.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::link::extract_as_link;
+/// use opentelemetry_aws_messaging::sqs::CompositeExtractor;
+///
+/// let links = batch
+///     .iter()
+///     .filter_map(|msg| extract_as_link(&CompositeExtractor::from_message(msg)))
+///     .collect();
+/// let span = tracer.span_builder("batch.process").with_links(links).start(&tracer);
+/// ```
+pub fn extract_as_link(extractor: &dyn Extractor) -> Option<Link> {
+    let cx = global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), extractor)
+    });
+    let span_cx = cx.span().span_context().clone();
+    span_cx.is_valid().then(|| Link::with_context(span_cx))
+}
+
+/// Extracts one link per carrier in a batch, skipping carriers without a valid
+/// context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::link::extract_links;
+/// use opentelemetry_aws_messaging::sqs::CompositeExtractor;
+///
+/// let extractors: Vec<_> = batch.iter().map(CompositeExtractor::from_message).collect();
+/// let links = extract_links(extractors.iter().map(|e| e as &dyn Extractor));
+/// ```
+pub fn extract_links<'a>(extractors: impl IntoIterator<Item = &'a dyn Extractor>) -> Vec<Link> {
+    extractors.into_iter().filter_map(extract_as_link).collect()
+}
+
+/// Like [`extract_links`], but keeps a single link per upstream span.
+///
+/// When one upstream span publishes several messages that land in the same batch,
+/// [`extract_links`] would link the batch span to the same parent repeatedly. Links
+/// are deduplicated by trace id and span id, keeping the first occurrence.
+pub fn extract_unique_links<'a>(
+    extractors: impl IntoIterator<Item = &'a dyn Extractor>,
+) -> Vec<Link> {
+    let mut seen = HashSet::new();
+    extract_links(extractors)
+        .into_iter()
+        .filter(|link| seen.insert((link.span_context.trace_id(), link.span_context.span_id())))
+        .collect()
+}
+
+/// Starts a `SpanKind::Consumer` span as a child of the context extracted from
+/// `extractor` with the global propagator.
+///
+/// With `link_producer`, the producer's span context is also attached as a link
+/// tagged `messaging.link.source = "producer"`, matching the link of
+/// [`start_process_span`](crate::sqs::operation::start_process_span). No link is
+/// added when the carrier holds no valid context. The returned context holds the span;
+/// the caller ends it once the message is handled.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::TraceContextExt;
+/// use opentelemetry_aws_messaging::link::start_consumer_span;
+///
+/// let cx = start_consumer_span(&tracer, "orders.process", &SqsMessageAttributesExtractor(attrs), true);
+/// handle(&msg).with_context(cx.clone()).await?;
+/// cx.span().end();
+/// ```
+pub fn start_consumer_span<T>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    extractor: &dyn Extractor,
+    link_producer: bool,
+) -> Context
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let parent_cx = global::get_text_map_propagator(|propagator| propagator.extract(extractor));
+    let producer_span_cx = parent_cx.span().span_context().clone();
+
+    let mut builder = tracer.span_builder(name).with_kind(SpanKind::Consumer);
+    if link_producer && producer_span_cx.is_valid() {
+        builder = builder.with_links(vec![Link::new(
+            producer_span_cx,
+            vec![KeyValue::new("messaging.link.source", "producer")],
+            0,
+        )]);
+    }
+    let span = builder.start_with_context(tracer, &parent_cx);
+    parent_cx.with_span(span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, test_tracer, traceparent};
+    use opentelemetry::trace::TracerProvider;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_extract_as_link() {
+        init_propagator();
+        let headers = HashMap::from([(
+            "traceparent".to_string(),
+            traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+        )]);
+
+        let link = extract_as_link(&headers).unwrap();
+
+        assert_eq!(
+            link.span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(link.span_context.span_id().to_string(), "00f067aa0ba902b7");
+        assert!(link.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_extract_as_link_without_context_is_none() {
+        init_propagator();
+
+        assert!(extract_as_link(&HashMap::<String, String>::new()).is_none());
+        let _guard = sampled_context().attach();
+        assert!(extract_as_link(&HashMap::<String, String>::new()).is_none());
+    }
+
+    #[test]
+    fn test_extract_unique_links_deduplicates_shared_parent() {
+        init_propagator();
+        let carrier = |span_id: &str| {
+            HashMap::from([(
+                "traceparent".to_string(),
+                traceparent("4bf92f3577b34da6a3ce929d0e0e4736", span_id),
+            )])
+        };
+        let batch = [
+            carrier("00f067aa0ba902b7"),
+            carrier("00f067aa0ba902b7"),
+            HashMap::new(),
+        ];
+        let extractors = || batch.iter().map(|c| c as &dyn Extractor);
+
+        assert_eq!(extract_links(extractors()).len(), 2);
+        assert_eq!(extract_unique_links(extractors()).len(), 1);
+
+        let distinct = [carrier("00f067aa0ba902b7"), carrier("b7ad6b7169203331")];
+        assert_eq!(
+            extract_unique_links(distinct.iter().map(|c| c as &dyn Extractor)).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_extract_links_skips_untraced_carriers_under_an_active_span() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+        let batch = [HashMap::<String, String>::new(), HashMap::new()];
+        let extractors = || batch.iter().map(|c| c as &dyn Extractor);
+
+        assert!(extract_links(extractors()).is_empty());
+        assert!(extract_unique_links(extractors()).is_empty());
+    }
+
+    #[test]
+    fn test_start_consumer_span_links_producer() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let tracer = provider.tracer("test");
+        let headers = HashMap::from([(
+            "traceparent".to_string(),
+            traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+        )]);
+
+        start_consumer_span(&tracer, "linked", &headers, true)
+            .span()
+            .end();
+        start_consumer_span(&tracer, "unlinked", &headers, false)
+            .span()
+            .end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let (linked, unlinked) = (&spans[0], &spans[1]);
+        assert_eq!(linked.span_kind, SpanKind::Consumer);
+        assert_eq!(linked.parent_span_id.to_string(), "00f067aa0ba902b7");
+        let link = &linked.links.links[0];
+        assert_eq!(
+            link.span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(link.span_context.span_id().to_string(), "00f067aa0ba902b7");
+        assert!(link.span_context.is_remote());
+        assert_eq!(unlinked.parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert!(unlinked.links.links.is_empty());
+    }
+}
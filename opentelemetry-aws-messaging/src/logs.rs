@@ -0,0 +1,157 @@
+//! Context extraction for CloudWatch Logs subscription filter deliveries.
+//!
+//! A subscription filter delivers batches of log events as gzip-compressed, base64
+//! JSON (the `awslogs.data` field of a Lambda event, or the record data of a Kinesis
+//! or Firehose destination). Once decoded, the payload has this shape:
+//!
+//! ```text
+//! {"messageType":"DATA_MESSAGE","logGroup":"/aws/lambda/orders","logStream":"...",
+//!  "logEvents":[{"id":"3719...","timestamp":1700000000123,
+//!                "message":"{\"level\":\"info\",\"traceparent\":\"00-4bf9...-01\"}"}]}
+//! ```
+//!
+//! When the application writes structured (JSON) logs that include its trace context,
+//! each log event can be processed under the trace that produced it. The name of the
+//! log field holding the W3C `traceparent` value is configurable, since logging
+//! libraries differ (`traceparent`, `otel.traceparent`, `trace_context`, ...).
+
+use opentelemetry::global;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::Context;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A decoded CloudWatch Logs subscription filter payload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogsData {
+    /// `DATA_MESSAGE`, or `CONTROL_MESSAGE` for the destination reachability check.
+    pub message_type: String,
+    /// The log group the events come from.
+    pub log_group: String,
+    /// The log stream the events come from.
+    pub log_stream: String,
+    /// The delivered log events.
+    pub log_events: Vec<LogEvent>,
+}
+
+/// A single log event in a [`LogsData`] payload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LogEvent {
+    /// The log event id.
+    pub id: String,
+    /// The event time, in milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    /// The raw log line.
+    pub message: String,
+}
+
+/// An [`Extractor`] exposing a single `traceparent` value.
+struct TraceparentExtractor<'a>(Option<&'a str>);
+
+impl Extractor for TraceparentExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        (key == "traceparent").then_some(self.0?)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.map(|_| "traceparent").into_iter().collect()
+    }
+}
+
+/// Extracts a parent [`Context`] per log event from the W3C `traceparent` value stored
+/// in the top-level `field` of its JSON message, using the global propagator.
+///
+/// Events whose message is not a JSON object, or has no string `field`, are still
+/// returned, paired with an empty context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::logs::{self, LogsData};
+///
+/// let data: LogsData = serde_json::from_slice(&decoded)?;
+/// for (event, parent_cx) in logs::extract_log_events(&data.log_events, "traceparent") {
+///     let _guard = parent_cx.attach();
+///     // Process `event` within the trace that logged it
+/// }
+/// ```
+pub fn extract_log_events<'a>(events: &'a [LogEvent], field: &str) -> Vec<(&'a LogEvent, Context)> {
+    events
+        .iter()
+        .map(|event| {
+            let message = serde_json::from_str::<Value>(&event.message).ok();
+            let traceparent = message
+                .as_ref()
+                .and_then(|message| message.get(field))
+                .and_then(Value::as_str);
+            let cx = global::get_text_map_propagator(|propagator| {
+                propagator.extract_with_context(&Context::new(), &TraceparentExtractor(traceparent))
+            });
+            (event, cx)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, traceparent};
+    use opentelemetry::trace::TraceContextExt;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    #[test]
+    fn test_extract_log_events_reads_configured_field() {
+        init_propagator();
+        let payload = serde_json::json!({
+            "messageType": "DATA_MESSAGE",
+            "logGroup": "/aws/lambda/orders",
+            "logStream": "2024/01/01/[$LATEST]abc",
+            "logEvents": [
+                {
+                    "id": "1",
+                    "timestamp": 1_700_000_000_123_i64,
+                    "message": serde_json::json!({
+                        "level": "info",
+                        "otel.traceparent": traceparent(TRACE_ID, "00f067aa0ba902b7"),
+                    })
+                    .to_string(),
+                },
+                {"id": "2", "timestamp": 1_700_000_000_124_i64, "message": "START RequestId: 42"},
+            ],
+        });
+        let data: LogsData = serde_json::from_value(payload).unwrap();
+
+        let events = extract_log_events(&data.log_events, "otel.traceparent");
+
+        assert_eq!(data.log_group, "/aws/lambda/orders");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0.id, "1");
+        assert_eq!(
+            events[0].1.span().span_context().trace_id().to_string(),
+            TRACE_ID
+        );
+        assert!(!events[1].1.span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_untraced_log_events_ignore_the_ambient_context() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+        let data: LogsData = serde_json::from_value(serde_json::json!({
+            "messageType": "DATA_MESSAGE",
+            "logGroup": "/aws/lambda/orders",
+            "logStream": "2024/01/01/[$LATEST]abc",
+            "logEvents": [
+                {"id": "1", "timestamp": 1_700_000_000_123_i64, "message": "START RequestId: 42"},
+            ],
+        }))
+        .unwrap();
+
+        let events = extract_log_events(&data.log_events, "otel.traceparent");
+
+        assert!(!events[0].1.span().span_context().is_valid());
+    }
+}
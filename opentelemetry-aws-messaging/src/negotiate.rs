@@ -0,0 +1,434 @@
+//! Propagation-format negotiation through a message attribute.
+//!
+//! In mixed fleets, producers instrumented with different tracers propagate context in
+//! different formats. A producer can advertise the format it used in the
+//! [`FORMAT_ATTRIBUTE`] attribute (`w3c`, `xray`, `b3`, ...); a
+//! [`FormatNegotiator`] on the consumer side reads it and extracts with the matching
+//! propagator, so heterogeneous producers and consumers interoperate without
+//! out-of-band coordination.
+//!
+//! When the consumers of a topic cannot negotiate, e.g. one subscriber reads W3C and
+//! another X-Ray, a [`MultiFormatInjector`] writes every format at once and checks
+//! the combined fields against the message attribute limit.
+
+use crate::core::{AttributeInjector, AttributeValue};
+use crate::error::PropagationError;
+use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::Context;
+use std::collections::HashMap;
+
+/// The message attribute naming the propagation format used by the producer.
+pub const FORMAT_ATTRIBUTE: &str = "otel-format";
+
+/// The format name for W3C trace context.
+pub const FORMAT_W3C: &str = "w3c";
+
+/// The format name for the AWS X-Ray trace header.
+pub const FORMAT_XRAY: &str = "xray";
+
+/// The format name for Zipkin B3.
+pub const FORMAT_B3: &str = "b3";
+
+/// Selects a propagator by the format a message advertises in [`FORMAT_ATTRIBUTE`].
+///
+/// When the attribute is absent or names an unknown format, every configured
+/// propagator is tried in registration order and the first valid span context wins.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::negotiate::{FormatNegotiator, FORMAT_B3, FORMAT_W3C};
+///
+/// let negotiator = FormatNegotiator::new()
+///     .with_format(FORMAT_W3C, TraceContextPropagator::new())
+///     .with_format(FORMAT_B3, opentelemetry_zipkin::Propagator::new());
+///
+/// let parent_cx = negotiator.extract(&SqsMessageAttributesExtractor(attrs));
+/// ```
+#[derive(Default)]
+pub struct FormatNegotiator {
+    propagators: Vec<(String, Box<dyn TextMapPropagator + Send + Sync>)>,
+}
+
+impl FormatNegotiator {
+    /// Creates a negotiator without any formats.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `propagator` for the format named `format`.
+    pub fn with_format(
+        mut self,
+        format: impl Into<String>,
+        propagator: impl TextMapPropagator + Send + Sync + 'static,
+    ) -> Self {
+        self.propagators.push((format.into(), Box::new(propagator)));
+        self
+    }
+
+    /// Injects `cx` with the propagator registered for `format` and advertises the
+    /// format in [`FORMAT_ATTRIBUTE`].
+    ///
+    /// Nothing is written when no propagator is registered for `format`.
+    pub fn inject(&self, format: &str, cx: &Context, injector: &mut dyn Injector) {
+        if let Some(propagator) = self.propagator(format) {
+            propagator.inject_context(cx, injector);
+            injector.set(FORMAT_ATTRIBUTE, format.to_string());
+        }
+    }
+
+    /// Extracts a parent [`Context`] from `extractor`, using the advertised format
+    /// when it is registered and trying every propagator otherwise.
+    ///
+    /// Returns an empty context when no propagator finds a valid span context; the
+    /// context attached on the calling thread is never merged in.
+    pub fn extract(&self, extractor: &dyn Extractor) -> Context {
+        if let Some(propagator) = extractor
+            .get(FORMAT_ATTRIBUTE)
+            .and_then(|format| self.propagator(format))
+        {
+            return propagator.extract_with_context(&Context::new(), extractor);
+        }
+        self.propagators
+            .iter()
+            .map(|(_, propagator)| propagator.extract_with_context(&Context::new(), extractor))
+            .find(|cx| cx.span().span_context().is_valid())
+            .unwrap_or_default()
+    }
+
+    fn propagator(&self, format: &str) -> Option<&(dyn TextMapPropagator + Send + Sync)> {
+        self.propagators
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(format.trim()))
+            .map(|(_, propagator)| propagator.as_ref())
+    }
+}
+
+/// Injects a context in several formats at once, within an attribute budget.
+///
+/// Formats are registered in decreasing priority. Formats sharing a field, e.g. the
+/// `baggage` of two W3C-based propagators, count it once.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::negotiate::{MultiFormatInjector, FORMAT_W3C, FORMAT_XRAY};
+///
+/// let injector = MultiFormatInjector::new()
+///     .with_format(FORMAT_W3C, TraceContextPropagator::new())
+///     .with_format(FORMAT_XRAY, opentelemetry_aws::trace::XrayPropagator::new());
+/// injector.inject(&cx, &mut attributes, sqs::MAX_MESSAGE_ATTRIBUTES)?;
+/// ```
+#[derive(Default)]
+pub struct MultiFormatInjector {
+    propagators: Vec<(String, Box<dyn TextMapPropagator + Send + Sync>)>,
+}
+
+impl MultiFormatInjector {
+    /// Creates an injector without any formats.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `propagator` for the format named `format`, at a lower priority than
+    /// the formats registered before it.
+    pub fn with_format(
+        mut self,
+        format: impl Into<String>,
+        propagator: impl TextMapPropagator + Send + Sync + 'static,
+    ) -> Self {
+        self.propagators.push((format.into(), Box::new(propagator)));
+        self
+    }
+
+    /// Injects `cx` into `attributes` with every registered format, if the result has
+    /// no more than `limit` attributes.
+    ///
+    /// The attributes already present count towards `limit`; fields they already hold
+    /// are overwritten rather than counted again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PropagationError::OverBudget`] without writing anything when the
+    /// fields do not fit. Its `drop` lists the lowest-priority formats whose fields
+    /// would have to be left out, e.g. by registering fewer formats.
+    pub fn inject<V: AttributeValue>(
+        &self,
+        cx: &Context,
+        attributes: &mut HashMap<String, V>,
+        limit: usize,
+    ) -> Result<(), PropagationError> {
+        let formats: Vec<(&str, HashMap<String, String>)> = self
+            .propagators
+            .iter()
+            .map(|(format, propagator)| {
+                let mut fields = FieldCollector::default();
+                propagator.inject_context(cx, &mut fields);
+                (format.as_str(), fields.0)
+            })
+            .collect();
+        let required = |formats: &[(&str, HashMap<String, String>)]| {
+            let mut names: Vec<&str> = attributes.keys().map(String::as_str).collect();
+            for name in formats.iter().flat_map(|(_, fields)| fields.keys()) {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+            names.len()
+        };
+
+        let total = required(&formats);
+        if total > limit {
+            let kept = (0..formats.len())
+                .rev()
+                .find(|&kept| required(&formats[..kept]) <= limit)
+                .unwrap_or(0);
+            return Err(PropagationError::OverBudget {
+                required: total,
+                limit,
+                drop: formats[kept..]
+                    .iter()
+                    .rev()
+                    .map(|(format, _)| format.to_string())
+                    .collect(),
+            });
+        }
+        let mut injector = AttributeInjector(attributes);
+        for (key, value) in formats.into_iter().flat_map(|(_, fields)| fields) {
+            injector.set(&key, value);
+        }
+        Ok(())
+    }
+}
+
+/// Collects injected fields with their names as written; the `HashMap` injector
+/// lowercases them.
+///
+/// Empty values, such as the `tracestate` of a context without trace state, are left
+/// out: AWS rejects them, so they are neither counted nor written.
+#[derive(Default)]
+struct FieldCollector(HashMap<String, String>);
+
+impl Injector for FieldCollector {
+    fn set(&mut self, key: &str, value: String) {
+        if !value.is_empty() {
+            self.0.insert(key.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{sampled_context, traceparent};
+    use crate::xray;
+    use opentelemetry::propagation::text_map_propagator::FieldIter;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use std::collections::HashMap;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const SPAN_ID: &str = "00f067aa0ba902b7";
+
+    /// Minimal X-Ray propagator over the crate's trace header parser.
+    #[derive(Debug)]
+    struct XrayPropagator;
+
+    impl TextMapPropagator for XrayPropagator {
+        fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+            let span = cx.span();
+            let span_cx = span.span_context();
+            if span_cx.is_valid() {
+                injector.set(xray::TRACE_HEADER, xray::format_trace_header(span_cx));
+            }
+        }
+
+        fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+            extractor
+                .get(xray::TRACE_HEADER)
+                .and_then(xray::parse_trace_header)
+                .map(|span_cx| cx.with_remote_span_context(span_cx))
+                .unwrap_or_else(|| cx.clone())
+        }
+
+        fn fields(&self) -> FieldIter<'_> {
+            FieldIter::new(&[])
+        }
+    }
+
+    fn negotiator() -> FormatNegotiator {
+        FormatNegotiator::new()
+            .with_format(FORMAT_W3C, TraceContextPropagator::new())
+            .with_format(FORMAT_XRAY, XrayPropagator)
+            .with_format(FORMAT_B3, opentelemetry_zipkin::Propagator::new())
+    }
+
+    fn extracted_ids(carrier: &HashMap<String, String>) -> (String, String) {
+        let cx = negotiator().extract(carrier);
+        let span_cx = cx.span().span_context().clone();
+        (
+            span_cx.trace_id().to_string(),
+            span_cx.span_id().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_advertised_format_selects_propagator() {
+        let w3c = HashMap::from([
+            (FORMAT_ATTRIBUTE.to_string(), FORMAT_W3C.to_string()),
+            ("traceparent".to_string(), traceparent(TRACE_ID, SPAN_ID)),
+        ]);
+        let xray = HashMap::from([
+            (FORMAT_ATTRIBUTE.to_string(), FORMAT_XRAY.to_string()),
+            // The `HashMap` extractor lowercases the keys it looks up.
+            (
+                xray::TRACE_HEADER.to_lowercase(),
+                format!("Root=1-4bf92f35-77b34da6a3ce929d0e0e4736;Parent={SPAN_ID};Sampled=1"),
+            ),
+        ]);
+        let b3 = HashMap::from([
+            (FORMAT_ATTRIBUTE.to_string(), FORMAT_B3.to_string()),
+            ("b3".to_string(), format!("{TRACE_ID}-{SPAN_ID}-1")),
+        ]);
+
+        for carrier in [w3c, xray, b3] {
+            assert_eq!(
+                extracted_ids(&carrier),
+                (TRACE_ID.to_string(), SPAN_ID.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_advertised_format_does_not_fall_back() {
+        let carrier = HashMap::from([
+            (FORMAT_ATTRIBUTE.to_string(), FORMAT_B3.to_string()),
+            ("traceparent".to_string(), traceparent(TRACE_ID, SPAN_ID)),
+        ]);
+
+        assert!(!negotiator()
+            .extract(&carrier)
+            .span()
+            .span_context()
+            .is_valid());
+    }
+
+    #[test]
+    fn test_missing_format_tries_every_propagator() {
+        let carrier = HashMap::from([("b3".to_string(), format!("{TRACE_ID}-{SPAN_ID}-1"))]);
+
+        assert_eq!(
+            extracted_ids(&carrier),
+            (TRACE_ID.to_string(), SPAN_ID.to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_ignores_the_ambient_context() {
+        let _guard = sampled_context().attach();
+        let advertised = HashMap::from([(FORMAT_ATTRIBUTE.to_string(), FORMAT_W3C.to_string())]);
+
+        for carrier in [advertised, HashMap::new()] {
+            assert!(!negotiator()
+                .extract(&carrier)
+                .span()
+                .span_context()
+                .is_valid());
+        }
+    }
+
+    #[test]
+    fn test_inject_advertises_format() {
+        let mut carrier = HashMap::new();
+        negotiator().inject(FORMAT_W3C, &sampled_context(), &mut carrier);
+
+        assert_eq!(
+            carrier.get(FORMAT_ATTRIBUTE).map(String::as_str),
+            Some("w3c")
+        );
+        assert_eq!(
+            carrier.get("traceparent"),
+            Some(&traceparent(TRACE_ID, SPAN_ID))
+        );
+    }
+
+    #[cfg(feature = "sqs")]
+    fn multi_format() -> MultiFormatInjector {
+        MultiFormatInjector::new()
+            .with_format(FORMAT_W3C, TraceContextPropagator::new())
+            .with_format(FORMAT_XRAY, XrayPropagator)
+    }
+
+    #[cfg(feature = "sqs")]
+    #[test]
+    fn test_multi_format_writes_every_format() {
+        let mut attributes = HashMap::new();
+
+        multi_format()
+            .inject(&sampled_context(), &mut attributes, 10)
+            .unwrap();
+
+        let value = |name: &str| {
+            let value: &aws_sdk_sqs::types::MessageAttributeValue = &attributes[name];
+            value.string_value().unwrap().to_string()
+        };
+        assert_eq!(value("traceparent"), traceparent(TRACE_ID, SPAN_ID));
+        assert!(!attributes.contains_key("tracestate"));
+        assert_eq!(
+            value(xray::TRACE_HEADER),
+            format!("Root=1-4bf92f35-77b34da6a3ce929d0e0e4736;Parent={SPAN_ID};Sampled=1")
+        );
+    }
+
+    #[cfg(feature = "sqs")]
+    #[test]
+    fn test_multi_format_over_budget_suggests_formats_to_drop() {
+        use aws_sdk_sqs::types::MessageAttributeValue;
+
+        let business = || -> HashMap<String, MessageAttributeValue> {
+            (0..7)
+                .map(|i| {
+                    (
+                        format!("attr-{i}"),
+                        AttributeValue::from_string(i.to_string()),
+                    )
+                })
+                .collect()
+        };
+        let mut attributes = business();
+
+        let err = multi_format()
+            .inject(&sampled_context(), &mut attributes, 8)
+            .unwrap_err();
+
+        // traceparent and X-Amzn-Trace-Id on top of 7 attributes; the empty tracestate
+        // is not written.
+        assert_eq!(
+            err,
+            PropagationError::OverBudget {
+                required: 9,
+                limit: 8,
+                drop: vec![FORMAT_XRAY.to_string()],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "injecting every format needs 9 attributes, more than the limit of 8; drop xray"
+        );
+        assert_eq!(attributes, business());
+
+        let mut fits = business();
+        multi_format()
+            .inject(&sampled_context(), &mut fits, 9)
+            .unwrap();
+        assert_eq!(fits.len(), 9);
+
+        let err = multi_format()
+            .inject(&sampled_context(), &mut attributes, 7)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PropagationError::OverBudget { drop, .. } if drop == [FORMAT_XRAY, FORMAT_W3C]
+        ));
+    }
+}
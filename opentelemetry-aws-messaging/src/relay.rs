@@ -0,0 +1,393 @@
+//! Context propagation for workers that receive, transform, and forward messages.
+//!
+//! In multi-hop topologies (e.g. SQS → worker → SNS → SQS) the intermediate worker
+//! both consumes and produces. It is modelled as a single internal span: a child of
+//! the incoming message's context, whose own context is injected into the outgoing
+//! message. Each hop can optionally also link the incoming context, leaving a
+//! breadcrumb that survives even if a backend drops the parent-child relationship.
+//!
+//! To detect accidental message loops in fan-out/fan-in topologies, each hop can also
+//! stamp a [`HOP_COUNT_ATTRIBUTE`] counter next to the trace fields with
+//! [`inject_with_hop_count`]; consumers surface it on their span with
+//! [`hop_count_attribute`].
+//!
+//! Workers that forward a copy of the received message's attributes call [`reinject`]
+//! so the copy carries their own span instead of the stale upstream context.
+//!
+//! Relays that republish to an SNS FIFO topic use [`start_fifo_relay_span`]
+//! (`fifo-relay` feature), which derives the `MessageDeduplicationId` from the body
+//! alone so the fresh trace attributes never defeat deduplication.
+
+use crate::core::{try_insert_string, AttributeValue};
+use crate::error::InjectError;
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::{Link, SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Starts a relay span for a message received via `incoming` and injects its context
+/// into `outgoing`.
+///
+/// The span is `SpanKind::Internal` and a child of the extracted incoming context.
+/// With `link_incoming`, the incoming span context is also attached as a link tagged
+/// `messaging.relay.direction = "in"`. The returned context holds the relay span;
+/// the caller ends it once the outgoing message has been sent.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::TraceContextExt;
+/// use opentelemetry_aws_messaging::relay::start_relay_span;
+/// use opentelemetry_aws_messaging::{SnsMessageAttributesInjector, SqsMessageAttributesExtractor};
+///
+/// let mut outgoing = HashMap::new();
+/// let cx = start_relay_span(
+///     &tracer,
+///     "orders.enrich",
+///     &SqsMessageAttributesExtractor(attrs),
+///     &mut SnsMessageAttributesInjector(&mut outgoing),
+///     true,
+/// );
+/// sns.publish().set_message_attributes(Some(outgoing)) /* ... */;
+/// cx.span().end();
+/// ```
+pub fn start_relay_span<T>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    incoming: &dyn Extractor,
+    outgoing: &mut dyn Injector,
+    link_incoming: bool,
+) -> Context
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let parent_cx = global::get_text_map_propagator(|propagator| propagator.extract(incoming));
+    let incoming_span_cx = parent_cx.span().span_context().clone();
+
+    let mut builder = tracer.span_builder(name).with_kind(SpanKind::Internal);
+    if link_incoming && incoming_span_cx.is_valid() {
+        builder = builder.with_links(vec![Link::new(
+            incoming_span_cx,
+            vec![KeyValue::new("messaging.relay.direction", "in")],
+            0,
+        )]);
+    }
+    let span = builder.start_with_context(tracer, &parent_cx);
+    let cx = parent_cx.with_span(span);
+
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&cx, outgoing));
+    cx
+}
+
+/// Starts a relay span like [`start_relay_span`] for a worker republishing `body` to
+/// an SNS FIFO topic, and returns it along with the `MessageDeduplicationId` to send.
+///
+/// Each republish injects a new relay span, so the trace attributes differ between
+/// two republishes of the same message. The deduplication id is therefore computed
+/// from `body` only: the hex SHA-256 digest of its UTF-8 bytes, the same digest SNS
+/// uses for content-based deduplication. Republishing an unchanged body within the
+/// five-minute deduplication interval, e.g. after the relay retried a receive, always
+/// yields the same id, whatever the trace context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::TraceContextExt;
+/// use opentelemetry_aws_messaging::relay::start_fifo_relay_span;
+///
+/// let mut outgoing = HashMap::new();
+/// let (cx, deduplication_id) = start_fifo_relay_span(
+///     &tracer,
+///     "orders.forward",
+///     &SqsMessageAttributesExtractor(attrs),
+///     &mut SnsMessageAttributesInjector(&mut outgoing),
+///     &body,
+///     true,
+/// );
+/// sns.publish()
+///     .topic_arn(&fifo_topic_arn)
+///     .message(&body)
+///     .message_group_id(group_id)
+///     .message_deduplication_id(deduplication_id)
+///     .set_message_attributes(Some(outgoing))
+///     .send()
+///     .await?;
+/// cx.span().end();
+/// ```
+#[cfg(feature = "fifo-relay")]
+pub fn start_fifo_relay_span<T>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    incoming: &dyn Extractor,
+    outgoing: &mut dyn Injector,
+    body: &str,
+    link_incoming: bool,
+) -> (Context, String)
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    use sha2::{Digest, Sha256};
+
+    let cx = start_relay_span(tracer, name, incoming, outgoing, link_incoming);
+    let deduplication_id = Sha256::digest(body.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    (cx, deduplication_id)
+}
+
+/// Replaces the trace fields in `outgoing` with the current context, using the global
+/// propagator.
+///
+/// A worker that forwards a received message usually copies its attributes, trace
+/// fields included. Injecting on top of the copy would leave fields the current
+/// context does not write (e.g. an upstream `baggage`) describing the old request,
+/// and a differently-cased copy such as `Traceparent` next to the new one. This first
+/// removes every attribute named after one of the propagator's fields, ignoring case,
+/// then injects [`Context::current`], so the forwarded message reflects the worker's
+/// span. Empty fields, such as the `tracestate` of a context without trace state,
+/// are not written since AWS rejects them.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::FutureExt;
+/// use opentelemetry_aws_messaging::relay::reinject;
+///
+/// async {
+///     let mut outgoing = msg.message_attributes().cloned().unwrap_or_default();
+///     reinject(&mut outgoing);
+///     sqs.send_message().set_message_attributes(Some(outgoing)) /* ... */;
+/// }
+/// .with_context(worker_cx)
+/// .await;
+/// ```
+pub fn reinject<V: AttributeValue>(outgoing: &mut HashMap<String, V>) {
+    global::get_text_map_propagator(|propagator| {
+        let fields: Vec<&str> = propagator.fields().collect();
+        outgoing.retain(|name, _| !fields.iter().any(|f| f.eq_ignore_ascii_case(name)));
+        let mut injected = HashMap::new();
+        propagator.inject_context(&Context::current(), &mut injected);
+        for (key, value) in injected {
+            match try_insert_string(outgoing, &key, value) {
+                Ok(()) | Err(InjectError::EmptyValue { .. }) => {}
+                Err(e) => tracing::warn!(error = %e, "skipping trace context attribute"),
+            }
+        }
+    });
+}
+
+/// The message attribute counting how many hops a message has travelled.
+pub const HOP_COUNT_ATTRIBUTE: &str = "otel-hop-count";
+
+/// The span attribute recording the hop count of the message being processed.
+pub const HOP_COUNT_SPAN_ATTRIBUTE: &str = "messaging.hop_count";
+
+/// Reads the hop count from `incoming`, if present and a valid number.
+pub fn hop_count(incoming: &dyn Extractor) -> Option<u64> {
+    incoming.get(HOP_COUNT_ATTRIBUTE)?.trim().parse().ok()
+}
+
+/// Injects `cx` with the global propagator and writes the incremented hop count.
+///
+/// The count is one more than the count read from `incoming`, or 1 for the first hop
+/// (no `incoming` message, or one without a valid count). Returns the written count.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::relay::inject_with_hop_count;
+///
+/// let hops = inject_with_hop_count(
+///     &cx,
+///     Some(&SqsMessageAttributesExtractor(attrs)),
+///     &mut SnsMessageAttributesInjector(&mut outgoing),
+/// );
+/// if hops > 20 {
+///     tracing::warn!(hops, "possible message loop");
+/// }
+/// ```
+pub fn inject_with_hop_count(
+    cx: &Context,
+    incoming: Option<&dyn Extractor>,
+    outgoing: &mut dyn Injector,
+) -> u64 {
+    let count = incoming.and_then(hop_count).unwrap_or(0).saturating_add(1);
+    global::get_text_map_propagator(|propagator| propagator.inject_context(cx, outgoing));
+    outgoing.set(HOP_COUNT_ATTRIBUTE, count.to_string());
+    count
+}
+
+/// Returns the hop count of `incoming` as a [`HOP_COUNT_SPAN_ATTRIBUTE`] span
+/// attribute, or `None` when the message carries no count.
+pub fn hop_count_attribute(incoming: &dyn Extractor) -> Option<KeyValue> {
+    let count = i64::try_from(hop_count(incoming)?).unwrap_or(i64::MAX);
+    Some(KeyValue::new(HOP_COUNT_SPAN_ATTRIBUTE, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use opentelemetry::trace::TracerProvider;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const UPSTREAM_SPAN_ID: &str = "00f067aa0ba902b7";
+
+    fn incoming() -> HashMap<String, String> {
+        HashMap::from([(
+            "traceparent".to_string(),
+            traceparent(TRACE_ID, UPSTREAM_SPAN_ID),
+        )])
+    }
+
+    #[test]
+    fn test_relay_continues_trace_and_injects_own_span() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let mut outgoing = HashMap::new();
+
+        let cx = start_relay_span(
+            &provider.tracer("test"),
+            "relay",
+            &incoming(),
+            &mut outgoing,
+            false,
+        );
+        let relay_span_id = cx.span().span_context().span_id();
+        cx.span().end();
+
+        assert_eq!(
+            outgoing.get("traceparent"),
+            Some(&traceparent(TRACE_ID, &relay_span_id.to_string()))
+        );
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].span_kind, SpanKind::Internal);
+        assert_eq!(spans[0].parent_span_id.to_string(), UPSTREAM_SPAN_ID);
+        assert!(spans[0].links.is_empty());
+    }
+
+    #[cfg(feature = "fifo-relay")]
+    #[test]
+    fn test_fifo_relay_deduplication_id_is_stable_across_republishes() {
+        init_propagator();
+        let (provider, _exporter) = test_tracer();
+        let tracer = provider.tracer("test");
+        let body = r#"{"order_id":42}"#;
+        let other_incoming = HashMap::from([(
+            "traceparent".to_string(),
+            traceparent("0af7651916cd43dd8448eb211c80319c", "b7ad6b7169203331"),
+        )]);
+
+        let mut first = HashMap::new();
+        let (_, first_id) =
+            start_fifo_relay_span(&tracer, "relay", &incoming(), &mut first, body, false);
+        let mut second = HashMap::new();
+        let (_, second_id) =
+            start_fifo_relay_span(&tracer, "relay", &other_incoming, &mut second, body, false);
+        let (_, changed_id) = start_fifo_relay_span(
+            &tracer,
+            "relay",
+            &incoming(),
+            &mut HashMap::new(),
+            r#"{"order_id":43}"#,
+            false,
+        );
+
+        assert_ne!(first.get("traceparent"), second.get("traceparent"));
+        assert_eq!(first_id, second_id);
+        assert_ne!(first_id, changed_id);
+        assert_eq!(
+            start_fifo_relay_span(&tracer, "relay", &incoming(), &mut first, "", false).1,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[cfg(feature = "sqs")]
+    #[test]
+    fn test_reinject_replaces_stale_trace_fields() {
+        use aws_sdk_sqs::types::MessageAttributeValue;
+        use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState};
+
+        init_propagator();
+        let attribute = |value: &str| MessageAttributeValue::from_string(value.to_string());
+        let mut outgoing = HashMap::from([
+            (
+                "Traceparent".to_string(),
+                attribute(&traceparent(TRACE_ID, UPSTREAM_SPAN_ID)),
+            ),
+            ("tracestate".to_string(), attribute("dd=s:1")),
+            ("tenant".to_string(), attribute("acme")),
+        ]);
+        let worker_cx = Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_hex(TRACE_ID).unwrap(),
+            SpanId::from_hex("b7ad6b7169203331").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        ));
+
+        let _guard = worker_cx.attach();
+        reinject(&mut outgoing);
+
+        let mut names: Vec<_> = outgoing.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["tenant", "traceparent"]);
+        assert!(!outgoing.contains_key("tracestate"));
+        assert_eq!(
+            outgoing["traceparent"].string_value(),
+            Some(traceparent(TRACE_ID, "b7ad6b7169203331").as_str())
+        );
+    }
+
+    #[test]
+    fn test_hop_count_increments_across_hops() {
+        init_propagator();
+        let cx = crate::test_util::sampled_context();
+
+        let mut first = HashMap::new();
+        assert_eq!(inject_with_hop_count(&cx, None, &mut first), 1);
+        let mut second = HashMap::new();
+        assert_eq!(inject_with_hop_count(&cx, Some(&first), &mut second), 2);
+        let mut third = HashMap::new();
+        assert_eq!(inject_with_hop_count(&cx, Some(&second), &mut third), 3);
+
+        assert_eq!(
+            third.get(HOP_COUNT_ATTRIBUTE).map(String::as_str),
+            Some("3")
+        );
+        assert!(third.contains_key("traceparent"));
+        assert_eq!(
+            hop_count_attribute(&third),
+            Some(KeyValue::new(HOP_COUNT_SPAN_ATTRIBUTE, 3))
+        );
+        assert_eq!(hop_count_attribute(&incoming()), None);
+    }
+
+    #[test]
+    fn test_relay_links_incoming_context_when_enabled() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let mut outgoing = HashMap::new();
+
+        let cx = start_relay_span(
+            &provider.tracer("test"),
+            "relay",
+            &incoming(),
+            &mut outgoing,
+            true,
+        );
+        cx.span().end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let link = &spans[0].links.links[0];
+        assert_eq!(link.span_context.span_id().to_string(), UPSTREAM_SPAN_ID);
+        assert_eq!(
+            link.attributes,
+            vec![KeyValue::new("messaging.relay.direction", "in")]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Consumer spans for messages replayed from an SNS FIFO topic archive.
+//!
+//! SNS message archiving redelivers historical messages with their original message
+//! attributes, trace fields included. Continuing those traces would attach today's
+//! processing to a trace whose spans were exported, and possibly expired, long ago.
+//! A replayed message should instead start a new trace that links to the original.
+//!
+//! SNS does not mark replayed deliveries itself, so the replay is recognized by a
+//! marker message attribute, [`DEFAULT_REPLAY_MARKER`] unless configured otherwise.
+//! Add it on the replay path, e.g. as a message attribute set by the tool that starts
+//! the replay or on the subscription used for it. [`ReplayConfig::start_span`] then
+//! starts a root span linked to the original context for marked messages and a child
+//! span for live ones.
+
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::{Link, SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use std::borrow::Cow;
+
+/// The attribute marking a replayed message by default.
+pub const DEFAULT_REPLAY_MARKER: &str = "messaging.replay";
+
+/// The span attribute set to `true` on spans of replayed messages.
+pub const REPLAY_SPAN_ATTRIBUTE: &str = "messaging.replay";
+
+/// Which attribute marks a message as replayed.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::TraceContextExt;
+/// use opentelemetry_aws_messaging::replay::ReplayConfig;
+///
+/// let cx = ReplayConfig::default().start_span(&tracer, "orders.process", &SqsMessageAttributesExtractor(attrs));
+/// handle(&msg).with_context(cx.clone()).await?;
+/// cx.span().end();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    marker: String,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            marker: DEFAULT_REPLAY_MARKER.to_string(),
+        }
+    }
+}
+
+impl ReplayConfig {
+    /// Recognizes replays by `attribute` instead of [`DEFAULT_REPLAY_MARKER`].
+    pub fn with_marker(mut self, attribute: impl Into<String>) -> Self {
+        self.marker = attribute.into();
+        self
+    }
+
+    /// Returns `true` when `extractor` carries the replay marker with any value other
+    /// than `false`.
+    pub fn is_replay(&self, extractor: &dyn Extractor) -> bool {
+        extractor
+            .get(&self.marker)
+            .is_some_and(|value| !value.eq_ignore_ascii_case("false"))
+    }
+
+    /// Starts a `SpanKind::Consumer` span for a message read through `extractor`.
+    ///
+    /// A live message's span is a child of the context extracted with the global
+    /// propagator, and a new root when the message carries none, whatever context is
+    /// attached on the calling thread. A replayed message's span is a new root instead:
+    /// it links to the original span context, when valid, with
+    /// `messaging.link.source = "replay"`, and has [`REPLAY_SPAN_ATTRIBUTE`] set to
+    /// `true`. The returned context holds the span; the caller ends it once the message
+    /// is handled.
+    pub fn start_span<T>(
+        &self,
+        tracer: &T,
+        name: impl Into<Cow<'static, str>>,
+        extractor: &dyn Extractor,
+    ) -> Context
+    where
+        T: Tracer,
+        T::Span: Send + Sync + 'static,
+    {
+        let original_cx = global::get_text_map_propagator(|propagator| {
+            propagator.extract_with_context(&Context::new(), extractor)
+        });
+        let builder = tracer.span_builder(name).with_kind(SpanKind::Consumer);
+        if !self.is_replay(extractor) {
+            let span = builder.start_with_context(tracer, &original_cx);
+            return original_cx.with_span(span);
+        }
+
+        let original_span_cx = original_cx.span().span_context().clone();
+        let mut builder = builder.with_attributes([KeyValue::new(REPLAY_SPAN_ATTRIBUTE, true)]);
+        if original_span_cx.is_valid() {
+            builder = builder.with_links(vec![Link::new(
+                original_span_cx,
+                vec![KeyValue::new("messaging.link.source", "replay")],
+                0,
+            )]);
+        }
+        let root_cx = Context::new();
+        let span = builder.start_with_context(tracer, &root_cx);
+        root_cx.with_span(span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, test_tracer, traceparent};
+    use opentelemetry::trace::{SpanId, TracerProvider};
+    use std::collections::HashMap;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    fn carrier(marker: Option<&str>) -> HashMap<String, String> {
+        let mut carrier = HashMap::from([(
+            "traceparent".to_string(),
+            traceparent(TRACE_ID, "00f067aa0ba902b7"),
+        )]);
+        if let Some(marker) = marker {
+            carrier.insert("replayed".to_string(), marker.to_string());
+        }
+        carrier
+    }
+
+    #[test]
+    fn test_replayed_message_starts_linked_root() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let config = ReplayConfig::default().with_marker("replayed");
+
+        config
+            .start_span(&provider.tracer("test"), "process", &carrier(Some("true")))
+            .span()
+            .end();
+
+        let span = &exporter.get_finished_spans().unwrap()[0];
+        assert_eq!(span.parent_span_id, SpanId::INVALID);
+        assert_ne!(span.span_context.trace_id().to_string(), TRACE_ID);
+        assert_eq!(
+            span.links.links[0].span_context.span_id().to_string(),
+            "00f067aa0ba902b7"
+        );
+        assert!(span
+            .attributes
+            .contains(&KeyValue::new(REPLAY_SPAN_ATTRIBUTE, true)));
+    }
+
+    #[test]
+    fn test_live_message_continues_trace() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let config = ReplayConfig::default().with_marker("replayed");
+
+        for marker in [None, Some("false")] {
+            config
+                .start_span(&provider.tracer("test"), "process", &carrier(marker))
+                .span()
+                .end();
+        }
+
+        for span in exporter.get_finished_spans().unwrap() {
+            assert_eq!(span.span_context.trace_id().to_string(), TRACE_ID);
+            assert_eq!(span.parent_span_id.to_string(), "00f067aa0ba902b7");
+            assert!(span.links.links.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_untraced_replay_is_not_linked_to_the_ambient_span() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let config = ReplayConfig::default().with_marker("replayed");
+        let untraced = HashMap::from([("replayed".to_string(), "true".to_string())]);
+
+        let _guard = sampled_context().attach();
+        config
+            .start_span(&provider.tracer("test"), "process", &untraced)
+            .span()
+            .end();
+
+        let span = &exporter.get_finished_spans().unwrap()[0];
+        assert_eq!(span.parent_span_id, SpanId::INVALID);
+        assert!(span.links.links.is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+//! Messaging semantic convention attributes for SNS and SQS spans.
+//!
+//! Backends categorize spans as queue spans by their `messaging.*` attributes: APM
+//! views such as Datadog's group spans by `messaging.system` and
+//! `messaging.destination.name`, and spans without them show up as generic internal
+//! work. The helpers here return the standard keys, plus the `cloud.*` attributes
+//! from [`arn`](crate::arn), for spans started by hand:
+//!
+//! ```ignore
+//! use opentelemetry_aws_messaging::semconv;
+//!
+//! let span = tracer
+//!     .span_builder("publish orders")
+//!     .with_kind(SpanKind::Producer)
+//!     .with_attributes(semconv::sns_producer_attributes(&topic_arn))
+//!     .start(&tracer);
+//! ```
+//!
+//! Both the current `messaging.operation.type` and the older `messaging.operation`
+//! name are set, since some dashboards still group by the latter.
+
+use crate::arn::{cloud_attributes, parse_arn, queue_url_cloud_attributes};
+use opentelemetry::KeyValue;
+
+/// The span attribute recording the messaging operation type.
+pub const OPERATION_TYPE_ATTRIBUTE: &str = "messaging.operation.type";
+
+/// The pre-1.26 semantic conventions name of [`OPERATION_TYPE_ATTRIBUTE`].
+pub const LEGACY_OPERATION_ATTRIBUTE: &str = "messaging.operation";
+
+/// Returns the topic name of an SNS topic or subscription ARN, or `topic_arn` itself
+/// when it is not an ARN.
+///
+/// `arn:aws:sns:us-east-1:123456789012:orders` and the subscription ARN
+/// `arn:aws:sns:us-east-1:123456789012:orders:<subscription id>` both name `orders`.
+pub fn topic_name(topic_arn: &str) -> &str {
+    match parse_arn(topic_arn) {
+        Some(arn) => arn.resource.split(':').next().unwrap_or(arn.resource),
+        None => topic_arn,
+    }
+}
+
+/// Returns the queue name of an SQS queue URL or queue ARN, or `queue` itself when it
+/// is neither.
+///
+/// `https://sqs.us-east-1.amazonaws.com/123456789012/orders` and
+/// `arn:aws:sqs:us-east-1:123456789012:orders` both name `orders`.
+pub fn queue_name(queue: &str) -> &str {
+    match parse_arn(queue) {
+        Some(arn) => arn.resource,
+        None => queue.rsplit('/').next().unwrap_or(queue),
+    }
+}
+
+/// Returns the attributes of a `SpanKind::Producer` span publishing to `topic_arn`.
+pub fn sns_producer_attributes(topic_arn: &str) -> Vec<KeyValue> {
+    let mut attributes = messaging_attributes("aws_sns", "send", topic_name(topic_arn));
+    attributes.extend(cloud_attributes(topic_arn));
+    attributes
+}
+
+/// Returns the attributes of a `SpanKind::Producer` span sending to `queue`, a queue
+/// URL or queue ARN.
+pub fn sqs_producer_attributes(queue: &str) -> Vec<KeyValue> {
+    sqs_attributes("send", queue)
+}
+
+/// Returns the attributes of a `SpanKind::Consumer` span processing a message from
+/// `queue`, a queue URL or queue ARN.
+pub fn sqs_consumer_attributes(queue: &str) -> Vec<KeyValue> {
+    sqs_attributes("process", queue)
+}
+
+pub(crate) fn sqs_attributes(operation: &'static str, queue: &str) -> Vec<KeyValue> {
+    let mut attributes = messaging_attributes("aws_sqs", operation, queue_name(queue));
+    if parse_arn(queue).is_some() {
+        attributes.extend(cloud_attributes(queue));
+    } else {
+        attributes.extend(queue_url_cloud_attributes(queue));
+    }
+    attributes
+}
+
+fn messaging_attributes(
+    system: &'static str,
+    operation: &'static str,
+    destination: &str,
+) -> Vec<KeyValue> {
+    vec![
+        KeyValue::new("messaging.system", system),
+        KeyValue::new(OPERATION_TYPE_ATTRIBUTE, operation),
+        KeyValue::new(LEGACY_OPERATION_ATTRIBUTE, operation),
+        KeyValue::new("messaging.destination.name", destination.to_string()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_name_from_arns() {
+        assert_eq!(
+            topic_name("arn:aws:sns:us-east-1:123456789012:orders"),
+            "orders"
+        );
+        assert_eq!(
+            topic_name("arn:aws:sns:us-east-1:123456789012:orders.fifo"),
+            "orders.fifo"
+        );
+        assert_eq!(
+            topic_name(
+                "arn:aws:sns:us-east-1:123456789012:orders:1a2b3c4d-5e6f-7a8b-9c0d-1e2f3a4b5c6d"
+            ),
+            "orders"
+        );
+        assert_eq!(topic_name("orders"), "orders");
+    }
+
+    #[test]
+    fn test_queue_name_from_url_and_arn() {
+        assert_eq!(
+            queue_name("https://sqs.us-east-1.amazonaws.com/123456789012/orders"),
+            "orders"
+        );
+        assert_eq!(
+            queue_name("arn:aws:sqs:us-east-1:123456789012:orders"),
+            "orders"
+        );
+        assert_eq!(queue_name("orders"), "orders");
+    }
+
+    #[test]
+    fn test_sns_producer_attributes() {
+        assert_eq!(
+            sns_producer_attributes("arn:aws:sns:eu-west-1:123456789012:orders"),
+            vec![
+                KeyValue::new("messaging.system", "aws_sns"),
+                KeyValue::new(OPERATION_TYPE_ATTRIBUTE, "send"),
+                KeyValue::new(LEGACY_OPERATION_ATTRIBUTE, "send"),
+                KeyValue::new("messaging.destination.name", "orders"),
+                KeyValue::new("cloud.provider", "aws"),
+                KeyValue::new("cloud.region", "eu-west-1"),
+                KeyValue::new("cloud.account.id", "123456789012"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sqs_consumer_attributes_from_url_and_arn() {
+        let from_url =
+            sqs_consumer_attributes("https://sqs.us-east-1.amazonaws.com/123456789012/orders");
+        let from_arn = sqs_consumer_attributes("arn:aws:sqs:us-east-1:123456789012:orders");
+
+        assert_eq!(from_url, from_arn);
+        assert_eq!(from_url[0], KeyValue::new("messaging.system", "aws_sqs"));
+        assert!(from_url.contains(&KeyValue::new(OPERATION_TYPE_ATTRIBUTE, "process")));
+        assert!(from_url.contains(&KeyValue::new("messaging.destination.name", "orders")));
+        assert!(from_url.contains(&KeyValue::new("cloud.account.id", "123456789012")));
+        assert!(sqs_producer_attributes("orders")
+            .contains(&KeyValue::new(OPERATION_TYPE_ATTRIBUTE, "send")));
+    }
+}
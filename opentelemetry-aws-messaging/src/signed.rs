@@ -0,0 +1,237 @@
+//! Detecting trace context modified in transit (`signed-context` feature).
+//!
+//! At an untrusted boundary, e.g. a topic that external accounts can publish to, a
+//! sender can forge or rewrite `traceparent` to attach its messages to someone else's
+//! trace or force sampling. [`SignedInjector`] writes an HMAC-SHA256 of the
+//! `traceparent` value as the [`SIGNATURE_ATTRIBUTE`], and [`verify_and_record`]
+//! checks it on the consumer, adding a [`TAMPERED_EVENT`] span event when the
+//! signature is missing or does not match.
+//!
+//! Only `traceparent` is signed: `tracestate` and `baggage` are vendor and
+//! application data that intermediaries legitimately rewrite.
+//!
+//! # Key management
+//!
+//! Producers and consumers share one secret key. Keep it in a secret store such as
+//! AWS Secrets Manager rather than in configuration, use at least 32 random bytes,
+//! and give each trust boundary its own key. To rotate, verify against both the old
+//! and the new key ([`verify`] once per key) until every producer signs with the new
+//! one. Anyone holding the key can sign, so never hand it to the untrusted side.
+
+use hmac::{Hmac, Mac};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::{Context, KeyValue};
+use sha2::Sha256;
+
+/// The attribute holding the hex HMAC-SHA256 of the `traceparent` value.
+pub const SIGNATURE_ATTRIBUTE: &str = "traceparent-sig";
+
+/// The span event added by [`verify_and_record`] when the signature does not verify.
+pub const TAMPERED_EVENT: &str = "trace_context_tampered";
+
+const TRACEPARENT: &str = "traceparent";
+
+/// The result of verifying the signature of a carrier's `traceparent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// The signature matches the `traceparent`.
+    Valid,
+    /// The carrier has neither a `traceparent` nor a signature.
+    Unsigned,
+    /// The carrier has a `traceparent` but no signature, or a signature without a
+    /// `traceparent`.
+    Missing,
+    /// The signature does not match the `traceparent`.
+    Mismatch,
+}
+
+impl SignatureStatus {
+    fn reason(self) -> Option<&'static str> {
+        match self {
+            SignatureStatus::Valid | SignatureStatus::Unsigned => None,
+            SignatureStatus::Missing => Some("missing"),
+            SignatureStatus::Mismatch => Some("mismatch"),
+        }
+    }
+}
+
+/// An [`Injector`] that signs the `traceparent` field with a shared key.
+///
+/// Every field is forwarded to the inner injector; `traceparent` is also signed and
+/// the signature written as [`SIGNATURE_ATTRIBUTE`], which counts towards the SQS
+/// limit of ten message attributes.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::signed::SignedInjector;
+///
+/// let mut carrier = MessageAttributesInjector(&mut attributes);
+/// propagator.inject_context(&cx, &mut SignedInjector::new(&mut carrier, &key));
+/// ```
+pub struct SignedInjector<'a> {
+    inner: &'a mut dyn Injector,
+    key: &'a [u8],
+}
+
+impl<'a> SignedInjector<'a> {
+    /// Creates an injector writing into `inner`, signing with `key`.
+    pub fn new(inner: &'a mut dyn Injector, key: &'a [u8]) -> Self {
+        Self { inner, key }
+    }
+}
+
+impl Injector for SignedInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if key == TRACEPARENT {
+            let signature = mac(self.key, &value)
+                .finalize()
+                .into_bytes()
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            self.inner.set(SIGNATURE_ATTRIBUTE, signature);
+        }
+        self.inner.set(key, value);
+    }
+}
+
+/// Verifies the [`SIGNATURE_ATTRIBUTE`] of the `traceparent` in `extractor` against
+/// `key`.
+///
+/// The comparison runs in constant time.
+pub fn verify(extractor: &dyn Extractor, key: &[u8]) -> SignatureStatus {
+    match (
+        extractor.get(TRACEPARENT),
+        extractor.get(SIGNATURE_ATTRIBUTE),
+    ) {
+        (None, None) => SignatureStatus::Unsigned,
+        (Some(traceparent), Some(signature)) => match decode_hex(signature) {
+            Some(signature) if mac(key, traceparent).verify_slice(&signature).is_ok() => {
+                SignatureStatus::Valid
+            }
+            _ => SignatureStatus::Mismatch,
+        },
+        _ => SignatureStatus::Missing,
+    }
+}
+
+/// Verifies the signature in `extractor` with [`verify`] and, when it is missing or
+/// does not match, adds a [`TAMPERED_EVENT`] event to the span in `cx` with a
+/// `reason` attribute of `missing` or `mismatch`.
+///
+/// The extracted context is not discarded: the caller decides whether to continue
+/// the trace, e.g. by starting a new root span that links to it instead.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::signed::{verify_and_record, SignatureStatus};
+///
+/// let extractor = SqsMessageAttributesExtractor(attrs);
+/// let parent_cx = propagator.extract(&extractor);
+/// let cx = parent_cx.with_span(tracer.start_with_context("orders.process", &parent_cx));
+/// if verify_and_record(&extractor, &key, &cx) != SignatureStatus::Valid {
+///     metrics.tampered.add(1, &[]);
+/// }
+/// ```
+pub fn verify_and_record(extractor: &dyn Extractor, key: &[u8], cx: &Context) -> SignatureStatus {
+    let status = verify(extractor, key);
+    if let Some(reason) = status.reason() {
+        cx.span()
+            .add_event(TAMPERED_EVENT, vec![KeyValue::new("reason", reason)]);
+    }
+    status
+}
+
+fn mac(key: &[u8], traceparent: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(traceparent.as_bytes());
+    mac
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{sampled_context, test_tracer, traceparent};
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry::trace::{Tracer, TracerProvider};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use std::collections::HashMap;
+
+    const KEY: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    fn signed_carrier() -> HashMap<String, String> {
+        let mut carrier = HashMap::new();
+        TraceContextPropagator::new().inject_context(
+            &sampled_context(),
+            &mut SignedInjector::new(&mut carrier, KEY),
+        );
+        carrier
+    }
+
+    #[test]
+    fn test_signed_traceparent_verifies() {
+        let carrier = signed_carrier();
+
+        assert_eq!(carrier[SIGNATURE_ATTRIBUTE].len(), 64);
+        assert_eq!(verify(&carrier, KEY), SignatureStatus::Valid);
+        assert_eq!(verify(&carrier, b"another key"), SignatureStatus::Mismatch);
+        assert_eq!(
+            verify(&HashMap::<String, String>::new(), KEY),
+            SignatureStatus::Unsigned
+        );
+    }
+
+    #[test]
+    fn test_tampered_traceparent_records_event() {
+        let (provider, exporter) = test_tracer();
+        let tracer = provider.tracer("test");
+        let mut tampered = signed_carrier();
+        tampered.insert(
+            TRACEPARENT.to_string(),
+            traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "b7ad6b7169203331"),
+        );
+        let mut stripped = signed_carrier();
+        stripped.remove(SIGNATURE_ATTRIBUTE);
+
+        let cx = Context::new().with_span(tracer.start("process"));
+        assert_eq!(
+            verify_and_record(&tampered, KEY, &cx),
+            SignatureStatus::Mismatch
+        );
+        assert_eq!(
+            verify_and_record(&stripped, KEY, &cx),
+            SignatureStatus::Missing
+        );
+        assert_eq!(
+            verify_and_record(&signed_carrier(), KEY, &cx),
+            SignatureStatus::Valid
+        );
+        cx.span().end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let events: Vec<_> = spans[0].events.iter().collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name, TAMPERED_EVENT);
+        assert_eq!(
+            events[0].attributes,
+            vec![KeyValue::new("reason", "mismatch")]
+        );
+        assert_eq!(
+            events[1].attributes,
+            vec![KeyValue::new("reason", "missing")]
+        );
+    }
+}
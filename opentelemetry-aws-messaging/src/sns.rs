@@ -1,12 +1,35 @@
 //! SNS message attribute carrier for OpenTelemetry context propagation.
 //!
 //! This module provides an [`Injector`] implementation that allows injecting
-//! trace context into SNS message attributes.
+//! trace context into SNS message attributes, and [`Extractor`] implementations for
+//! services that receive SNS attribute maps directly (e.g. over an internal relay)
+//! rather than through SQS. The extractors behave exactly like their `sqs`
+//! counterparts: [`MessageAttributesExtractor`] falls back to binary values, a
+//! [`ConfiguredExtractor`] reads only the configured encoding, and list
+//! (`String.Array`) values are never read.
 
+use crate::config::CarrierConfig;
+use crate::core::{
+    field_rank, try_insert_encoded, try_insert_string, try_insert_with, AttributeValue,
+    ConfiguredAttributeExtractor,
+};
+use crate::error::InjectError;
+use aws_sdk_sns::primitives::Blob;
 use aws_sdk_sns::types::MessageAttributeValue;
-use opentelemetry::propagation::Injector;
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::{SpanContext, TraceContextExt, TraceFlags};
+use opentelemetry::{global, Context, KeyValue};
 use std::collections::HashMap;
 
+#[cfg(feature = "publisher")]
+pub mod client;
+#[cfg(feature = "datadog-compat")]
+pub mod datadog;
+#[cfg(feature = "publisher")]
+pub mod publisher;
+#[cfg(feature = "subject")]
+pub mod subject;
+
 /// An [`Injector`] implementation for SNS message attributes.
 ///
 /// Wraps a mutable reference to a `HashMap` of SNS message attributes and
@@ -36,24 +59,731 @@ use std::collections::HashMap;
 ///     .send()
 ///     .await?;
 /// ```
+///
+/// [`Injector::set`] never panics: a value AWS would reject is logged and skipped. Use
+/// [`try_set`](Self::try_set) to handle the error instead.
 pub struct MessageAttributesInjector<'a>(pub &'a mut HashMap<String, MessageAttributeValue>);
 
+impl<'a> MessageAttributesInjector<'a> {
+    /// Creates an injector that keeps `attributes` at no more than `limit` entries,
+    /// counting the attributes already present.
+    ///
+    /// SNS and SQS reject a whole publish with more than 10 message attributes, see
+    /// [`BoundedInjector`]. Pass the limit minus any attributes still to be added
+    /// after injection.
+    pub fn with_capacity_limit(
+        attributes: &'a mut HashMap<String, MessageAttributeValue>,
+        limit: usize,
+    ) -> BoundedInjector<'a> {
+        BoundedInjector {
+            attributes,
+            limit,
+            injected: Vec::new(),
+            dropped: Vec::new(),
+        }
+    }
+
+    /// Returns a builder for an injector with a key prefix or a custom data type.
+    pub fn builder() -> MessageAttributesInjectorBuilder {
+        MessageAttributesInjectorBuilder::default()
+    }
+
+    /// Inserts `value` as a `String` attribute, or returns an error without inserting
+    /// when the value is empty.
+    pub fn try_set(&mut self, key: &str, value: String) -> Result<(), InjectError> {
+        try_insert_string(self.0, key, value)
+    }
+}
+
 impl Injector for MessageAttributesInjector<'_> {
     fn set(&mut self, key: &str, value: String) {
-        self.0.insert(
-            key.to_string(),
-            MessageAttributeValue::builder()
-                .data_type("String")
-                .string_value(value)
-                .build()
-                .expect("MessageAttributeValue build should not fail with valid String data_type"),
-        );
+        if let Err(e) = self.try_set(key, value) {
+            tracing::warn!(error = %e, "skipping trace context attribute");
+        }
+    }
+}
+
+fn string_attribute(
+    key: &str,
+    value: String,
+    data_type: &str,
+) -> Result<MessageAttributeValue, InjectError> {
+    MessageAttributeValue::builder()
+        .data_type(data_type)
+        .string_value(value)
+        .build()
+        .map_err(|e| InjectError::Build {
+            key: key.to_string(),
+            message: e.to_string(),
+        })
+}
+
+/// Builds a [`PrefixedInjector`] that namespaces the trace attributes.
+///
+/// Keeping trace fields under a prefix such as `x-trace-` keeps them apart from the
+/// business attributes of a message. The data type may carry a custom label, e.g.
+/// `String.w3c`; AWS accepts any label after `String.`, and the value is still read
+/// as a string. Consumers strip the prefix with
+/// [`MessageAttributesExtractor::strip_prefix`], or, on SQS, with
+/// `sqs::TransformAwareExtractor::with_prefix`.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns::MessageAttributesInjector;
+///
+/// let builder = MessageAttributesInjector::builder()
+///     .prefix("x-trace-")
+///     .data_type("String.w3c");
+/// propagator.inject_context(&cx, &mut builder.build(&mut attributes));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MessageAttributesInjectorBuilder {
+    prefix: String,
+    data_type: String,
+}
+
+impl Default for MessageAttributesInjectorBuilder {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            data_type: "String".to_string(),
+        }
+    }
+}
+
+impl MessageAttributesInjectorBuilder {
+    /// Prepends `prefix` to every attribute name.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Writes the attributes with `data_type` instead of `String`.
+    pub fn data_type(mut self, data_type: &str) -> Self {
+        self.data_type = data_type.to_string();
+        self
+    }
+
+    /// Returns an injector writing into `attributes` with this configuration.
+    pub fn build<'a>(
+        &self,
+        attributes: &'a mut HashMap<String, MessageAttributeValue>,
+    ) -> PrefixedInjector<'a> {
+        PrefixedInjector {
+            attributes,
+            prefix: self.prefix.clone(),
+            data_type: self.data_type.clone(),
+        }
+    }
+}
+
+/// An [`Injector`] for SNS message attributes with a key prefix and data type, created
+/// with [`MessageAttributesInjectorBuilder::build`].
+///
+/// Like [`MessageAttributesInjector`], rejected values are logged and skipped.
+pub struct PrefixedInjector<'a> {
+    attributes: &'a mut HashMap<String, MessageAttributeValue>,
+    prefix: String,
+    data_type: String,
+}
+
+impl Injector for PrefixedInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        let name = format!("{}{key}", self.prefix);
+        let data_type = &self.data_type;
+        if let Err(e) = try_insert_with(self.attributes, &name, value, |value| {
+            string_attribute(&name, value, data_type)
+        }) {
+            tracing::warn!(error = %e, "skipping trace context attribute");
+        }
+    }
+}
+
+impl AttributeValue for MessageAttributeValue {
+    fn from_string(value: String) -> Self {
+        MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .expect("MessageAttributeValue build should not fail with valid String data_type")
+    }
+
+    fn from_binary(value: Vec<u8>) -> Self {
+        MessageAttributeValue::builder()
+            .data_type("Binary")
+            .binary_value(Blob::new(value))
+            .build()
+            .expect("MessageAttributeValue build should not fail with valid Binary data_type")
+    }
+
+    fn with_string_value(&self, value: String) -> Self {
+        MessageAttributeValue::builder()
+            .data_type(self.data_type())
+            .string_value(value)
+            .build()
+            .expect("MessageAttributeValue build should not fail with a data_type")
+    }
+
+    fn string_value(&self) -> Option<&str> {
+        MessageAttributeValue::string_value(self)
+    }
+
+    fn binary_value(&self) -> Option<&[u8]> {
+        MessageAttributeValue::binary_value(self).map(|b| b.as_ref())
+    }
+
+    fn is_list(&self) -> bool {
+        self.data_type() == "String.Array"
+    }
+}
+
+/// An [`Injector`] that refuses to grow a map of SNS message attributes past a limit,
+/// created with [`MessageAttributesInjector::with_capacity_limit`].
+///
+/// Fields that would exceed the limit are dropped and reported by
+/// [`dropped_keys`](Self::dropped_keys) instead of failing the publish later. The
+/// attributes already in the map are never removed. Among the fields written by this
+/// injector, `traceparent` is kept over `tracestate`, which is kept over `baggage`,
+/// which is kept over any other field: a higher-priority field evicts a lower-priority
+/// one written earlier, so `traceparent` is only dropped when the existing attributes
+/// alone fill the limit.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns::MessageAttributesInjector;
+/// use opentelemetry_aws_messaging::sqs::MAX_MESSAGE_ATTRIBUTES;
+///
+/// let mut injector = MessageAttributesInjector::with_capacity_limit(&mut attributes, MAX_MESSAGE_ATTRIBUTES);
+/// propagator.inject_context(&cx, &mut injector);
+/// if !injector.dropped_keys().is_empty() {
+///     tracing::debug!(dropped = ?injector.dropped_keys(), "trace fields over the attribute limit");
+/// }
+/// ```
+pub struct BoundedInjector<'a> {
+    attributes: &'a mut HashMap<String, MessageAttributeValue>,
+    limit: usize,
+    injected: Vec<String>,
+    dropped: Vec<String>,
+}
+
+impl BoundedInjector<'_> {
+    /// Returns the fields dropped to stay within the limit, in the order they were
+    /// dropped.
+    pub fn dropped_keys(&self) -> &[String] {
+        &self.dropped
+    }
+}
+
+impl Injector for BoundedInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if value.is_empty() {
+            // Skipped below anyway; it must not evict a field or count as dropped.
+            return MessageAttributesInjector(&mut *self.attributes).set(key, value);
+        }
+        if !self.attributes.contains_key(key) && self.attributes.len() >= self.limit {
+            let evict = self
+                .injected
+                .iter()
+                .enumerate()
+                .filter(|(_, injected)| field_rank(injected) > field_rank(key))
+                .max_by_key(|(_, injected)| field_rank(injected))
+                .map(|(i, _)| i);
+            let Some(i) = evict else {
+                self.dropped.push(key.to_string());
+                return;
+            };
+            let evicted = self.injected.remove(i);
+            self.attributes.remove(&evicted);
+            self.dropped.push(evicted);
+        }
+        let inserted = !self.attributes.contains_key(key);
+        MessageAttributesInjector(&mut *self.attributes).set(key, value);
+        if inserted && self.attributes.contains_key(key) {
+            self.injected.push(key.to_string());
+        }
+    }
+}
+
+/// An [`Extractor`] implementation for SNS message attributes.
+///
+/// Reads `String` values, the counterpart of [`MessageAttributesInjector`], and
+/// `Binary` values that are valid UTF-8 when there is no string value. Use it
+/// where the attributes arrive typed as SNS values, e.g. in services subscribed to a
+/// topic over HTTP or Lambda rather than through SQS.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_aws_messaging::sns::MessageAttributesExtractor;
+///
+/// let parent_cx = global::get_text_map_propagator(|propagator| {
+///     propagator.extract(&MessageAttributesExtractor(&relayed_attributes))
+/// });
+/// ```
+pub struct MessageAttributesExtractor<'a>(pub &'a HashMap<String, MessageAttributeValue>);
+
+impl Extractor for MessageAttributesExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.decode_lenient())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|s| s.as_str()).collect()
+    }
+}
+
+impl<'a> MessageAttributesExtractor<'a> {
+    /// Creates an extractor reading the fields written under `prefix`, the counterpart
+    /// of a [`PrefixedInjector`].
+    pub fn strip_prefix(
+        attributes: &'a HashMap<String, MessageAttributeValue>,
+        prefix: &str,
+    ) -> PrefixedExtractor<'a> {
+        PrefixedExtractor {
+            attributes,
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+/// An [`Extractor`] for SNS message attributes written under a prefix, created with
+/// [`MessageAttributesExtractor::strip_prefix`].
+///
+/// Only attributes named with the prefix are visible, and `keys` reports their names
+/// without it. Values are read like [`MessageAttributesExtractor`] reads them.
+pub struct PrefixedExtractor<'a> {
+    attributes: &'a HashMap<String, MessageAttributeValue>,
+    prefix: String,
+}
+
+impl Extractor for PrefixedExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .get(&format!("{}{key}", self.prefix))
+            .and_then(|v| v.decode_lenient())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.attributes
+            .keys()
+            .filter_map(|name| name.strip_prefix(self.prefix.as_str()))
+            .collect()
+    }
+}
+
+/// An [`Extractor`] for SNS message attributes that reads values according to a
+/// [`CarrierConfig`], the counterpart of [`ConfiguredInjector`].
+pub type ConfiguredExtractor<'a> = ConfiguredAttributeExtractor<'a, MessageAttributeValue>;
+
+/// An [`Injector`] for SNS message attributes that encodes values according to a
+/// [`CarrierConfig`].
+///
+/// Build the consumer-side extractor from the same config so both sides agree on
+/// the attribute encoding. Like [`MessageAttributesInjector`], it logs and skips
+/// empty values, which AWS rejects.
+pub struct ConfiguredInjector<'a> {
+    attributes: &'a mut HashMap<String, MessageAttributeValue>,
+    config: &'a CarrierConfig,
+}
+
+impl<'a> ConfiguredInjector<'a> {
+    /// Creates an injector writing into `attributes` with the encoding and key escaping
+    /// from `config`.
+    pub fn new(
+        attributes: &'a mut HashMap<String, MessageAttributeValue>,
+        config: &'a CarrierConfig,
+    ) -> Self {
+        Self { attributes, config }
+    }
+}
+
+impl Injector for ConfiguredInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        let key = self.config.encode_key(key);
+        if let Err(e) = try_insert_encoded(self.attributes, &key, value, self.config.encoding) {
+            tracing::warn!(error = %e, "skipping trace context attribute");
+        }
+    }
+}
+
+/// Injects `cx` with the global propagator into a new map of SNS message attributes.
+///
+/// Shorthand for wrapping a fresh map in a [`MessageAttributesInjector`]; add any
+/// business attributes to the returned map before publishing.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns;
+///
+/// client
+///     .publish()
+///     .topic_arn(&topic_arn)
+///     .message(&message_body)
+///     .set_message_attributes(Some(sns::inject_context(&cx)))
+///     .send()
+///     .await?;
+/// ```
+pub fn inject_context(cx: &Context) -> HashMap<String, MessageAttributeValue> {
+    let mut attributes = HashMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(cx, &mut MessageAttributesInjector(&mut attributes));
+    });
+    attributes
+}
+
+/// Injects the current context, see [`inject_context`].
+pub fn inject_current_context() -> HashMap<String, MessageAttributeValue> {
+    inject_context(&Context::current())
+}
+
+/// Injects `cx` into `attributes` and records the propagation on the span in `cx`.
+///
+/// After the global propagator has written its fields, a `context.injected` event
+/// carrying `messaging.destination.name` is added to the span, leaving a trace-level
+/// record of every propagation boundary crossed. This is useful for auditing fan-out,
+/// where a single span publishes to several destinations.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns;
+///
+/// let mut attributes = HashMap::new();
+/// sns::inject_context_with_event(&cx, "orders-topic", &mut attributes);
+/// ```
+pub fn inject_context_with_event(
+    cx: &Context,
+    destination_name: &str,
+    attributes: &mut HashMap<String, MessageAttributeValue>,
+) {
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(cx, &mut MessageAttributesInjector(attributes));
+    });
+    cx.span().add_event(
+        "context.injected",
+        vec![KeyValue::new(
+            "messaging.destination.name",
+            destination_name.to_string(),
+        )],
+    );
+}
+
+/// Writes only a W3C `traceparent` attribute for the span in `cx`, bypassing the
+/// global propagator.
+///
+/// SNS and SQS allow at most 10 message attributes. When business attributes leave
+/// little room, this guarantees trace propagation costs exactly one attribute:
+/// `tracestate`, baggage, and any other propagator fields are dropped. Nothing is
+/// written when `cx` holds no valid span context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns;
+///
+/// sns::inject_traceparent_only(&cx, &mut attributes);
+/// ```
+pub fn inject_traceparent_only(
+    cx: &Context,
+    attributes: &mut HashMap<String, MessageAttributeValue>,
+) {
+    let span = cx.span();
+    let span_cx = span.span_context();
+    if !span_cx.is_valid() {
+        return;
     }
+    let traceparent = format!(
+        "00-{}-{}-{:02x}",
+        span_cx.trace_id(),
+        span_cx.span_id(),
+        (span_cx.trace_flags() & TraceFlags::SAMPLED).to_u8()
+    );
+    MessageAttributesInjector(attributes).set("traceparent", traceparent);
+}
+
+/// Injects `cx` into `attributes` with the global propagator, forcing the sampled flag
+/// when `force_sampled` is set.
+///
+/// High-value messages (e.g. payment events) can be marked for tracing regardless of
+/// the local sampling decision: with `force_sampled`, the written `traceparent` has
+/// the sampled flag set even when the span in `cx` was not sampled. Trace id, span id,
+/// trace state, and baggage are unchanged. Without `force_sampled` this is a plain
+/// injection.
+///
+/// Consumers using a parent-based sampler then sample the message's processing, and
+/// everything downstream of it, as a sampled subtree. Its parent, the unsampled
+/// publisher span, is never exported, so backends show the subtree as a trace with a
+/// missing root. Forcing the flag also multiplies trace volume by the share of tagged
+/// messages, so reserve it for low-volume message types.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns;
+///
+/// let mut attributes = HashMap::new();
+/// sns::inject_context_with_sampling(&cx, &mut attributes, event.kind == EventKind::Payment);
+/// ```
+pub fn inject_context_with_sampling(
+    cx: &Context,
+    attributes: &mut HashMap<String, MessageAttributeValue>,
+    force_sampled: bool,
+) {
+    let span_cx = cx.span().span_context().clone();
+    let forced;
+    let cx = if force_sampled && span_cx.is_valid() && !span_cx.is_sampled() {
+        forced = cx.with_remote_span_context(SpanContext::new(
+            span_cx.trace_id(),
+            span_cx.span_id(),
+            span_cx.trace_flags() | TraceFlags::SAMPLED,
+            span_cx.is_remote(),
+            span_cx.trace_state().clone(),
+        ));
+        &forced
+    } else {
+        cx
+    };
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(cx, &mut MessageAttributesInjector(attributes));
+    });
+}
+
+/// Returns `true` if `cx` has no valid span context to inject.
+///
+/// This typically means no span was active, and a message published with `cx` would
+/// carry no trace context or start an unrelated trace. Check it before injecting: the
+/// W3C propagator writes no `traceparent` at all for such a context, so
+/// [`has_zero_trace_id`] cannot catch it afterwards.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns;
+///
+/// if sns::is_untraced(&cx) {
+///     tracing::warn!("publishing without an active span");
+/// }
+/// sns::inject_context_with_event(&cx, "orders-topic", &mut attributes);
+/// ```
+pub fn is_untraced(cx: &Context) -> bool {
+    !cx.span().span_context().is_valid()
+}
+
+/// Returns `true` if the `traceparent` injected into `attributes` carries the all-zero
+/// (invalid) trace id.
+///
+/// The W3C propagator never writes one, but custom propagators that serialize an
+/// invalid context verbatim do, and the message would pollute downstream traces.
+/// Attributes without a `traceparent` are not flagged; use [`is_untraced`] on the
+/// context before injecting to catch a missing span.
+pub fn has_zero_trace_id(attributes: &HashMap<String, MessageAttributeValue>) -> bool {
+    attributes
+        .get("traceparent")
+        .and_then(|v| v.string_value())
+        .and_then(|traceparent| traceparent.split('-').nth(1))
+        .is_some_and(|trace_id| !trace_id.is_empty() && trace_id.bytes().all(|b| b == b'0'))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Encoding;
+    use crate::test_util::{init_propagator, sampled_context, test_tracer};
+    use opentelemetry::baggage::BaggageExt;
+    use opentelemetry::trace::{Tracer, TracerProvider};
+
+    #[test]
+    fn test_inject_current_context_returns_traceparent() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+
+        let attributes = inject_current_context();
+
+        assert_eq!(
+            attributes["traceparent"].string_value(),
+            Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+        assert!(inject_context(&Context::new()).is_empty());
+    }
+
+    #[test]
+    fn test_prefixed_injector_round_trips() {
+        use opentelemetry::propagation::TextMapPropagator;
+        use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+        let propagator = TraceContextPropagator::new();
+        let mut attributes = HashMap::new();
+        let builder = MessageAttributesInjector::builder()
+            .prefix("x-trace-")
+            .data_type("String.w3c");
+
+        propagator.inject_context(&sampled_context(), &mut builder.build(&mut attributes));
+
+        let attr = &attributes["x-trace-traceparent"];
+        assert_eq!(attr.data_type(), "String.w3c");
+        assert!(!attributes.contains_key("traceparent"));
+
+        let extractor = MessageAttributesExtractor::strip_prefix(&attributes, "x-trace-");
+        assert_eq!(extractor.keys(), ["traceparent"]);
+        let cx = propagator.extract(&extractor);
+        assert_eq!(
+            cx.span().span_context(),
+            sampled_context().span().span_context()
+        );
+        assert_eq!(
+            MessageAttributesExtractor(&attributes).get("traceparent"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_injector_skips_empty_value() {
+        let mut attributes = HashMap::new();
+        let mut injector = MessageAttributesInjector(&mut attributes);
+
+        injector.set("tracestate", String::new());
+        assert_eq!(
+            injector.try_set("tracestate", String::new()),
+            Err(InjectError::EmptyValue {
+                key: "tracestate".to_string()
+            })
+        );
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(
+            attributes["traceparent"].string_value(),
+            Some("00-abc123-def456-01")
+        );
+    }
+
+    #[test]
+    fn test_prefixed_injector_skips_empty_value() {
+        let mut attributes = HashMap::new();
+        let builder = MessageAttributesInjector::builder()
+            .prefix("x-trace-")
+            .data_type("String.w3c");
+        let mut injector = builder.build(&mut attributes);
+
+        injector.set("tracestate", String::new());
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+
+        assert_eq!(
+            attributes.keys().collect::<Vec<_>>(),
+            ["x-trace-traceparent"]
+        );
+    }
+
+    #[test]
+    fn test_validate_trace_attributes_keeps_data_type() {
+        use crate::core::{validate_trace_attributes, InvalidValuePolicy};
+
+        init_propagator();
+        let mut attributes = HashMap::from([(
+            "tracestate".to_string(),
+            MessageAttributeValue::builder()
+                .data_type("String.w3c")
+                .string_value("vendor=a\u{7}b")
+                .build()
+                .unwrap(),
+        )]);
+
+        validate_trace_attributes(&mut attributes, InvalidValuePolicy::Sanitize).unwrap();
+
+        assert_eq!(attributes["tracestate"].data_type(), "String.w3c");
+        assert_eq!(
+            attributes["tracestate"].string_value(),
+            Some("vendor=a%07b")
+        );
+    }
+
+    fn user_attributes(count: usize) -> HashMap<String, MessageAttributeValue> {
+        (0..count)
+            .map(|i| {
+                (
+                    format!("attr-{i}"),
+                    MessageAttributeValue::from_string(i.to_string()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bounded_injector_drops_fields_over_limit() {
+        let mut attributes = user_attributes(8);
+        let mut injector = MessageAttributesInjector::with_capacity_limit(&mut attributes, 10);
+
+        injector.set("baggage", "tenant=acme".to_string());
+        injector.set("tracestate", "dd=s:1".to_string());
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+        injector.set("b3", "abc-def-1".to_string());
+
+        assert_eq!(injector.dropped_keys(), ["baggage", "b3"]);
+        assert_eq!(attributes.len(), 10);
+        assert!(attributes.contains_key("traceparent"));
+        assert!(attributes.contains_key("tracestate"));
+    }
+
+    #[test]
+    fn test_bounded_injector_ignores_empty_values() {
+        let mut attributes = user_attributes(9);
+        let mut injector = MessageAttributesInjector::with_capacity_limit(&mut attributes, 10);
+
+        injector.set("baggage", "tenant=acme".to_string());
+        injector.set("tracestate", String::new());
+
+        assert!(injector.dropped_keys().is_empty());
+        assert!(attributes.contains_key("baggage"));
+        assert!(!attributes.contains_key("tracestate"));
+    }
+
+    #[test]
+    fn test_bounded_injector_keeps_existing_attributes() {
+        let mut attributes = user_attributes(10);
+        let mut injector = MessageAttributesInjector::with_capacity_limit(&mut attributes, 10);
+
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+        injector.set("attr-0", "replaced".to_string());
+
+        assert_eq!(injector.dropped_keys(), ["traceparent"]);
+        assert_eq!(attributes.len(), 10);
+        assert_eq!(attributes["attr-0"].string_value(), Some("replaced"));
+    }
+
+    #[test]
+    fn test_inject_context_with_sampling_forces_sampled_flag() {
+        use opentelemetry::trace::{SpanId, TraceId, TraceState};
+
+        init_propagator();
+        let unsampled = Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::default(),
+            true,
+            TraceState::default(),
+        ));
+        let traceparent = |force_sampled| {
+            let mut attributes = HashMap::new();
+            inject_context_with_sampling(&unsampled, &mut attributes, force_sampled);
+            attributes["traceparent"]
+                .string_value()
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            traceparent(true),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+        assert_eq!(
+            traceparent(false),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00"
+        );
+    }
 
     #[test]
     fn test_injector_sets_string_attribute() {
@@ -79,5 +809,147 @@ mod tests {
         assert_eq!(attrs.len(), 1);
         assert_eq!(attrs.get("key").unwrap().string_value(), Some("value2"));
     }
-}
 
+    fn make_attr(value: &str) -> MessageAttributeValue {
+        MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extractor_gets_existing_key() {
+        let mut attrs = HashMap::new();
+        attrs.insert("traceparent".to_string(), make_attr("00-abc123-def456-01"));
+
+        let extractor = MessageAttributesExtractor(&attrs);
+
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+    }
+
+    #[test]
+    fn test_extractor_returns_none_for_missing_key() {
+        let attrs = HashMap::new();
+        let extractor = MessageAttributesExtractor(&attrs);
+
+        assert_eq!(extractor.get("nonexistent"), None);
+    }
+
+    #[test]
+    fn test_extractor_keys_returns_all_keys() {
+        let mut attrs = HashMap::new();
+        attrs.insert("key1".to_string(), make_attr("value1"));
+        attrs.insert("key2".to_string(), make_attr("value2"));
+
+        let extractor = MessageAttributesExtractor(&attrs);
+        let mut keys = extractor.keys();
+        keys.sort();
+
+        assert_eq!(keys, vec!["key1", "key2"]);
+    }
+
+    #[test]
+    fn test_configured_injector_binary_encoding() {
+        let mut attrs = HashMap::new();
+        let config = CarrierConfig {
+            encoding: Encoding::Binary,
+            ..Default::default()
+        };
+        let mut injector = ConfiguredInjector::new(&mut attrs, &config);
+
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+
+        let attr = attrs.get("traceparent").unwrap();
+        assert_eq!(attr.data_type(), "Binary");
+        assert_eq!(attr.string_value(), None);
+        assert_eq!(
+            attr.binary_value().map(|b| b.as_ref()),
+            Some("00-abc123-def456-01".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_configured_injector_skips_empty_value() {
+        let mut attrs = HashMap::new();
+        let config = CarrierConfig {
+            encoding: Encoding::Binary,
+            ..Default::default()
+        };
+        let mut injector = ConfiguredInjector::new(&mut attrs, &config);
+
+        injector.set("tracestate", String::new());
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+
+        assert_eq!(attrs.len(), 1);
+        assert!(attrs.contains_key("traceparent"));
+    }
+
+    #[test]
+    fn test_inject_context_with_event_records_event() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let span = provider.tracer("test").start("sns.publish");
+        let cx = Context::current_with_span(span);
+        let mut attrs = HashMap::new();
+
+        inject_context_with_event(&cx, "orders-topic", &mut attrs);
+        cx.span().end();
+
+        assert!(attrs.contains_key("traceparent"));
+        let spans = exporter.get_finished_spans().unwrap();
+        let event = &spans[0].events[0];
+        assert_eq!(event.name, "context.injected");
+        assert_eq!(
+            event.attributes,
+            vec![KeyValue::new("messaging.destination.name", "orders-topic")]
+        );
+    }
+
+    #[test]
+    fn test_has_zero_trace_id() {
+        let mut attrs = HashMap::new();
+        assert!(!has_zero_trace_id(&attrs));
+
+        MessageAttributesInjector(&mut attrs).set(
+            "traceparent",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01".to_string(),
+        );
+        assert!(has_zero_trace_id(&attrs));
+
+        MessageAttributesInjector(&mut attrs).set(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+        );
+        assert!(!has_zero_trace_id(&attrs));
+    }
+
+    #[test]
+    fn test_is_untraced() {
+        init_propagator();
+        assert!(is_untraced(&Context::new()));
+        assert!(!is_untraced(&sampled_context()));
+
+        let attrs = inject_context(&Context::new());
+        assert!(!attrs.contains_key("traceparent"));
+        assert!(!has_zero_trace_id(&attrs));
+    }
+
+    #[test]
+    fn test_inject_traceparent_only_writes_single_attribute() {
+        let cx = sampled_context().with_baggage(vec![KeyValue::new("user", "1")]);
+        let mut attrs = HashMap::new();
+
+        inject_traceparent_only(&cx, &mut attrs);
+
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(
+            attrs["traceparent"].string_value(),
+            Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+
+        let mut attrs = HashMap::new();
+        inject_traceparent_only(&Context::new(), &mut attrs);
+        assert!(attrs.is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+//! The SNS operations used by [`TracedSnsPublisher`](super::publisher::TracedSnsPublisher).
+//!
+//! The publisher is generic over [`SnsPublishClient`], implemented for the SDK
+//! [`Client`]. Tests can swap in [`InMemorySnsClient`] (`testing` feature), which
+//! records every published message instead of calling AWS.
+
+use aws_sdk_sns::types::MessageAttributeValue;
+use aws_sdk_sns::Client;
+use std::collections::HashMap;
+use std::future::Future;
+
+/// The SNS operations a traced publisher needs.
+pub trait SnsPublishClient {
+    /// Publishes `body` with `attributes` to `topic_arn`, returning the message id.
+    fn publish_message(
+        &self,
+        topic_arn: &str,
+        body: &str,
+        attributes: HashMap<String, MessageAttributeValue>,
+    ) -> impl Future<Output = Result<Option<String>, aws_sdk_sns::Error>> + Send;
+}
+
+impl SnsPublishClient for Client {
+    async fn publish_message(
+        &self,
+        topic_arn: &str,
+        body: &str,
+        attributes: HashMap<String, MessageAttributeValue>,
+    ) -> Result<Option<String>, aws_sdk_sns::Error> {
+        let output = self
+            .publish()
+            .topic_arn(topic_arn)
+            .message(body)
+            .set_message_attributes(Some(attributes))
+            .send()
+            .await?;
+        Ok(output.message_id().map(str::to_string))
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub use fake::{InMemorySnsClient, PublishedMessage};
+
+#[cfg(any(test, feature = "testing"))]
+mod fake {
+    use super::*;
+    use aws_sdk_sns::types::error::InternalErrorException;
+    use std::sync::Mutex;
+
+    /// A message recorded by [`InMemorySnsClient`].
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PublishedMessage {
+        /// The id returned for the message, `msg-<n>` counting from 1.
+        pub message_id: String,
+        /// The topic the message was published to.
+        pub topic_arn: String,
+        /// The message body.
+        pub body: String,
+        /// The message attributes, trace fields included.
+        pub attributes: HashMap<String, MessageAttributeValue>,
+    }
+
+    /// An [`SnsPublishClient`] that keeps published messages in memory.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use opentelemetry_aws_messaging::sns::client::InMemorySnsClient;
+    ///
+    /// let publisher = TracedSnsPublisher::new(InMemorySnsClient::default(), topic_arn);
+    /// publisher.publish("hello").await?;
+    /// assert!(publisher.client().published()[0].attributes.contains_key("traceparent"));
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct InMemorySnsClient {
+        published: Mutex<Vec<PublishedMessage>>,
+        failures: Mutex<u32>,
+    }
+
+    impl InMemorySnsClient {
+        /// Makes the next `count` publishes fail with an `InternalErrorException`.
+        pub fn fail_next(&self, count: u32) {
+            *self.failures.lock().unwrap() = count;
+        }
+
+        /// Returns the messages published so far, in order.
+        pub fn published(&self) -> Vec<PublishedMessage> {
+            self.published.lock().unwrap().clone()
+        }
+    }
+
+    impl SnsPublishClient for InMemorySnsClient {
+        async fn publish_message(
+            &self,
+            topic_arn: &str,
+            body: &str,
+            attributes: HashMap<String, MessageAttributeValue>,
+        ) -> Result<Option<String>, aws_sdk_sns::Error> {
+            {
+                let mut failures = self.failures.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err(aws_sdk_sns::Error::InternalErrorException(
+                        InternalErrorException::builder()
+                            .message("injected failure")
+                            .build(),
+                    ));
+                }
+            }
+            let mut published = self.published.lock().unwrap();
+            let message_id = format!("msg-{}", published.len() + 1);
+            published.push(PublishedMessage {
+                message_id: message_id.clone(),
+                topic_arn: topic_arn.to_string(),
+                body: body.to_string(),
+                attributes,
+            });
+            Ok(Some(message_id))
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Dual-format injection for migrations from Datadog tracing libraries.
+//!
+//! Legacy Datadog consumers look for a `_datadog` message attribute instead of the
+//! W3C fields. [`inject_context_with_datadog`] writes both, so OpenTelemetry and
+//! Datadog consumers can continue the same trace. It is behind the `datadog-compat`
+//! feature.
+//!
+//! # Attribute format
+//!
+//! `_datadog` is a `Binary` attribute, as written by the Datadog tracers for SNS,
+//! holding a UTF-8 JSON object of Datadog propagation headers:
+//!
+//! ```text
+//! {
+//!   "x-datadog-trace-id": "<low 64 bits of the trace id, decimal>",
+//!   "x-datadog-parent-id": "<span id, decimal>",
+//!   "x-datadog-sampling-priority": "<1 if sampled, 0 otherwise>",
+//!   "x-datadog-tags": "_dd.p.tid=<high 64 bits of the trace id, 16 hex digits>"
+//! }
+//! ```
+
+use super::MessageAttributesInjector;
+use aws_sdk_sns::primitives::Blob;
+use aws_sdk_sns::types::MessageAttributeValue;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::{global, Context};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// The message attribute read by Datadog tracers.
+pub const DATADOG_ATTRIBUTE: &str = "_datadog";
+
+/// Injects `cx` into `attributes` with the global propagator and also writes the
+/// [`DATADOG_ATTRIBUTE`] attribute.
+///
+/// `_datadog` is only written when `cx` holds a valid span context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns::datadog::inject_context_with_datadog;
+///
+/// let mut attributes = HashMap::new();
+/// inject_context_with_datadog(&cx, &mut attributes);
+/// ```
+pub fn inject_context_with_datadog(
+    cx: &Context,
+    attributes: &mut HashMap<String, MessageAttributeValue>,
+) {
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(cx, &mut MessageAttributesInjector(attributes));
+    });
+
+    let span = cx.span();
+    let span_cx = span.span_context();
+    if !span_cx.is_valid() {
+        return;
+    }
+    let trace_id = u128::from_be_bytes(span_cx.trace_id().to_bytes());
+    let headers = json!({
+        "x-datadog-trace-id": (trace_id as u64).to_string(),
+        "x-datadog-parent-id": u64::from_be_bytes(span_cx.span_id().to_bytes()).to_string(),
+        "x-datadog-sampling-priority": if span_cx.is_sampled() { "1" } else { "0" },
+        "x-datadog-tags": format!("_dd.p.tid={:016x}", (trace_id >> 64) as u64),
+    });
+    attributes.insert(
+        DATADOG_ATTRIBUTE.to_string(),
+        MessageAttributeValue::builder()
+            .data_type("Binary")
+            .binary_value(Blob::new(headers.to_string()))
+            .build()
+            .expect("MessageAttributeValue build should not fail with valid Binary data_type"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context};
+    use serde_json::Value;
+
+    #[test]
+    fn test_writes_w3c_and_datadog_attributes() {
+        init_propagator();
+        let mut attrs = HashMap::new();
+
+        inject_context_with_datadog(&sampled_context(), &mut attrs);
+
+        assert!(attrs.contains_key("traceparent"));
+        let datadog = &attrs[DATADOG_ATTRIBUTE];
+        assert_eq!(datadog.data_type(), "Binary");
+        let headers: Value =
+            serde_json::from_slice(datadog.binary_value().unwrap().as_ref()).unwrap();
+        // 4bf92f3577b34da6a3ce929d0e0e4736 / 00f067aa0ba902b7
+        assert_eq!(headers["x-datadog-trace-id"], "11803532876627986230");
+        assert_eq!(headers["x-datadog-parent-id"], "67667974448284343");
+        assert_eq!(headers["x-datadog-sampling-priority"], "1");
+        assert_eq!(headers["x-datadog-tags"], "_dd.p.tid=4bf92f3577b34da6");
+    }
+
+    #[test]
+    fn test_skips_datadog_attribute_without_span() {
+        init_propagator();
+        let mut attrs = HashMap::new();
+
+        inject_context_with_datadog(&Context::new(), &mut attrs);
+
+        assert!(attrs.is_empty());
+    }
+}
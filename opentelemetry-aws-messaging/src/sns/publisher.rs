@@ -0,0 +1,416 @@
+//! A high-level SNS publisher that traces every message it sends.
+//!
+//! [`TracedSnsPublisher`] packages the span → inject → publish pattern so applications
+//! only provide the message body. It is behind the `publisher` feature so the
+//! low-level carriers stay dependency-light.
+//!
+//! The publisher is generic over the [`SnsPublishClient`] it sends through, the SDK
+//! `Client` by default, so it can be tested against an in-memory fake.
+
+use super::client::SnsPublishClient;
+use super::{is_untraced, MessageAttributesInjector};
+use crate::core::DebugInjector;
+use crate::semconv::sns_producer_attributes;
+use aws_sdk_sns::types::MessageAttributeValue;
+use aws_sdk_sns::Client;
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::collections::HashMap;
+
+/// Wraps an SNS client and topic ARN, publishing each message inside a producer span
+/// whose context is injected into the message attributes.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns::publisher::TracedSnsPublisher;
+///
+/// let publisher = TracedSnsPublisher::new(client, topic_arn);
+/// let message_id = publisher.publish(&message_body).await?;
+/// ```
+pub struct TracedSnsPublisher<C = Client> {
+    client: C,
+    topic_arn: String,
+    tracer: BoxedTracer,
+    debug_attributes: bool,
+}
+
+impl<C: SnsPublishClient> TracedSnsPublisher<C> {
+    /// Creates a publisher for `topic_arn` using the global tracer provider.
+    pub fn new(client: C, topic_arn: impl Into<String>) -> Self {
+        Self {
+            client,
+            topic_arn: topic_arn.into(),
+            tracer: global::tracer(env!("CARGO_PKG_NAME")),
+            debug_attributes: false,
+        }
+    }
+
+    /// Returns the client messages are published through.
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Uses `tracer` instead of the global tracer to create producer spans.
+    pub fn with_tracer(mut self, tracer: BoxedTracer) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Records each injected trace field on the producer span as
+    /// `debug.injected.<field>`, e.g. `debug.injected.traceparent`.
+    ///
+    /// Off by default; see [`DebugInjector`].
+    pub fn with_debug_attributes(mut self, enabled: bool) -> Self {
+        self.debug_attributes = enabled;
+        self
+    }
+
+    /// Publishes `body` with the trace context as its only message attributes.
+    ///
+    /// Returns the SNS message id.
+    pub async fn publish(&self, body: &str) -> Result<Option<String>, aws_sdk_sns::Error> {
+        self.publish_with_attributes(body, HashMap::new()).await
+    }
+
+    /// Publishes `body` with the caller's business `attributes` merged with the
+    /// injected trace context.
+    ///
+    /// The producer span is a child of the current context. Trace fields are written on
+    /// top of `attributes`, so a business attribute that reuses a propagator field name
+    /// (e.g. `traceparent`) is replaced. Publishing with no active span, which starts a
+    /// new trace, is logged as a warning. The span carries the
+    /// [`sns_producer_attributes`] of the topic, including its region and account as
+    /// `cloud.region` and `cloud.account.id`. On success the returned message id is
+    /// recorded as `messaging.message.id`; on failure the span status is set to an
+    /// error.
+    pub async fn publish_with_attributes(
+        &self,
+        body: &str,
+        mut attributes: HashMap<String, MessageAttributeValue>,
+    ) -> Result<Option<String>, aws_sdk_sns::Error> {
+        let cx = self.start_publish_span(&mut attributes);
+        let result = self.send(body, attributes).await;
+        Self::end_publish_span(&cx, &result);
+        result
+    }
+
+    /// Publishes like [`publish_with_attributes`](Self::publish_with_attributes),
+    /// retrying failed requests up to `max_attempts` attempts in total.
+    ///
+    /// Every attempt runs in its own `SpanKind::Client` `sns.publish.attempt` span, a
+    /// child of the producer span, recording its number as
+    /// `messaging.aws_sns.publish.attempt` and an error status when it fails. All
+    /// attempts carry the same injected context, so consumers see the producer span as
+    /// their parent whichever attempt succeeded. Attempts follow each other
+    /// immediately; throttling and transient network errors are better left to the
+    /// SDK's own retry configuration, which backs off between attempts.
+    pub async fn publish_with_retry(
+        &self,
+        body: &str,
+        mut attributes: HashMap<String, MessageAttributeValue>,
+        max_attempts: u32,
+    ) -> Result<Option<String>, aws_sdk_sns::Error> {
+        let cx = self.start_publish_span(&mut attributes);
+        let max_attempts = max_attempts.max(1);
+        let mut attempt = 1;
+        let result = loop {
+            let span = self
+                .tracer
+                .span_builder("sns.publish.attempt")
+                .with_kind(SpanKind::Client)
+                .with_attributes(vec![KeyValue::new(
+                    "messaging.aws_sns.publish.attempt",
+                    i64::from(attempt),
+                )])
+                .start_with_context(&self.tracer, &cx);
+            let attempt_cx = cx.with_span(span);
+
+            let result = self.send(body, attributes.clone()).await;
+            if let Err(e) = &result {
+                attempt_cx.span().set_status(Status::error(e.to_string()));
+            }
+            attempt_cx.span().end();
+
+            if result.is_ok() || attempt == max_attempts {
+                break result;
+            }
+            attempt += 1;
+        };
+        Self::end_publish_span(&cx, &result);
+        result
+    }
+
+    /// Starts the producer span and injects its context into `attributes`.
+    fn start_publish_span(
+        &self,
+        attributes: &mut HashMap<String, MessageAttributeValue>,
+    ) -> Context {
+        let parent = Context::current();
+        self.warn_if_untraced(&parent);
+        let span = self
+            .tracer
+            .span_builder("sns.publish")
+            .with_kind(SpanKind::Producer)
+            .with_attributes(sns_producer_attributes(&self.topic_arn))
+            .start_with_context(&self.tracer, &parent);
+        let cx = parent.with_span(span);
+
+        global::get_text_map_propagator(|propagator| {
+            let mut injector = MessageAttributesInjector(attributes);
+            if self.debug_attributes {
+                propagator.inject_context(&cx, &mut DebugInjector::new(&mut injector, &cx));
+            } else {
+                propagator.inject_context(&cx, &mut injector);
+            }
+        });
+        cx
+    }
+
+    /// Logs a warning if `parent` has no active span to parent the producer span.
+    ///
+    /// Returns whether it did.
+    fn warn_if_untraced(&self, parent: &Context) -> bool {
+        if !is_untraced(parent) {
+            return false;
+        }
+        tracing::warn!(
+            topic_arn = %self.topic_arn,
+            "publishing with no active span; the message starts a new trace"
+        );
+        true
+    }
+
+    async fn send(
+        &self,
+        body: &str,
+        attributes: HashMap<String, MessageAttributeValue>,
+    ) -> Result<Option<String>, aws_sdk_sns::Error> {
+        self.client
+            .publish_message(&self.topic_arn, body, attributes)
+            .await
+    }
+
+    /// Records the outcome of the publish on the producer span in `cx` and ends it.
+    fn end_publish_span(cx: &Context, result: &Result<Option<String>, aws_sdk_sns::Error>) {
+        let span = cx.span();
+        match result {
+            Ok(Some(message_id)) => {
+                span.set_attribute(KeyValue::new("messaging.message.id", message_id.clone()))
+            }
+            Ok(None) => {}
+            Err(e) => span.set_status(Status::error(e.to_string())),
+        }
+        span.end();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, test_tracer};
+    use aws_sdk_sns::operation::publish::{PublishError, PublishOutput};
+    use aws_sdk_sns::types::error::NotFoundException;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+    use opentelemetry::trace::TracerProvider;
+
+    const TOPIC_ARN: &str = "arn:aws:sns:us-east-1:123456789012:orders";
+
+    fn string_attr(value: &str) -> MessageAttributeValue {
+        MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_publish_merges_attributes_and_records_message_id() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let publish = mock!(Client::publish)
+            .match_requests(|req| {
+                let attrs = req.message_attributes().unwrap();
+                attrs.contains_key("traceparent")
+                    && attrs["event_type"].string_value() == Some("order.created")
+            })
+            .then_output(|| PublishOutput::builder().message_id("msg-1").build());
+        let client = mock_client!(aws_sdk_sns, [&publish]);
+        let publisher = TracedSnsPublisher::new(client, TOPIC_ARN)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+
+        let mut attrs = HashMap::new();
+        attrs.insert("event_type".to_string(), string_attr("order.created"));
+        let message_id = publisher
+            .publish_with_attributes("{\"id\":1}", attrs)
+            .await
+            .unwrap();
+
+        assert_eq!(message_id.as_deref(), Some("msg-1"));
+        assert_eq!(publish.num_calls(), 1);
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].span_kind, SpanKind::Producer);
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.destination.name", "orders")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.message.id", "msg-1")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("cloud.account.id", "123456789012")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.operation.type", "send")));
+    }
+
+    #[tokio::test]
+    async fn test_debug_attributes_record_injected_traceparent() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let publish = mock!(Client::publish).then_output(|| PublishOutput::builder().build());
+        let client = mock_client!(aws_sdk_sns, [&publish]);
+        let publisher = TracedSnsPublisher::new(client, TOPIC_ARN)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))))
+            .with_debug_attributes(true);
+
+        publisher.publish("hello").await.unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let span_cx = &spans[0].span_context;
+        let expected = format!("00-{}-{}-01", span_cx.trace_id(), span_cx.span_id());
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("debug.injected.traceparent", expected)));
+    }
+
+    #[tokio::test]
+    async fn test_publish_with_retry_records_attempts_under_publish_span() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let failure = mock!(Client::publish).then_error(|| {
+            PublishError::NotFoundException(NotFoundException::builder().message("flaky").build())
+        });
+        let success = mock!(Client::publish)
+            .then_output(|| PublishOutput::builder().message_id("msg-2").build());
+        let client = mock_client!(aws_sdk_sns, RuleMode::Sequential, [&failure, &success]);
+        let publisher = TracedSnsPublisher::new(client, TOPIC_ARN)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+
+        let message_id = publisher
+            .publish_with_retry("hello", HashMap::new(), 3)
+            .await
+            .unwrap();
+
+        assert_eq!(message_id.as_deref(), Some("msg-2"));
+        let spans = exporter.get_finished_spans().unwrap();
+        let publish = spans.iter().find(|s| s.name == "sns.publish").unwrap();
+        let attempts: Vec<_> = spans
+            .iter()
+            .filter(|s| s.name == "sns.publish.attempt")
+            .collect();
+        assert_eq!(attempts.len(), 2);
+        for (attempt, span) in attempts.iter().enumerate() {
+            assert_eq!(span.parent_span_id, publish.span_context.span_id());
+            assert!(span.attributes.contains(&KeyValue::new(
+                "messaging.aws_sns.publish.attempt",
+                attempt as i64 + 1
+            )));
+        }
+        assert!(matches!(attempts[0].status, Status::Error { .. }));
+        assert_eq!(attempts[1].status, Status::Unset);
+        assert_eq!(publish.status, Status::Unset);
+    }
+
+    #[tokio::test]
+    async fn test_publish_through_in_memory_client() {
+        use crate::sns::client::InMemorySnsClient;
+
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let publisher = TracedSnsPublisher::new(InMemorySnsClient::default(), TOPIC_ARN)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+        publisher.client().fail_next(1);
+
+        let message_id = publisher
+            .publish_with_retry("hello", HashMap::new(), 2)
+            .await
+            .unwrap();
+
+        let published = publisher.client().published();
+        assert_eq!(message_id.as_deref(), Some("msg-1"));
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].topic_arn, TOPIC_ARN);
+        let span = exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .find(|s| s.name == "sns.publish")
+            .unwrap();
+        assert_eq!(
+            published[0].attributes["traceparent"].string_value(),
+            Some(
+                format!(
+                    "00-{}-{}-01",
+                    span.span_context.trace_id(),
+                    span.span_context.span_id()
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_publish_failure_marks_span_as_error() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let publish = mock!(Client::publish).then_error(|| {
+            PublishError::NotFoundException(
+                NotFoundException::builder().message("no topic").build(),
+            )
+        });
+        let client = mock_client!(aws_sdk_sns, [&publish]);
+        let publisher = TracedSnsPublisher::new(client, TOPIC_ARN)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+
+        assert!(publisher.publish("hello").await.is_err());
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert!(matches!(spans[0].status, Status::Error { .. }));
+    }
+
+    #[test]
+    fn test_warns_when_publishing_without_an_active_span() {
+        let client = mock_client!(aws_sdk_sns, []);
+        let publisher = TracedSnsPublisher::new(client, TOPIC_ARN);
+
+        assert!(publisher.warn_if_untraced(&Context::current()));
+        let _guard = sampled_context().attach();
+        assert!(!publisher.warn_if_untraced(&Context::current()));
+    }
+
+    #[tokio::test]
+    async fn test_publish_span_is_a_child_of_the_current_span() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let publish = mock!(Client::publish).then_output(|| PublishOutput::builder().build());
+        let client = mock_client!(aws_sdk_sns, [&publish]);
+        let publisher = TracedSnsPublisher::new(client, TOPIC_ARN)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+
+        let parent = sampled_context();
+        let _guard = parent.clone().attach();
+        publisher.publish("hello").await.unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(
+            spans[0].span_context.trace_id(),
+            parent.span().span_context().trace_id()
+        );
+        assert_eq!(
+            spans[0].parent_span_id,
+            parent.span().span_context().span_id()
+        );
+    }
+}
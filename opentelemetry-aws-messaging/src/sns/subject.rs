@@ -0,0 +1,127 @@
+//! Trace context carried in the SNS `Subject`, as a last resort.
+//!
+//! In rare configurations no message attribute survives delivery, but the subject
+//! does (e.g. it is exposed in the SNS envelope delivered to SQS or HTTP endpoints).
+//! [`subject_with_context`] appends a compact encoding of the W3C `traceparent` to the
+//! subject, and [`context_from_subject`] reads it back. Prefer message attributes or
+//! the `body` carrier whenever they are available: this channel carries no
+//! `tracestate` or baggage, and it changes a field that humans read (e.g. in email
+//! subscriptions). It is behind the `subject` feature.
+//!
+//! # Size
+//!
+//! SNS subjects are limited to [`MAX_SUBJECT_LEN`] ASCII characters. The context is
+//! the trace id, span id, and sampled flag (25 bytes) as unpadded URL-safe base64,
+//! appended as ` [tp:<34 characters>]`, which leaves [`MAX_PLAIN_SUBJECT_LEN`]
+//! characters for the subject itself.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+
+/// The longest subject SNS accepts.
+pub const MAX_SUBJECT_LEN: usize = 100;
+
+/// The marker preceding the encoded context in a subject.
+const MARKER: &str = " [tp:";
+
+/// The length of the encoded context: 25 bytes as unpadded base64.
+const ENCODED_LEN: usize = 34;
+
+/// The longest subject that still leaves room for the context.
+pub const MAX_PLAIN_SUBJECT_LEN: usize = MAX_SUBJECT_LEN - MARKER.len() - ENCODED_LEN - 1;
+
+/// Returns `subject` with the span context of `cx` appended.
+///
+/// Returns `None`, leaving the caller to publish the plain subject, when `cx` holds no
+/// valid span context or `subject` is longer than [`MAX_PLAIN_SUBJECT_LEN`]
+/// characters, so the result never exceeds [`MAX_SUBJECT_LEN`].
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sns::subject;
+///
+/// let subject = subject::subject_with_context(&cx, "Order created")
+///     .unwrap_or_else(|| "Order created".to_string());
+/// client.publish().topic_arn(&topic_arn).subject(subject) /* ... */;
+/// ```
+pub fn subject_with_context(cx: &Context, subject: &str) -> Option<String> {
+    let span = cx.span();
+    let span_cx = span.span_context();
+    if !span_cx.is_valid() || subject.len() > MAX_PLAIN_SUBJECT_LEN {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(25);
+    bytes.extend_from_slice(&span_cx.trace_id().to_bytes());
+    bytes.extend_from_slice(&span_cx.span_id().to_bytes());
+    bytes.push((span_cx.trace_flags() & TraceFlags::SAMPLED).to_u8());
+    Some(format!(
+        "{subject}{MARKER}{}]",
+        URL_SAFE_NO_PAD.encode(bytes)
+    ))
+}
+
+/// Returns a parent [`Context`] from a subject written by [`subject_with_context`],
+/// or `None` if it carries no valid context.
+pub fn context_from_subject(subject: &str) -> Option<Context> {
+    let encoded = subject.strip_suffix(']')?.rsplit_once(MARKER)?.1;
+    let bytes = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+    let bytes: [u8; 25] = bytes.try_into().ok()?;
+    let span_cx = SpanContext::new(
+        TraceId::from_bytes(bytes[..16].try_into().ok()?),
+        SpanId::from_bytes(bytes[16..24].try_into().ok()?),
+        TraceFlags::new(bytes[24]) & TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    span_cx
+        .is_valid()
+        .then(|| Context::new().with_remote_span_context(span_cx))
+}
+
+/// Returns `subject` without the context appended by [`subject_with_context`].
+pub fn strip_context(subject: &str) -> &str {
+    match subject
+        .strip_suffix(']')
+        .and_then(|s| s.rsplit_once(MARKER))
+    {
+        Some((plain, encoded)) if encoded.len() == ENCODED_LEN => plain,
+        _ => subject,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sampled_context;
+
+    #[test]
+    fn test_subject_round_trips_within_limit() {
+        let plain = "x".repeat(MAX_PLAIN_SUBJECT_LEN);
+
+        let subject = subject_with_context(&sampled_context(), &plain).unwrap();
+        let cx = context_from_subject(&subject).unwrap();
+
+        assert_eq!(subject.len(), MAX_SUBJECT_LEN);
+        assert_eq!(strip_context(&subject), plain);
+        let span_cx = cx.span().span_context().clone();
+        assert_eq!(
+            span_cx.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(span_cx.span_id().to_string(), "00f067aa0ba902b7");
+        assert!(span_cx.is_sampled());
+    }
+
+    #[test]
+    fn test_subject_too_long_is_not_extended() {
+        let plain = "x".repeat(MAX_PLAIN_SUBJECT_LEN + 1);
+
+        assert_eq!(subject_with_context(&sampled_context(), &plain), None);
+        assert_eq!(subject_with_context(&Context::new(), "Order created"), None);
+        assert!(context_from_subject("Order created").is_none());
+        assert_eq!(strip_context("Order created"), "Order created");
+    }
+}
@@ -1,11 +1,138 @@
 //! SQS message attribute carrier for OpenTelemetry context propagation.
 //!
-//! This module provides an [`Extractor`] implementation that allows extracting
-//! trace context from SQS message attributes.
+//! This module provides [`Injector`] and [`Extractor`] implementations that allow
+//! injecting trace context into, and extracting it from, SQS message attributes.
 
-use aws_sdk_sqs::types::MessageAttributeValue;
-use opentelemetry::propagation::Extractor;
+use crate::body::BodyExtractor;
+use crate::config::CarrierConfig;
+use crate::core::{field_rank, try_insert_string, AttributeValue, ConfiguredAttributeExtractor};
+use crate::diagnose::{present_fields, ContextSource, PropagationReport};
+use crate::error::InjectError;
+use aws_sdk_sqs::primitives::Blob;
+use aws_sdk_sqs::types::{
+    Message, MessageAttributeValue, MessageSystemAttributeName, MessageSystemAttributeNameForSends,
+    MessageSystemAttributeValue,
+};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::{Link, SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "consumer")]
+pub mod client;
+#[cfg(feature = "consumer")]
+pub mod consumer;
+pub mod destination;
+mod envelope;
+pub mod fifo;
+pub mod firehose;
+#[cfg(feature = "tower")]
+pub mod layer;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "consumer")]
+pub mod migration;
+pub mod operation;
+pub mod request_reply;
+pub mod ses;
+pub mod step_functions;
+
+#[cfg(feature = "gzip")]
+pub use envelope::MAX_DECOMPRESSED_SIZE;
+pub use envelope::{
+    ControlMessageType, SnsEnvelope, SnsEnvelopeExtractor, SnsMessageAttribute, SnsNotification,
+};
+
+impl AttributeValue for MessageAttributeValue {
+    fn from_string(value: String) -> Self {
+        MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .expect("MessageAttributeValue build should not fail with valid String data_type")
+    }
+
+    fn from_binary(value: Vec<u8>) -> Self {
+        MessageAttributeValue::builder()
+            .data_type("Binary")
+            .binary_value(Blob::new(value))
+            .build()
+            .expect("MessageAttributeValue build should not fail with valid Binary data_type")
+    }
+
+    fn with_string_value(&self, value: String) -> Self {
+        MessageAttributeValue::builder()
+            .data_type(self.data_type())
+            .string_value(value)
+            .build()
+            .expect("MessageAttributeValue build should not fail with a data_type")
+    }
+
+    fn string_value(&self) -> Option<&str> {
+        MessageAttributeValue::string_value(self)
+    }
+
+    fn binary_value(&self) -> Option<&[u8]> {
+        MessageAttributeValue::binary_value(self).map(|b| b.as_ref())
+    }
+
+    fn is_list(&self) -> bool {
+        !self.string_list_values().is_empty() || !self.binary_list_values().is_empty()
+    }
+}
+
+/// An [`Injector`] implementation for SQS message attributes.
+///
+/// Wraps a mutable reference to a `HashMap` of SQS message attributes and
+/// implements the OpenTelemetry `Injector` trait, allowing trace context
+/// to be injected into messages sent directly to a queue.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_aws_messaging::sqs::MessageAttributesInjector;
+/// use aws_sdk_sqs::types::MessageAttributeValue;
+/// use std::collections::HashMap;
+///
+/// let mut attributes: HashMap<String, MessageAttributeValue> = HashMap::new();
+///
+/// // Inject trace context from the current span
+/// global::get_text_map_propagator(|propagator| {
+///     propagator.inject_context(&cx, &mut MessageAttributesInjector(&mut attributes));
+/// });
+///
+/// // Now use `attributes` when sending to SQS
+/// client.send_message()
+///     .queue_url(&queue_url)
+///     .message_body(&message_body)
+///     .set_message_attributes(Some(attributes))
+///     .send()
+///     .await?;
+/// ```
+///
+/// Like the SNS injector, [`Injector::set`] logs and skips empty values, which AWS
+/// rejects. Use [`try_set`](Self::try_set) to handle the error instead.
+pub struct MessageAttributesInjector<'a>(pub &'a mut HashMap<String, MessageAttributeValue>);
+
+impl MessageAttributesInjector<'_> {
+    /// Inserts `value` as a `String` attribute, or returns an error without inserting
+    /// when the value is empty.
+    pub fn try_set(&mut self, key: &str, value: String) -> Result<(), InjectError> {
+        try_insert_string(self.0, key, value)
+    }
+}
+
+impl Injector for MessageAttributesInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let Err(e) = self.try_set(key, value) {
+            tracing::warn!(error = %e, "skipping trace context attribute");
+        }
+    }
+}
 
 /// An [`Extractor`] implementation for SQS message attributes.
 ///
@@ -13,6 +140,11 @@ use std::collections::HashMap;
 /// implements the OpenTelemetry `Extractor` trait, allowing trace context
 /// to be extracted from SQS messages.
 ///
+/// `String` values are read as is. Some SDKs write trace fields as `Binary` values
+/// instead; those are decoded as UTF-8 when there is no string value, and skipped when
+/// they are not valid UTF-8. The bytes are borrowed from the map, so no copy is made.
+/// Use a [`ConfiguredExtractor`] to read only one of the two.
+///
 /// # Example
 ///
 /// ```ignore
@@ -21,9 +153,8 @@ use std::collections::HashMap;
 /// use opentelemetry_aws_messaging::sqs::MessageAttributesExtractor;
 ///
 /// // Extract trace context from SQS message
-/// let attrs = msg.message_attributes().unwrap_or(&HashMap::new());
 /// let parent_cx = global::get_text_map_propagator(|propagator| {
-///     propagator.extract(&MessageAttributesExtractor(attrs))
+///     propagator.extract(&MessageAttributesExtractor::from_message(&msg))
 /// });
 ///
 /// // Create a child span linked to the extracted context
@@ -36,7 +167,7 @@ pub struct MessageAttributesExtractor<'a>(pub &'a HashMap<String, MessageAttribu
 
 impl Extractor for MessageAttributesExtractor<'_> {
     fn get(&self, key: &str) -> Option<&str> {
-        self.0.get(key).and_then(|v| v.string_value())
+        self.get_field(key)
     }
 
     fn keys(&self) -> Vec<&str> {
@@ -44,9 +175,988 @@ impl Extractor for MessageAttributesExtractor<'_> {
     }
 }
 
+/// The attributes of a message received without any.
+static NO_ATTRIBUTES: LazyLock<HashMap<String, MessageAttributeValue>> =
+    LazyLock::new(HashMap::new);
+
+impl<'a> MessageAttributesExtractor<'a> {
+    /// Creates an extractor over the message attributes of `msg`.
+    ///
+    /// A message received without `message_attribute_names` has no attribute map;
+    /// the extractor then has no keys and `get` returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let parent_cx = global::get_text_map_propagator(|propagator| {
+    ///     propagator.extract(&MessageAttributesExtractor::from_message(&msg))
+    /// });
+    /// ```
+    pub fn from_message(msg: &'a Message) -> Self {
+        Self(msg.message_attributes().unwrap_or(&NO_ATTRIBUTES))
+    }
+
+    /// Returns the value of a single known field.
+    ///
+    /// This is the lookup behind [`Extractor::get`], usable without a propagator. A
+    /// propagator's `extract` may call [`Extractor::keys`], which allocates a vector of
+    /// every attribute name; callers that only read fixed fields can skip that, see
+    /// [`extract_w3c`].
+    pub fn get_field(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.decode_lenient())
+    }
+
+    /// Creates an extractor over `attributes` that matches names ignoring ASCII case.
+    ///
+    /// Exact matching stays the default; use this when an intermediary re-cases
+    /// attribute names, e.g. delivers `Traceparent` for `traceparent`.
+    pub fn case_insensitive(
+        attributes: &'a HashMap<String, MessageAttributeValue>,
+    ) -> CaseInsensitiveExtractor<'a> {
+        CaseInsensitiveExtractor {
+            attributes,
+            names: attributes
+                .keys()
+                .map(|name| (name.to_ascii_lowercase(), name.as_str()))
+                .collect(),
+        }
+    }
+}
+
+/// An [`Extractor`] over SQS message attributes that matches names ignoring ASCII
+/// case, created with [`MessageAttributesExtractor::case_insensitive`].
+///
+/// Values are read like [`MessageAttributesExtractor`] reads them. An exact match wins
+/// when several attributes differ only in case. `keys` reports
+/// the lower-cased names, the spelling propagators use for their fields.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::MessageAttributesExtractor;
+///
+/// let parent_cx = global::get_text_map_propagator(|propagator| {
+///     propagator.extract(&MessageAttributesExtractor::case_insensitive(attrs))
+/// });
+/// ```
+pub struct CaseInsensitiveExtractor<'a> {
+    attributes: &'a HashMap<String, MessageAttributeValue>,
+    names: HashMap<String, &'a str>,
+}
+
+impl Extractor for CaseInsensitiveExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        let name = if self.attributes.contains_key(key) {
+            key
+        } else {
+            self.names.get(&key.to_ascii_lowercase())?
+        };
+        self.attributes.get(name).and_then(|v| v.decode_lenient())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.names.keys().map(|s| s.as_str()).collect()
+    }
+}
+
+/// An [`Extractor`] over SQS message attributes held as a slice of name/value pairs.
+///
+/// Some serialization layers expose attributes in their Smithy list form,
+/// `Vec<(String, MessageAttributeValue)>`, instead of a map. Lookups scan the slice,
+/// so `get` is linear in the number of attributes; that is cheap for SQS's ten
+/// attributes, but prefer [`MessageAttributesExtractor`] over a `HashMap` for larger
+/// attribute sets.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::MessageAttributePairsExtractor;
+///
+/// let pairs: Vec<(String, MessageAttributeValue)> = decode_attributes(&record)?;
+/// let parent_cx = global::get_text_map_propagator(|propagator| {
+///     propagator.extract(&MessageAttributePairsExtractor(&pairs))
+/// });
+/// ```
+pub struct MessageAttributePairsExtractor<'a>(pub &'a [(String, MessageAttributeValue)]);
+
+impl Extractor for MessageAttributePairsExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == key)
+            .and_then(|(_, v)| v.decode_lenient())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.iter().map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+/// An [`Extractor`] for SQS message attributes that reads values according to a
+/// [`CarrierConfig`].
+///
+/// Only attributes written with the configured encoding are visible: with
+/// [`Encoding::Binary`](crate::Encoding::Binary), `get` decodes
+/// `binary_value()` as UTF-8 and ignores values that are not valid UTF-8.
+pub type ConfiguredExtractor<'a> = ConfiguredAttributeExtractor<'a, MessageAttributeValue>;
+
+/// An [`Extractor`] for subscriptions that rename attributes during delivery.
+///
+/// Some subscription configurations transform attribute names on the way to the
+/// queue, e.g. renaming `traceparent` to `otel-traceparent`. This extractor maps the
+/// propagator's field names back to the delivered names: an alias for the field is
+/// tried first, then the field name with the configured prefix, then the field name
+/// as is. `keys` reports the propagator field names.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::TransformAwareExtractor;
+///
+/// let extractor = TransformAwareExtractor::new(attrs)
+///     .with_prefix("otel-")
+///     .with_alias("tracestate", "x-state");
+/// let parent_cx = global::get_text_map_propagator(|propagator| propagator.extract(&extractor));
+/// ```
+pub struct TransformAwareExtractor<'a> {
+    attributes: &'a HashMap<String, MessageAttributeValue>,
+    prefix: Option<String>,
+    aliases: HashMap<String, String>,
+}
+
+impl<'a> TransformAwareExtractor<'a> {
+    /// Creates an extractor over `attributes` with no transform configured.
+    pub fn new(attributes: &'a HashMap<String, MessageAttributeValue>) -> Self {
+        Self {
+            attributes,
+            prefix: None,
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// Strips `prefix` from delivered attribute names.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Reads propagator field `field` from the delivered attribute `delivered_name`.
+    pub fn with_alias(
+        mut self,
+        field: impl Into<String>,
+        delivered_name: impl Into<String>,
+    ) -> Self {
+        self.aliases.insert(field.into(), delivered_name.into());
+        self
+    }
+
+    fn lookup(&self, name: &str) -> Option<&'a str> {
+        self.attributes.get(name).and_then(|v| v.decode_lenient())
+    }
+}
+
+impl Extractor for TransformAwareExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.aliases
+            .get(key)
+            .and_then(|name| self.lookup(name))
+            .or_else(|| {
+                self.prefix
+                    .as_ref()
+                    .and_then(|prefix| self.lookup(&format!("{prefix}{key}")))
+            })
+            .or_else(|| self.lookup(key))
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        for name in self.attributes.keys() {
+            let key = self
+                .aliases
+                .iter()
+                .find(|(_, delivered)| *delivered == name)
+                .map(|(field, _)| field.as_str())
+                .or_else(|| {
+                    self.prefix
+                        .as_deref()
+                        .and_then(|prefix| name.strip_prefix(prefix))
+                })
+                .unwrap_or(name);
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+}
+
+/// An [`Extractor`] that reads the SQS message attributes first and falls back to the
+/// SNS envelope embedded in the body.
+///
+/// Trace context lives in the SQS message attributes when the SNS subscription uses
+/// raw message delivery, and inside the body's SNS envelope when it does not. This
+/// extractor handles both, so consumers keep working if the delivery configuration
+/// changes. When both sources carry a key, the message attribute wins, and `keys` is
+/// the union of both sources. Binary message attributes are read as UTF-8, as by
+/// [`MessageAttributesExtractor`].
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_aws_messaging::sqs::CompositeExtractor;
+///
+/// let parent_cx = global::get_text_map_propagator(|propagator| {
+///     propagator.extract(&CompositeExtractor::from_message(&msg))
+/// });
+/// ```
+pub struct CompositeExtractor<'a> {
+    attributes: Option<&'a HashMap<String, MessageAttributeValue>>,
+    envelope: Option<SnsEnvelopeExtractor>,
+    body: Option<BodyExtractor>,
+}
+
+impl<'a> CompositeExtractor<'a> {
+    /// Creates an extractor over the message `attributes` and an optional `body`.
+    ///
+    /// A body that is not an SNS `Notification` envelope is ignored.
+    pub fn new(
+        attributes: Option<&'a HashMap<String, MessageAttributeValue>>,
+        body: Option<&str>,
+    ) -> Self {
+        Self::with_config(attributes, body, &CarrierConfig::default())
+    }
+
+    /// Like [`new`](Self::new), with the body size limit taken from
+    /// [`CarrierConfig::max_body_size`].
+    pub fn with_config(
+        attributes: Option<&'a HashMap<String, MessageAttributeValue>>,
+        body: Option<&str>,
+        config: &CarrierConfig,
+    ) -> Self {
+        let envelope =
+            body.and_then(
+                |body| match SnsEnvelopeExtractor::parse_with_config(body, config) {
+                    SnsEnvelope::Notification(extractor) => Some(extractor),
+                    _ => None,
+                },
+            );
+        Self {
+            attributes,
+            envelope,
+            body: None,
+        }
+    }
+
+    /// Creates an extractor over the attributes and body of `msg`.
+    pub fn from_message(msg: &'a Message) -> Self {
+        Self::new(msg.message_attributes(), msg.body())
+    }
+
+    /// Like [`from_message`](Self::from_message), also falling back to the body carrier
+    /// written by [`body::inject_redundant`](crate::body::inject_redundant).
+    ///
+    /// The `_otel` body field is read from the published message: the body itself
+    /// with raw message delivery, or the envelope's `Message` without it. It is only
+    /// consulted for keys that neither the message attributes nor the envelope
+    /// attributes carry, so extraction succeeds as long as either channel survives.
+    pub fn from_message_redundant(msg: &'a Message) -> Self {
+        let mut extractor = Self::from_message(msg);
+        let published = match &extractor.envelope {
+            Some(envelope) => Some(envelope.notification().message.as_str()),
+            None => msg.body(),
+        };
+        extractor.body = published.map(BodyExtractor::new);
+        extractor
+    }
+}
+
+impl Extractor for CompositeExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .and_then(|attrs| attrs.get(key))
+            .and_then(|v| v.decode_lenient())
+            .or_else(|| self.envelope.as_ref().and_then(|e| e.get(key)))
+            .or_else(|| self.body.as_ref().and_then(|b| b.get(key)))
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .attributes
+            .map(|attrs| attrs.keys().map(|s| s.as_str()).collect())
+            .unwrap_or_default();
+        let fallbacks = [
+            self.envelope.as_ref().map(Extractor::keys),
+            self.body.as_ref().map(Extractor::keys),
+        ];
+        for key in fallbacks.into_iter().flatten().flatten() {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+}
+
+/// Copies the string-valued attributes into an owned `HashMap<String, String>`.
+///
+/// The result can be persisted (e.g. in a database) and later turned back into a
+/// parent context with [`context_from_headers`](crate::headers::context_from_headers).
+/// Attributes without a string value are skipped.
+pub fn to_owned_headers(
+    attributes: &HashMap<String, MessageAttributeValue>,
+) -> HashMap<String, String> {
+    crate::core::to_owned_headers(attributes)
+}
+
+/// The message attribute and span attribute recording a message's `DelaySeconds`.
+pub const DELAY_SECONDS_ATTRIBUTE: &str = "messaging.aws_sqs.delay_seconds";
+
+/// Injects `cx` into `attributes` for a message sent with `DelaySeconds` and records
+/// the delay on both sides.
+///
+/// A delayed message leaves a gap between the producer span and the consumer span
+/// that looks like a broken trace. The delay is set as
+/// [`DELAY_SECONDS_ATTRIBUTE`] on the span in `cx`, and written as a `Number` message
+/// attribute of the same name so the consumer can read it back with
+/// [`delay_seconds`]; SQS itself does not return the delay on received messages.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let mut attributes = HashMap::new();
+/// sqs::inject_context_with_delay(&cx, 300, &mut attributes);
+/// client
+///     .send_message()
+///     .queue_url(&queue_url)
+///     .delay_seconds(300)
+///     .set_message_attributes(Some(attributes))
+///     .message_body(body)
+///     .send()
+///     .await?;
+/// ```
+pub fn inject_context_with_delay(
+    cx: &Context,
+    delay_seconds: i32,
+    attributes: &mut HashMap<String, MessageAttributeValue>,
+) {
+    cx.span().set_attribute(KeyValue::new(
+        DELAY_SECONDS_ATTRIBUTE,
+        i64::from(delay_seconds),
+    ));
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(cx, &mut MessageAttributesInjector(attributes));
+    });
+    attributes.insert(
+        DELAY_SECONDS_ATTRIBUTE.to_string(),
+        MessageAttributeValue::builder()
+            .data_type("Number")
+            .string_value(delay_seconds.to_string())
+            .build()
+            .expect("MessageAttributeValue build should not fail with valid Number data_type"),
+    );
+}
+
+/// Returns the delay recorded by [`inject_context_with_delay`], if any.
+pub fn delay_seconds(msg: &Message) -> Option<i64> {
+    msg.message_attributes()?
+        .get(DELAY_SECONDS_ATTRIBUTE)?
+        .string_value()?
+        .parse()
+        .ok()
+}
+
+/// Extracts the parent [`Context`] from the message attributes of `msg` with the
+/// global propagator.
+///
+/// A message received without `message_attribute_names` has no attribute map at all;
+/// it is read as an empty one. Like `TextMapPropagator::extract`, the fields are
+/// merged into the current context; see [`extract_isolated`] for polling loops, and
+/// for reading SNS envelope bodies too.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let parent_cx = sqs::extract_context(&msg);
+/// let span = tracer.start_with_context("sqs.process", &parent_cx);
+/// ```
+pub fn extract_context(msg: &Message) -> Context {
+    global::get_text_map_propagator(|propagator| {
+        propagator.extract(&MessageAttributesExtractor::from_message(msg))
+    })
+}
+
+/// Extracts the parent [`Context`] from optional message attributes, see
+/// [`extract_context`].
+pub fn extract_context_from(
+    attributes: Option<&HashMap<String, MessageAttributeValue>>,
+) -> Context {
+    let attributes = attributes.unwrap_or(&NO_ATTRIBUTES);
+    global::get_text_map_propagator(|propagator| {
+        propagator.extract(&MessageAttributesExtractor(attributes))
+    })
+}
+
+/// Extracts the parent [`Context`] of `msg` without inheriting anything from the
+/// current context.
+///
+/// `TextMapPropagator::extract` merges the extracted fields into
+/// `Context::current()`. In a loop that polls SQS manually, e.g. a long-running Lambda
+/// function outside the managed event-source mapping, a context still attached from
+/// the previous iteration then leaks into the next message: one that carries no trace
+/// fields silently continues the previous message's trace. This helper always starts
+/// from an empty context, and reads both raw and enveloped SNS deliveries through
+/// [`CompositeExtractor`].
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::FutureExt;
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// loop {
+///     let output = client.receive_message().queue_url(&queue_url).send().await?;
+///     for msg in output.messages.unwrap_or_default() {
+///         let parent_cx = sqs::extract_isolated(&msg);
+///         let span = tracer.start_with_context("sqs.process", &parent_cx);
+///         // Scope the context to this message's future; never hold a guard
+///         // across iterations.
+///         handle(msg).with_context(parent_cx.with_span(span)).await?;
+///     }
+/// }
+/// ```
+pub fn extract_isolated(msg: &Message) -> Context {
+    global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), &CompositeExtractor::from_message(msg))
+    })
+}
+
+/// Extracts one [`Link`] per message of a `ReceiveMessage` batch, skipping messages
+/// without a valid context.
+///
+/// The messaging semantic conventions model batch processing as a single
+/// `SpanKind::Consumer` span linked to every message's producer. Each context is read
+/// with [`extract_isolated`], so SNS envelope bodies are linked too and the current
+/// context never leaks into a link. Use
+/// [`link::extract_unique_links`](crate::link::extract_unique_links) to link a shared
+/// producer only once.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let messages = output.messages.unwrap_or_default();
+/// let span = tracer
+///     .span_builder("orders process")
+///     .with_kind(SpanKind::Consumer)
+///     .with_links(sqs::extract_links(&messages))
+///     .start(&tracer);
+/// ```
+pub fn extract_links(messages: &[Message]) -> Vec<Link> {
+    messages
+        .iter()
+        .filter_map(|msg| {
+            let span_cx = extract_isolated(msg).span().span_context().clone();
+            span_cx.is_valid().then(|| Link::with_context(span_cx))
+        })
+        .collect()
+}
+
+/// Extracts a parent [`Context`] from the W3C `traceparent` and `tracestate` message
+/// attributes of `msg`, without going through a propagator.
+///
+/// A fast path for high-throughput consumers that only use W3C trace context: the two
+/// fields are read with [`MessageAttributesExtractor::get_field`] and parsed directly,
+/// with no global propagator lookup and no `keys` allocation. Unlike
+/// [`extract_isolated`], baggage and SNS envelope bodies (raw message delivery off) are
+/// not read. Returns an empty context when `traceparent` is absent or malformed.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let parent_cx = sqs::extract_w3c(&msg);
+/// let span = tracer.start_with_context("sqs.process", &parent_cx);
+/// ```
+pub fn extract_w3c(msg: &Message) -> Context {
+    let Some(attributes) = msg.message_attributes() else {
+        return Context::new();
+    };
+    let extractor = MessageAttributesExtractor(attributes);
+    match extractor.get_field("traceparent") {
+        Some(traceparent) => {
+            crate::headers::context_from_w3c(traceparent, extractor.get_field("tracestate"))
+        }
+        None => Context::new(),
+    }
+}
+
+/// Extracts a parent [`Context`] from an X-Ray trace header stored in the
+/// `X-Amzn-Trace-Id` user message attribute.
+///
+/// Some X-Ray SDK integrations write the header as a regular message attribute
+/// instead of the `AWSTraceHeader` system attribute that AWS services set. The two
+/// paths differ on the wire: user attributes are returned in
+/// `Message::message_attributes` when requested through `message_attribute_names` and
+/// count toward the 10-attribute limit, while the system attribute is returned in
+/// `Message::attributes` when requested through `message_system_attribute_names`.
+/// Both carry the same header format, parsed by
+/// [`parse_trace_header`](crate::xray::parse_trace_header).
+///
+/// Returns `None` when the attribute is absent or does not parse.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let parent_cx = msg
+///     .message_attributes()
+///     .and_then(sqs::xray_context_from_attributes)
+///     .unwrap_or_default();
+/// ```
+pub fn xray_context_from_attributes(
+    attributes: &HashMap<String, MessageAttributeValue>,
+) -> Option<Context> {
+    attributes
+        .get(crate::xray::TRACE_HEADER)
+        .and_then(|v| v.decode_lenient())
+        .and_then(crate::xray::context_from_trace_header)
+}
+
+/// An [`Extractor`] over the system attributes of an SQS message, exposing the X-Ray
+/// `AWSTraceHeader` as [`TRACE_HEADER`](crate::xray::TRACE_HEADER).
+///
+/// AWS services and the X-Ray SDKs propagate context through the `AWSTraceHeader`
+/// system attribute, returned in `Message::attributes` when requested through
+/// `message_system_attribute_names`. X-Ray propagators read the header under its HTTP
+/// name, so that is the only key this extractor has; lookups ignore ASCII case, as
+/// propagators differ in how they spell it. Other system attributes are not exposed.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::SystemAttributesExtractor;
+///
+/// let parent_cx = msg
+///     .attributes()
+///     .map(|attrs| xray_propagator.extract(&SystemAttributesExtractor(attrs)))
+///     .unwrap_or_default();
+/// ```
+pub struct SystemAttributesExtractor<'a>(pub &'a HashMap<MessageSystemAttributeName, String>);
+
+impl Extractor for SystemAttributesExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        if !key.eq_ignore_ascii_case(crate::xray::TRACE_HEADER) {
+            return None;
+        }
+        self.0
+            .get(&MessageSystemAttributeName::AwsTraceHeader)
+            .map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        if self
+            .0
+            .contains_key(&MessageSystemAttributeName::AwsTraceHeader)
+        {
+            vec![crate::xray::TRACE_HEADER]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// An [`Injector`] that writes the X-Ray trace header as the `AWSTraceHeader` system
+/// attribute of an SQS send.
+///
+/// X-Ray consumers, and AWS services such as Lambda event source mappings, read the
+/// trace from the system attribute rather than from message attributes. This
+/// injector only responds to the [`TRACE_HEADER`](crate::xray::TRACE_HEADER) key,
+/// matched ignoring ASCII case, and ignores every other field, so the same
+/// propagator can also write the W3C fields through a [`MessageAttributesInjector`].
+/// The system attribute does not count toward the 10 message attribute limit.
+///
+/// Without an X-Ray propagator, set the header directly with
+/// [`format_trace_header`](crate::xray::format_trace_header).
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::SystemAttributesInjector;
+///
+/// let mut system_attributes = HashMap::new();
+/// xray_propagator.inject_context(&cx, &mut SystemAttributesInjector(&mut system_attributes));
+/// client
+///     .send_message()
+///     .queue_url(&queue_url)
+///     .message_body(body)
+///     .set_message_system_attributes(Some(system_attributes))
+///     .send()
+///     .await?;
+/// ```
+pub struct SystemAttributesInjector<'a>(
+    pub &'a mut HashMap<MessageSystemAttributeNameForSends, MessageSystemAttributeValue>,
+);
+
+impl Injector for SystemAttributesInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if !key.eq_ignore_ascii_case(crate::xray::TRACE_HEADER) {
+            return;
+        }
+        let value = MessageSystemAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .expect(
+                "MessageSystemAttributeValue build should not fail with valid String data_type",
+            );
+        self.0
+            .insert(MessageSystemAttributeNameForSends::AwsTraceHeader, value);
+    }
+}
+
+/// Reports the propagation state of `msg`, telling the sources apart.
+///
+/// The sources are tried in the order a consumer would fall back through them: the
+/// message attributes, the SNS envelope in the body, then the X-Ray `AWSTraceHeader`
+/// system attribute (only present when requested through
+/// `message_system_attribute_names`) or the [`TRACE_HEADER`](crate::xray::TRACE_HEADER)
+/// message attribute. The report's `source` is
+/// the first one holding a valid context; its `fields` list the trace fields found in
+/// any of them.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// for msg in output.messages.unwrap_or_default() {
+///     println!("{}", serde_json::to_string(&sqs::diagnose_message(&msg))?);
+/// }
+/// ```
+pub fn diagnose_message(msg: &Message) -> PropagationReport {
+    let extract = |extractor: &dyn Extractor| {
+        global::get_text_map_propagator(|propagator| {
+            propagator.extract_with_context(&Context::new(), extractor)
+        })
+    };
+    let mut fields = present_fields(&CompositeExtractor::from_message(msg));
+    let system_header = msg
+        .attributes()
+        .and_then(|attrs| attrs.get(&MessageSystemAttributeName::AwsTraceHeader))
+        .map(|header| {
+            (
+                MessageSystemAttributeName::AwsTraceHeader.as_str(),
+                header.as_str(),
+            )
+        });
+    let xray_header = system_header.or_else(|| {
+        let header = msg
+            .message_attributes()?
+            .get(crate::xray::TRACE_HEADER)?
+            .decode_lenient()?;
+        Some((crate::xray::TRACE_HEADER, header))
+    });
+    if let Some((field, _)) = xray_header {
+        fields.push(field.to_string());
+    }
+
+    let candidates = [
+        msg.message_attributes().map(|attrs| {
+            (
+                extract(&MessageAttributesExtractor(attrs)),
+                ContextSource::MessageAttributes,
+            )
+        }),
+        match msg.body().map(SnsEnvelopeExtractor::parse) {
+            Some(SnsEnvelope::Notification(envelope)) => {
+                Some((extract(&envelope), ContextSource::SnsEnvelope))
+            }
+            _ => None,
+        },
+        xray_header
+            .and_then(|(_, header)| crate::xray::context_from_trace_header(header))
+            .map(|cx| (cx, ContextSource::XRay)),
+    ];
+    let (cx, source) = candidates
+        .into_iter()
+        .flatten()
+        .find(|(cx, _)| cx.span().span_context().is_valid())
+        .unwrap_or((Context::new(), ContextSource::MessageAttributes));
+    PropagationReport::new(fields, &cx, source)
+}
+
+/// The span attribute recording whether a message is a redelivery.
+pub const IS_REDELIVERY_ATTRIBUTE: &str = "messaging.aws_sqs.is_redelivery";
+
+/// Returns whether `msg` has been received before, based on its
+/// `ApproximateReceiveCount` system attribute.
+///
+/// A count above 1 means an earlier receive did not delete the message, e.g. a
+/// handler failed or the visibility timeout expired. Returns `None` when the attribute
+/// was not requested through `message_system_attribute_names` or does not parse.
+pub fn is_redelivery(msg: &Message) -> Option<bool> {
+    receive_count(msg).map(|count| count > 1)
+}
+
+/// The span attribute recording how many times a message has been received.
+pub const RECEIVE_COUNT_ATTRIBUTE: &str = "messaging.aws_sqs.receive_count";
+
+/// The span attribute recording whether a message came with a receipt handle, i.e.
+/// can be deleted or have its visibility changed.
+pub const RECEIPT_HANDLE_PRESENT_ATTRIBUTE: &str = "messaging.aws_sqs.receipt_handle_present";
+
+/// Returns the `ApproximateReceiveCount` system attribute of `msg`, or `None` when it
+/// was not requested through `message_system_attribute_names` or does not parse.
+pub fn receive_count(msg: &Message) -> Option<u64> {
+    msg.attributes()?
+        .get(&MessageSystemAttributeName::ApproximateReceiveCount)?
+        .parse()
+        .ok()
+}
+
+/// Returns the metadata of `msg` as span attributes, to correlate a span with the
+/// message it handled.
+///
+/// Along with `messaging.system`, `messaging.message.id`, the send delay, and
+/// [`IS_REDELIVERY_ATTRIBUTE`], the receive count is recorded as
+/// [`RECEIVE_COUNT_ATTRIBUTE`] and whether the message has a receipt handle as
+/// [`RECEIPT_HANDLE_PRESENT_ATTRIBUTE`]. The receipt handle itself is never recorded:
+/// it changes on every receive and grants delete access. Attributes whose source is
+/// missing are left out.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let span = tracer
+///     .span_builder("orders process")
+///     .with_kind(SpanKind::Consumer)
+///     .with_attributes(sqs::message_span_attributes(&msg))
+///     .start_with_context(&tracer, &parent_cx);
+/// ```
+pub fn message_span_attributes(msg: &Message) -> Vec<KeyValue> {
+    let mut attributes = process_span_attributes(msg);
+    if let Some(count) = receive_count(msg) {
+        attributes.push(KeyValue::new(
+            RECEIVE_COUNT_ATTRIBUTE,
+            i64::try_from(count).unwrap_or(i64::MAX),
+        ));
+    }
+    attributes.push(KeyValue::new(
+        RECEIPT_HANDLE_PRESENT_ATTRIBUTE,
+        msg.receipt_handle().is_some(),
+    ));
+    attributes
+}
+
+/// Returns the time `msg` was sent, from its `SentTimestamp` system attribute.
+///
+/// Returns `None` when the attribute was not requested through
+/// `message_system_attribute_names` or does not parse.
+pub fn sent_timestamp(msg: &Message) -> Option<SystemTime> {
+    let millis: u64 = msg
+        .attributes()?
+        .get(&MessageSystemAttributeName::SentTimestamp)?
+        .parse()
+        .ok()?;
+    Some(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+/// Starts a `SpanKind::Consumer` span for `msg` as a child of `parent_cx`, anchored
+/// at the message's `SentTimestamp`.
+///
+/// The span's duration then covers the time the message spent in the queue, giving
+/// an accurate end-to-end latency. `SentTimestamp` must be requested through
+/// `message_system_attribute_names`; without it the span starts now. The timestamp is
+/// taken from the SQS clock, so clock skew with the consumer host can make the
+/// duration slightly inaccurate.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let output = client
+///     .receive_message()
+///     .queue_url(&queue_url)
+///     .message_system_attribute_names(MessageSystemAttributeName::SentTimestamp)
+///     .send()
+///     .await?;
+/// for msg in output.messages.unwrap_or_default() {
+///     let parent_cx = sqs::extract_isolated(&msg);
+///     let mut span = sqs::start_span_at_sent_time(&tracer, "sqs.dwell", &msg, &parent_cx);
+///     span.end();
+/// }
+/// ```
+pub fn start_span_at_sent_time<T: Tracer>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    msg: &Message,
+    parent_cx: &Context,
+) -> T::Span {
+    let mut builder = tracer.span_builder(name).with_kind(SpanKind::Consumer);
+    if let Some(sent) = sent_timestamp(msg) {
+        builder = builder.with_start_time(sent);
+    }
+    builder.start_with_context(tracer, parent_cx)
+}
+
+/// Starts a `SpanKind::Consumer` span for `msg` as a child of the context extracted
+/// from it with [`extract_isolated`].
+///
+/// The span records the attributes of [`message_span_attributes`]. The returned context
+/// holds the span but is not attached: the caller attaches it, or scopes a future to
+/// it, and ends the span once the message is handled.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::{FutureExt, TraceContextExt};
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// for msg in output.messages.unwrap_or_default() {
+///     let cx = sqs::start_consumer_span(&tracer, "orders process", &msg);
+///     handle(&msg).with_context(cx.clone()).await?;
+///     cx.span().end();
+/// }
+/// ```
+pub fn start_consumer_span<T>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    msg: &Message,
+) -> Context
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let parent_cx = extract_isolated(msg);
+    let span = tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Consumer)
+        .with_attributes(message_span_attributes(msg))
+        .start_with_context(tracer, &parent_cx);
+    parent_cx.with_span(span)
+}
+
+/// The maximum number of message attributes SQS accepts on a message.
+pub const MAX_MESSAGE_ATTRIBUTES: usize = 10;
+
+/// Injects `cx` into `attributes` with the global propagator while leaving room for
+/// `reserved` attributes to be added later.
+///
+/// The map is kept at no more than [`MAX_MESSAGE_ATTRIBUTES`]` - reserved` entries,
+/// counting the attributes already present. When the trace fields do not all fit,
+/// the lowest-priority ones are dropped first: `traceparent` is kept over
+/// `tracestate`, which is kept over `baggage`, which is kept over any other field.
+/// Empty fields, such as the `tracestate` of a context without trace state, are never
+/// written and take no slot. Returns the names of the dropped fields.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// // A downstream stage adds one routing attribute later.
+/// let dropped = sqs::inject_context_with_reserve(&cx, &mut attributes, 1);
+/// ```
+pub fn inject_context_with_reserve(
+    cx: &Context,
+    attributes: &mut HashMap<String, MessageAttributeValue>,
+    reserved: usize,
+) -> Vec<String> {
+    let mut fields: Vec<(String, String)> = global::get_text_map_propagator(|propagator| {
+        let mut fields = HashMap::new();
+        propagator.inject_context(cx, &mut fields);
+        fields.into_iter().collect()
+    });
+    fields.retain(|(_, value)| !value.is_empty());
+    fields.sort_by_key(|(key, _)| (field_rank(key), key.clone()));
+
+    let budget = MAX_MESSAGE_ATTRIBUTES.saturating_sub(reserved);
+    let mut dropped = Vec::new();
+    for (key, value) in fields {
+        if attributes.contains_key(&key) || attributes.len() < budget {
+            if let Err(e) = try_insert_string(attributes, &key, value) {
+                tracing::warn!(error = %e, "skipping trace context attribute");
+            }
+        } else {
+            dropped.push(key);
+        }
+    }
+    dropped
+}
+
+/// The prefix of the span attributes copied from message attributes.
+pub const MESSAGE_ATTRIBUTE_PREFIX: &str = "messaging.message.attributes.";
+
+/// Returns the allowlisted message attributes of `msg` as span attributes named
+/// `messaging.message.attributes.<name>`.
+///
+/// Only attributes named in `allowlist` with a string value (`String` or `Number`
+/// data types) are copied, so high-cardinality or sensitive attributes never reach
+/// the span unless asked for. Attributes are returned in `allowlist` order.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs;
+///
+/// let span = tracer
+///     .span_builder("sqs.process")
+///     .with_attributes(sqs::allowlisted_span_attributes(&msg, &["tenant_id", "event_type"]))
+///     .start_with_context(&tracer, &parent_cx);
+/// ```
+pub fn allowlisted_span_attributes<S: AsRef<str>>(msg: &Message, allowlist: &[S]) -> Vec<KeyValue> {
+    let Some(attributes) = msg.message_attributes() else {
+        return Vec::new();
+    };
+    allowlist
+        .iter()
+        .filter_map(|name| {
+            let name = name.as_ref();
+            let value = attributes.get(name)?.string_value()?;
+            Some(KeyValue::new(
+                format!("{MESSAGE_ATTRIBUTE_PREFIX}{name}"),
+                value.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Returns the attributes of a consumer span processing `msg`.
+pub(crate) fn process_span_attributes(msg: &Message) -> Vec<KeyValue> {
+    let mut attributes = vec![KeyValue::new("messaging.system", "aws_sqs")];
+    if let Some(message_id) = msg.message_id() {
+        attributes.push(KeyValue::new(
+            "messaging.message.id",
+            message_id.to_string(),
+        ));
+    }
+    if let Some(delay) = delay_seconds(msg) {
+        attributes.push(KeyValue::new(DELAY_SECONDS_ATTRIBUTE, delay));
+    }
+    if let Some(redelivery) = is_redelivery(msg) {
+        attributes.push(KeyValue::new(IS_REDELIVERY_ATTRIBUTE, redelivery));
+    }
+    attributes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::headers::context_from_headers;
+    use crate::test_util::{init_propagator, sampled_context, test_tracer, traceparent};
+    use opentelemetry::baggage::BaggageExt;
+    use opentelemetry::trace::{Span, SpanContext, TraceState, TracerProvider};
 
     fn make_attr(value: &str) -> MessageAttributeValue {
         MessageAttributeValue::builder()
@@ -56,6 +1166,31 @@ mod tests {
             .unwrap()
     }
 
+    #[test]
+    fn test_injector_sets_string_attribute() {
+        let mut attrs = HashMap::new();
+        let mut injector = MessageAttributesInjector(&mut attrs);
+
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+
+        assert!(attrs.contains_key("traceparent"));
+        let attr = attrs.get("traceparent").unwrap();
+        assert_eq!(attr.data_type(), "String");
+        assert_eq!(attr.string_value(), Some("00-abc123-def456-01"));
+    }
+
+    #[test]
+    fn test_injector_overwrites_existing_key() {
+        let mut attrs = HashMap::new();
+        let mut injector = MessageAttributesInjector(&mut attrs);
+
+        injector.set("key", "value1".to_string());
+        injector.set("key", "value2".to_string());
+
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(attrs.get("key").unwrap().string_value(), Some("value2"));
+    }
+
     #[test]
     fn test_extractor_gets_existing_key() {
         let mut attrs = HashMap::new();
@@ -66,6 +1201,114 @@ mod tests {
         assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
     }
 
+    #[test]
+    fn test_extract_w3c_matches_propagator_extraction() {
+        init_propagator();
+        let msg = Message::builder()
+            .message_attributes(
+                "traceparent",
+                make_attr(&traceparent(
+                    "4bf92f3577b34da6a3ce929d0e0e4736",
+                    "00f067aa0ba902b7",
+                )),
+            )
+            .message_attributes("tracestate", make_attr("dd=s:1"))
+            .build();
+
+        let fast = extract_w3c(&msg);
+        let full = extract_isolated(&msg);
+
+        assert_eq!(fast.span().span_context(), full.span().span_context());
+        assert_eq!(
+            fast.span().span_context().trace_state().get("dd"),
+            Some("s:1")
+        );
+        assert!(!extract_w3c(&Message::builder().build()).has_active_span());
+    }
+
+    #[test]
+    fn test_redundant_extraction_survives_either_channel_removed() {
+        use crate::core::AttributeInjector;
+
+        init_propagator();
+        let mut attributes = HashMap::new();
+        let body = crate::body::inject_redundant(
+            &sampled_context(),
+            &mut AttributeInjector(&mut attributes),
+            r#"{"id":1}"#,
+        );
+        let trace_id = |msg: &Message| {
+            global::get_text_map_propagator(|propagator| {
+                propagator.extract(&CompositeExtractor::from_message_redundant(msg))
+            })
+            .span()
+            .span_context()
+            .trace_id()
+            .to_string()
+        };
+
+        let attributes_stripped = Message::builder().body(&body).build();
+        let body_rewritten = Message::builder()
+            .body("{}")
+            .set_message_attributes(Some(attributes))
+            .build();
+        let envelope = serde_json::json!({"Type": "Notification", "Message": body}).to_string();
+        let enveloped = Message::builder().body(envelope).build();
+
+        for msg in [&attributes_stripped, &body_rewritten, &enveloped] {
+            assert_eq!(trace_id(msg), "4bf92f3577b34da6a3ce929d0e0e4736");
+        }
+        assert!(CompositeExtractor::from_message(&attributes_stripped)
+            .keys()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_pairs_extractor_reads_list_representation() {
+        let pairs = vec![
+            ("event_type".to_string(), make_attr("order.created")),
+            ("traceparent".to_string(), make_attr("00-abc123-def456-01")),
+        ];
+
+        let extractor = MessageAttributePairsExtractor(&pairs);
+
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+        assert_eq!(extractor.get("tracestate"), None);
+        assert_eq!(extractor.keys(), vec!["event_type", "traceparent"]);
+    }
+
+    #[test]
+    fn test_allowlisted_span_attributes_excludes_other_attributes() {
+        let msg = Message::builder()
+            .message_attributes("tenant_id", make_attr("acme"))
+            .message_attributes("email", make_attr("someone@example.com"))
+            .message_attributes("traceparent", make_attr("00-abc123-def456-01"))
+            .build();
+
+        let attributes = allowlisted_span_attributes(&msg, &["tenant_id", "event_type"]);
+
+        assert_eq!(
+            attributes,
+            vec![KeyValue::new(
+                "messaging.message.attributes.tenant_id",
+                "acme"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_generic_injector_writes_sqs_attributes() {
+        let mut attrs: HashMap<String, MessageAttributeValue> = HashMap::new();
+        crate::core::AttributeInjector(&mut attrs)
+            .set("traceparent", "00-abc123-def456-01".to_string());
+
+        assert_eq!(attrs["traceparent"].data_type(), "String");
+        assert_eq!(
+            MessageAttributesExtractor(&attrs).get("traceparent"),
+            Some("00-abc123-def456-01")
+        );
+    }
+
     #[test]
     fn test_extractor_returns_none_for_missing_key() {
         let attrs = HashMap::new();
@@ -86,5 +1329,563 @@ mod tests {
 
         assert_eq!(keys, vec!["key1", "key2"]);
     }
-}
 
+    #[test]
+    fn test_to_owned_headers_round_trips_context() {
+        init_propagator();
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "traceparent".to_string(),
+            make_attr(&traceparent(
+                "4bf92f3577b34da6a3ce929d0e0e4736",
+                "00f067aa0ba902b7",
+            )),
+        );
+        attrs.insert(
+            "payload".to_string(),
+            MessageAttributeValue::builder()
+                .data_type("Binary")
+                .binary_value(aws_sdk_sqs::primitives::Blob::new(vec![1, 2, 3]))
+                .build()
+                .unwrap(),
+        );
+
+        let headers = to_owned_headers(&attrs);
+        let cx = context_from_headers(&headers);
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(
+            cx.span().span_context().trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+
+    #[test]
+    fn test_inject_context_with_delay_records_delay() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let span = provider.tracer("test").start("send");
+        let cx = Context::current_with_span(span);
+        let mut attrs = HashMap::new();
+
+        inject_context_with_delay(&cx, 300, &mut attrs);
+        cx.span().end();
+
+        assert!(attrs.contains_key("traceparent"));
+        let msg = Message::builder()
+            .set_message_attributes(Some(attrs))
+            .build();
+        assert_eq!(delay_seconds(&msg), Some(300));
+        let spans = exporter.get_finished_spans().unwrap();
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new(DELAY_SECONDS_ATTRIBUTE, 300_i64)));
+    }
+
+    #[test]
+    fn test_delay_seconds_without_attribute_is_none() {
+        assert_eq!(
+            delay_seconds(&Message::builder().body("hello").build()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_diagnose_message_detects_source() {
+        init_propagator();
+        let tp = traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7");
+        let envelope = serde_json::json!({
+            "Type": "Notification",
+            "Message": "hello",
+            "MessageAttributes": {"traceparent": {"Type": "String", "Value": tp}},
+        })
+        .to_string();
+        let enveloped = Message::builder().body(envelope).build();
+        let xray = Message::builder()
+            .attributes(
+                MessageSystemAttributeName::AwsTraceHeader,
+                "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=0",
+            )
+            .build();
+
+        let report = diagnose_message(&enveloped);
+        assert_eq!(report.source, Some(ContextSource::SnsEnvelope));
+        assert_eq!(report.fields, ["traceparent"]);
+        assert_eq!(report.sampled, Some(true));
+
+        let report = diagnose_message(&xray);
+        assert_eq!(report.source, Some(ContextSource::XRay));
+        assert_eq!(report.fields, ["AWSTraceHeader"]);
+        assert_eq!(report.sampled, Some(false));
+
+        let report = diagnose_message(&Message::builder().body("hello").build());
+        assert!(!report.valid);
+        assert_eq!(report.source, None);
+    }
+
+    #[test]
+    fn test_diagnose_message_ignores_the_ambient_context() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+        let msg = Message::builder()
+            .message_attributes("priority", make_attr("high"))
+            .build();
+
+        let report = diagnose_message(&msg);
+
+        assert!(!report.valid);
+        assert_eq!(report.source, None);
+    }
+
+    fn envelope_body(attributes: serde_json::Value) -> String {
+        serde_json::json!({
+            "Type": "Notification",
+            "Message": "hello",
+            "MessageAttributes": attributes,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_composite_extractor_reads_attributes() {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "traceparent".to_string(),
+            make_attr("00-from-attributes-01"),
+        );
+
+        let extractor = CompositeExtractor::new(Some(&attrs), Some("plain body"));
+
+        assert_eq!(extractor.get("traceparent"), Some("00-from-attributes-01"));
+        assert_eq!(extractor.keys(), ["traceparent"]);
+    }
+
+    #[test]
+    fn test_composite_extractor_falls_back_to_envelope() {
+        let body = envelope_body(serde_json::json!({
+            "traceparent": {"Type": "String", "Value": "00-from-envelope-01"},
+        }));
+        let msg = Message::builder().body(body).build();
+
+        let extractor = CompositeExtractor::from_message(&msg);
+
+        assert_eq!(extractor.get("traceparent"), Some("00-from-envelope-01"));
+        assert_eq!(extractor.keys(), ["traceparent"]);
+        assert_eq!(CompositeExtractor::new(None, None).get("traceparent"), None);
+    }
+
+    #[test]
+    fn test_composite_extractor_prefers_attributes_over_envelope() {
+        let body = envelope_body(serde_json::json!({
+            "traceparent": {"Type": "String", "Value": "00-from-envelope-01"},
+            "baggage": {"Type": "String", "Value": "tenant=acme"},
+        }));
+        let msg = Message::builder()
+            .body(body)
+            .message_attributes("traceparent", make_attr("00-from-attributes-01"))
+            .build();
+
+        let extractor = CompositeExtractor::from_message(&msg);
+
+        assert_eq!(extractor.get("traceparent"), Some("00-from-attributes-01"));
+        assert_eq!(extractor.get("baggage"), Some("tenant=acme"));
+        let mut keys = extractor.keys();
+        keys.sort_unstable();
+        assert_eq!(keys, ["baggage", "traceparent"]);
+    }
+
+    #[test]
+    fn test_composite_extractor_reads_binary_attributes() {
+        init_propagator();
+        let traceparent = traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7");
+        let body = envelope_body(serde_json::json!({
+            "traceparent": {"Type": "String", "Value": "00-from-envelope-01"},
+        }));
+        let binary = MessageAttributeValue::from_binary(traceparent.clone().into_bytes());
+        let msg = Message::builder()
+            .body(body)
+            .message_attributes("traceparent", binary.clone())
+            .build();
+
+        let extractor = CompositeExtractor::from_message(&msg);
+
+        assert_eq!(extractor.get("traceparent"), Some(traceparent.as_str()));
+        assert_eq!(
+            TransformAwareExtractor::new(msg.message_attributes().unwrap()).get("traceparent"),
+            Some(traceparent.as_str())
+        );
+        let pairs = [("traceparent".to_string(), binary)];
+        assert_eq!(
+            MessageAttributePairsExtractor(&pairs).get("traceparent"),
+            Some(traceparent.as_str())
+        );
+        assert_eq!(
+            extract_isolated(&msg).span().span_context(),
+            extract_context(&msg).span().span_context()
+        );
+        assert!(extract_isolated(&msg).span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_message_span_attributes_of_full_message() {
+        let msg = Message::builder()
+            .message_id("m-1")
+            .receipt_handle("AQEB-handle")
+            .body("hello")
+            .md5_of_body("5d41402abc4b2a76b9719d911017c592")
+            .message_attributes("traceparent", make_attr("00-abc123-def456-01"))
+            .attributes(MessageSystemAttributeName::ApproximateReceiveCount, "3")
+            .attributes(MessageSystemAttributeName::SentTimestamp, "1700000000000")
+            .build();
+
+        assert_eq!(
+            message_span_attributes(&msg),
+            vec![
+                KeyValue::new("messaging.system", "aws_sqs"),
+                KeyValue::new("messaging.message.id", "m-1"),
+                KeyValue::new(IS_REDELIVERY_ATTRIBUTE, true),
+                KeyValue::new(RECEIVE_COUNT_ATTRIBUTE, 3),
+                KeyValue::new(RECEIPT_HANDLE_PRESENT_ATTRIBUTE, true),
+            ]
+        );
+        assert!(!format!("{:?}", message_span_attributes(&msg)).contains("AQEB-handle"));
+    }
+
+    #[test]
+    fn test_message_span_attributes_of_bare_message() {
+        assert_eq!(
+            message_span_attributes(&Message::builder().build()),
+            vec![
+                KeyValue::new("messaging.system", "aws_sqs"),
+                KeyValue::new(RECEIPT_HANDLE_PRESENT_ATTRIBUTE, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_start_consumer_span_is_child_of_producer() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let msg = Message::builder()
+            .message_id("m-1")
+            .message_attributes(
+                "traceparent",
+                make_attr(&traceparent(
+                    "4bf92f3577b34da6a3ce929d0e0e4736",
+                    "00f067aa0ba902b7",
+                )),
+            )
+            .build();
+
+        let cx = start_consumer_span(&provider.tracer("test"), "process", &msg);
+        cx.span().end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].span_kind, SpanKind::Consumer);
+        assert_eq!(
+            spans[0].span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(spans[0].parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.message.id", "m-1")));
+    }
+
+    #[test]
+    fn test_extract_links_skips_messages_without_context() {
+        init_propagator();
+        let with_context = |span_id: &str| {
+            Message::builder()
+                .message_attributes(
+                    "traceparent",
+                    make_attr(&traceparent("4bf92f3577b34da6a3ce929d0e0e4736", span_id)),
+                )
+                .build()
+        };
+        let messages = [
+            with_context("00f067aa0ba902b7"),
+            Message::builder().body("hello").build(),
+            with_context("b7ad6b7169203331"),
+            Message::builder()
+                .message_attributes("traceparent", make_attr("garbage"))
+                .build(),
+        ];
+
+        let links = extract_links(&messages);
+
+        let span_ids: Vec<_> = links
+            .iter()
+            .map(|link| link.span_context.span_id().to_string())
+            .collect();
+        assert_eq!(span_ids, ["00f067aa0ba902b7", "b7ad6b7169203331"]);
+        assert!(extract_links(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_case_insensitive_extractor_matches_recased_names() {
+        let mut attrs = HashMap::new();
+        attrs.insert("Traceparent".to_string(), make_attr("00-abc123-def456-01"));
+        attrs.insert("TraceState".to_string(), make_attr("dd=s:1"));
+
+        let extractor = MessageAttributesExtractor::case_insensitive(&attrs);
+
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+        assert_eq!(extractor.get("tracestate"), Some("dd=s:1"));
+        assert_eq!(extractor.get("baggage"), None);
+        let mut keys = extractor.keys();
+        keys.sort_unstable();
+        assert_eq!(keys, ["traceparent", "tracestate"]);
+        assert_eq!(MessageAttributesExtractor(&attrs).get("traceparent"), None);
+    }
+
+    #[test]
+    fn test_case_insensitive_extractor_prefers_exact_match() {
+        let mut attrs = HashMap::new();
+        attrs.insert("traceparent".to_string(), make_attr("exact"));
+        attrs.insert("TRACEPARENT".to_string(), make_attr("recased"));
+
+        let extractor = MessageAttributesExtractor::case_insensitive(&attrs);
+
+        assert_eq!(extractor.get("traceparent"), Some("exact"));
+        assert_eq!(extractor.get("TRACEPARENT"), Some("recased"));
+    }
+
+    #[test]
+    fn test_extractor_from_message_without_attributes() {
+        let msg = Message::builder().body("hello").build();
+        assert!(msg.message_attributes().is_none());
+
+        let extractor = MessageAttributesExtractor::from_message(&msg);
+
+        assert_eq!(extractor.get("traceparent"), None);
+        assert!(extractor.keys().is_empty());
+
+        let msg = Message::builder()
+            .message_attributes("traceparent", make_attr("00-abc123-def456-01"))
+            .build();
+        let extractor = MessageAttributesExtractor::from_message(&msg);
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+    }
+
+    #[test]
+    fn test_extract_context_with_and_without_attributes() {
+        init_propagator();
+        let tp = traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7");
+        let msg = Message::builder()
+            .message_attributes("traceparent", make_attr(&tp))
+            .build();
+
+        let cx = extract_context(&msg);
+        assert_eq!(
+            cx.span().span_context().span_id().to_string(),
+            "00f067aa0ba902b7"
+        );
+
+        let cx = extract_context(&Message::builder().body("hello").build());
+        assert!(!cx.span().span_context().is_valid());
+        assert!(!extract_context_from(None).span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_system_attributes_extractor_surfaces_trace_header() {
+        let header = "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1";
+        let attrs = HashMap::from([
+            (
+                MessageSystemAttributeName::AwsTraceHeader,
+                header.to_string(),
+            ),
+            (
+                MessageSystemAttributeName::ApproximateReceiveCount,
+                "1".to_string(),
+            ),
+        ]);
+
+        let extractor = SystemAttributesExtractor(&attrs);
+
+        assert_eq!(extractor.get("X-Amzn-Trace-Id"), Some(header));
+        assert_eq!(extractor.get("x-amzn-trace-id"), Some(header));
+        assert_eq!(extractor.get("ApproximateReceiveCount"), None);
+        assert_eq!(extractor.keys(), ["X-Amzn-Trace-Id"]);
+        let cx = crate::xray::context_from_trace_header(extractor.get("X-Amzn-Trace-Id").unwrap())
+            .unwrap();
+        assert_eq!(
+            cx.span().span_context().span_id().to_string(),
+            "53995c3f42cd8ad8"
+        );
+        assert!(SystemAttributesExtractor(&HashMap::new()).keys().is_empty());
+    }
+
+    #[test]
+    fn test_system_attributes_injector_writes_trace_header() {
+        let header = "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1";
+        let mut attrs = HashMap::new();
+        let mut injector = SystemAttributesInjector(&mut attrs);
+
+        injector.set("traceparent", "00-abc123-def456-01".to_string());
+        injector.set("x-amzn-trace-id", header.to_string());
+
+        assert_eq!(attrs.len(), 1);
+        let value = &attrs[&MessageSystemAttributeNameForSends::AwsTraceHeader];
+        assert_eq!(
+            MessageSystemAttributeNameForSends::AwsTraceHeader.as_str(),
+            "AWSTraceHeader"
+        );
+        assert_eq!(value.data_type(), "String");
+        assert_eq!(value.string_value(), Some(header));
+    }
+
+    #[test]
+    fn test_xray_context_from_user_attribute() {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "X-Amzn-Trace-Id".to_string(),
+            make_attr("Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1"),
+        );
+
+        let cx = xray_context_from_attributes(&attrs).unwrap();
+
+        let span_cx = cx.span().span_context().clone();
+        assert_eq!(
+            span_cx.trace_id().to_string(),
+            "5759e988bd862e3fe1be46a994272793"
+        );
+        assert_eq!(span_cx.span_id().to_string(), "53995c3f42cd8ad8");
+        assert!(xray_context_from_attributes(&HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_extract_isolated_does_not_inherit_attached_context() {
+        init_propagator();
+        let traced = Message::builder()
+            .message_attributes(
+                "traceparent",
+                make_attr(&traceparent(
+                    "4bf92f3577b34da6a3ce929d0e0e4736",
+                    "00f067aa0ba902b7",
+                )),
+            )
+            .build();
+        let untraced = Message::builder().body("hello").build();
+
+        // Simulates a guard leaked from the previous iteration.
+        let _guard = extract_isolated(&traced).attach();
+
+        assert!(Context::current().span().span_context().is_valid());
+        assert!(!extract_isolated(&untraced).span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_is_redelivery_from_receive_count() {
+        let received = |count: &str| {
+            Message::builder()
+                .attributes(MessageSystemAttributeName::ApproximateReceiveCount, count)
+                .build()
+        };
+
+        assert_eq!(is_redelivery(&received("1")), Some(false));
+        assert_eq!(is_redelivery(&received("3")), Some(true));
+        assert_eq!(is_redelivery(&received("n/a")), None);
+        assert_eq!(is_redelivery(&Message::builder().build()), None);
+    }
+
+    #[test]
+    fn test_span_starts_at_sent_timestamp() {
+        let (provider, exporter) = test_tracer();
+        let msg = Message::builder()
+            .attributes(MessageSystemAttributeName::SentTimestamp, "1700000000123")
+            .build();
+
+        let mut span =
+            start_span_at_sent_time(&provider.tracer("test"), "dwell", &msg, &Context::new());
+        span.end();
+
+        let sent = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        assert_eq!(sent_timestamp(&msg), Some(sent));
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].start_time, sent);
+        assert_eq!(spans[0].span_kind, SpanKind::Consumer);
+    }
+
+    #[test]
+    fn test_transform_aware_extractor_handles_renamed_traceparent() {
+        init_propagator();
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "otel-traceparent".to_string(),
+            make_attr(&traceparent(
+                "4bf92f3577b34da6a3ce929d0e0e4736",
+                "00f067aa0ba902b7",
+            )),
+        );
+        attrs.insert("x-state".to_string(), make_attr("dd=s:1"));
+
+        let aliased = TransformAwareExtractor::new(&attrs)
+            .with_alias("traceparent", "otel-traceparent")
+            .with_alias("tracestate", "x-state");
+        let prefixed = TransformAwareExtractor::new(&attrs).with_prefix("otel-");
+
+        assert_eq!(aliased.get("tracestate"), Some("dd=s:1"));
+        let mut keys = aliased.keys();
+        keys.sort();
+        assert_eq!(keys, vec!["traceparent", "tracestate"]);
+        for extractor in [&aliased, &prefixed] {
+            let cx = global::get_text_map_propagator(|propagator| propagator.extract(extractor));
+            assert_eq!(
+                cx.span().span_context().trace_id().to_string(),
+                "4bf92f3577b34da6a3ce929d0e0e4736"
+            );
+        }
+        assert_eq!(
+            TransformAwareExtractor::new(&attrs).get("traceparent"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_inject_context_with_reserve_drops_lowest_priority_fields() {
+        init_propagator();
+        let span_cx = sampled_context().span().span_context().clone();
+        let cx = Context::new()
+            .with_remote_span_context(SpanContext::new(
+                span_cx.trace_id(),
+                span_cx.span_id(),
+                span_cx.trace_flags(),
+                true,
+                TraceState::from_key_value([("dd", "s:1")]).unwrap(),
+            ))
+            .with_baggage(vec![KeyValue::new("user", "1")]);
+        let mut attrs: HashMap<String, MessageAttributeValue> = (0..7)
+            .map(|i| (format!("business-{i}"), make_attr("x")))
+            .collect();
+
+        let dropped = inject_context_with_reserve(&cx, &mut attrs, 1);
+
+        assert_eq!(attrs.len(), MAX_MESSAGE_ATTRIBUTES - 1);
+        assert!(attrs.contains_key("traceparent"));
+        assert!(attrs.contains_key("tracestate"));
+        assert_eq!(dropped, vec!["baggage"]);
+    }
+
+    #[test]
+    fn test_inject_context_with_reserve_skips_empty_tracestate() {
+        init_propagator();
+        let mut attrs = HashMap::new();
+
+        let dropped = inject_context_with_reserve(&sampled_context(), &mut attrs, 0);
+
+        assert!(dropped.is_empty());
+        assert_eq!(attrs.keys().collect::<Vec<_>>(), ["traceparent"]);
+
+        let cx = sampled_context().with_baggage(vec![KeyValue::new("user", "1")]);
+        let mut attrs: HashMap<String, MessageAttributeValue> = (0..7)
+            .map(|i| (format!("business-{i}"), make_attr("x")))
+            .collect();
+
+        let dropped = inject_context_with_reserve(&cx, &mut attrs, 1);
+
+        assert!(dropped.is_empty());
+        assert!(attrs.contains_key("baggage"));
+        assert!(!attrs.contains_key("tracestate"));
+    }
+}
@@ -0,0 +1,311 @@
+//! The SQS operations used by [`TracedSqsConsumer`](super::consumer::TracedSqsConsumer).
+//!
+//! The consumer is generic over [`SqsReceiveClient`], implemented for the SDK
+//! [`Client`]. Tests can swap in [`InMemorySqsClient`] (`testing` feature), an
+//! in-memory queue that records what the consumer deleted, released, and sent.
+
+use aws_sdk_sqs::operation::delete_message_batch::DeleteMessageBatchOutput;
+use aws_sdk_sqs::types::{
+    DeleteMessageBatchRequestEntry, Message, MessageAttributeValue, MessageSystemAttributeName,
+};
+use aws_sdk_sqs::Client;
+use std::collections::HashMap;
+use std::future::Future;
+
+/// The SQS operations a traced consumer needs.
+pub trait SqsReceiveClient {
+    /// Receives up to `max_messages` messages from `queue_url`, waiting up to
+    /// `wait_time_seconds` for one to arrive.
+    ///
+    /// Implementations return all message attributes and the
+    /// `ApproximateReceiveCount` system attribute.
+    fn receive(
+        &self,
+        queue_url: &str,
+        max_messages: i32,
+        wait_time_seconds: i32,
+    ) -> impl Future<Output = Result<Vec<Message>, aws_sdk_sqs::Error>> + Send;
+
+    /// Deletes the message with `receipt_handle` from `queue_url`.
+    fn delete(
+        &self,
+        queue_url: &str,
+        receipt_handle: &str,
+    ) -> impl Future<Output = Result<(), aws_sdk_sqs::Error>> + Send;
+
+    /// Deletes up to ten messages from `queue_url` in one request.
+    fn delete_batch(
+        &self,
+        queue_url: &str,
+        entries: Vec<DeleteMessageBatchRequestEntry>,
+    ) -> impl Future<Output = Result<DeleteMessageBatchOutput, aws_sdk_sqs::Error>> + Send;
+
+    /// Sets the visibility timeout of the message with `receipt_handle`.
+    fn change_visibility(
+        &self,
+        queue_url: &str,
+        receipt_handle: &str,
+        visibility_timeout: i32,
+    ) -> impl Future<Output = Result<(), aws_sdk_sqs::Error>> + Send;
+
+    /// Sends a message with `body` and `attributes` to `queue_url`.
+    fn send(
+        &self,
+        queue_url: &str,
+        body: Option<String>,
+        attributes: Option<HashMap<String, MessageAttributeValue>>,
+    ) -> impl Future<Output = Result<(), aws_sdk_sqs::Error>> + Send;
+}
+
+impl SqsReceiveClient for Client {
+    async fn receive(
+        &self,
+        queue_url: &str,
+        max_messages: i32,
+        wait_time_seconds: i32,
+    ) -> Result<Vec<Message>, aws_sdk_sqs::Error> {
+        let output = self
+            .receive_message()
+            .queue_url(queue_url)
+            .max_number_of_messages(max_messages)
+            .wait_time_seconds(wait_time_seconds)
+            .message_attribute_names("All")
+            .message_system_attribute_names(MessageSystemAttributeName::ApproximateReceiveCount)
+            .send()
+            .await?;
+        Ok(output.messages.unwrap_or_default())
+    }
+
+    async fn delete(
+        &self,
+        queue_url: &str,
+        receipt_handle: &str,
+    ) -> Result<(), aws_sdk_sqs::Error> {
+        self.delete_message()
+            .queue_url(queue_url)
+            .receipt_handle(receipt_handle)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_batch(
+        &self,
+        queue_url: &str,
+        entries: Vec<DeleteMessageBatchRequestEntry>,
+    ) -> Result<DeleteMessageBatchOutput, aws_sdk_sqs::Error> {
+        Ok(self
+            .delete_message_batch()
+            .queue_url(queue_url)
+            .set_entries(Some(entries))
+            .send()
+            .await?)
+    }
+
+    async fn change_visibility(
+        &self,
+        queue_url: &str,
+        receipt_handle: &str,
+        visibility_timeout: i32,
+    ) -> Result<(), aws_sdk_sqs::Error> {
+        self.change_message_visibility()
+            .queue_url(queue_url)
+            .receipt_handle(receipt_handle)
+            .visibility_timeout(visibility_timeout)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn send(
+        &self,
+        queue_url: &str,
+        body: Option<String>,
+        attributes: Option<HashMap<String, MessageAttributeValue>>,
+    ) -> Result<(), aws_sdk_sqs::Error> {
+        self.send_message()
+            .queue_url(queue_url)
+            .set_message_body(body)
+            .set_message_attributes(attributes)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub use fake::InMemorySqsClient;
+
+#[cfg(any(test, feature = "testing"))]
+mod fake {
+    use super::*;
+    use aws_sdk_sqs::types::DeleteMessageBatchResultEntry;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct State {
+        queues: HashMap<String, VecDeque<Message>>,
+        in_flight: HashMap<String, (String, Message)>,
+        deleted: Vec<String>,
+        next_id: u64,
+    }
+
+    /// An [`SqsReceiveClient`] over in-memory queues.
+    ///
+    /// Received messages are in flight until deleted; changing their visibility
+    /// returns them to the front of their queue. Visibility timeouts do not expire on
+    /// their own. Messages sent to a queue, e.g. a dead-letter queue, can be
+    /// inspected with [`messages`](Self::messages).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use opentelemetry_aws_messaging::sqs::client::InMemorySqsClient;
+    ///
+    /// let client = InMemorySqsClient::default();
+    /// client.push(queue_url, message);
+    /// let consumer = TracedSqsConsumer::new(client, queue_url);
+    /// consumer.process(handler).await?;
+    /// assert_eq!(consumer.client().deleted().len(), 1);
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct InMemorySqsClient {
+        state: Mutex<State>,
+    }
+
+    impl InMemorySqsClient {
+        /// Adds `message` to the back of `queue_url`.
+        ///
+        /// A message without a receipt handle is given one, `handle-<n>`.
+        pub fn push(&self, queue_url: &str, message: Message) {
+            let mut state = self.state.lock().unwrap();
+            let message = if message.receipt_handle().is_some() {
+                message
+            } else {
+                state.next_id += 1;
+                let mut message = message;
+                message.receipt_handle = Some(format!("handle-{}", state.next_id));
+                message
+            };
+            state
+                .queues
+                .entry(queue_url.to_string())
+                .or_default()
+                .push_back(message);
+        }
+
+        /// Returns the messages waiting in `queue_url`, in order.
+        pub fn messages(&self, queue_url: &str) -> Vec<Message> {
+            let state = self.state.lock().unwrap();
+            state
+                .queues
+                .get(queue_url)
+                .map(|queue| queue.iter().cloned().collect())
+                .unwrap_or_default()
+        }
+
+        /// Returns the receipt handles of the deleted messages, in order.
+        pub fn deleted(&self) -> Vec<String> {
+            self.state.lock().unwrap().deleted.clone()
+        }
+
+        fn remove_in_flight(&self, receipt_handle: &str) -> bool {
+            let mut state = self.state.lock().unwrap();
+            if state.in_flight.remove(receipt_handle).is_some() {
+                state.deleted.push(receipt_handle.to_string());
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    impl SqsReceiveClient for InMemorySqsClient {
+        async fn receive(
+            &self,
+            queue_url: &str,
+            max_messages: i32,
+            _wait_time_seconds: i32,
+        ) -> Result<Vec<Message>, aws_sdk_sqs::Error> {
+            let mut state = self.state.lock().unwrap();
+            let queue = state.queues.entry(queue_url.to_string()).or_default();
+            let count = usize::try_from(max_messages).unwrap_or(0).min(queue.len());
+            let received: Vec<Message> = queue.drain(..count).collect();
+            for msg in &received {
+                let receipt_handle = msg.receipt_handle().unwrap_or_default().to_string();
+                state
+                    .in_flight
+                    .insert(receipt_handle, (queue_url.to_string(), msg.clone()));
+            }
+            Ok(received)
+        }
+
+        async fn delete(
+            &self,
+            _queue_url: &str,
+            receipt_handle: &str,
+        ) -> Result<(), aws_sdk_sqs::Error> {
+            self.remove_in_flight(receipt_handle);
+            Ok(())
+        }
+
+        async fn delete_batch(
+            &self,
+            _queue_url: &str,
+            entries: Vec<DeleteMessageBatchRequestEntry>,
+        ) -> Result<DeleteMessageBatchOutput, aws_sdk_sqs::Error> {
+            let mut output = DeleteMessageBatchOutput::builder();
+            for entry in entries {
+                if self.remove_in_flight(entry.receipt_handle()) {
+                    output = output.successful(
+                        DeleteMessageBatchResultEntry::builder()
+                            .id(entry.id())
+                            .build()
+                            .expect("id is set"),
+                    );
+                }
+            }
+            Ok(output
+                .set_failed(Some(Vec::new()))
+                .build()
+                .expect("successful and failed are set"))
+        }
+
+        async fn change_visibility(
+            &self,
+            _queue_url: &str,
+            receipt_handle: &str,
+            _visibility_timeout: i32,
+        ) -> Result<(), aws_sdk_sqs::Error> {
+            let mut state = self.state.lock().unwrap();
+            if let Some((queue_url, msg)) = state.in_flight.remove(receipt_handle) {
+                state.queues.entry(queue_url).or_default().push_front(msg);
+            }
+            Ok(())
+        }
+
+        async fn send(
+            &self,
+            queue_url: &str,
+            body: Option<String>,
+            attributes: Option<HashMap<String, MessageAttributeValue>>,
+        ) -> Result<(), aws_sdk_sqs::Error> {
+            let id = {
+                let mut state = self.state.lock().unwrap();
+                state.next_id += 1;
+                state.next_id
+            };
+            self.push(
+                queue_url,
+                Message::builder()
+                    .message_id(format!("msg-{id}"))
+                    .receipt_handle(format!("handle-{id}"))
+                    .set_body(body)
+                    .set_message_attributes(attributes)
+                    .build(),
+            );
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,754 @@
+//! A high-level SQS consumer that traces every message it processes.
+//!
+//! [`TracedSqsConsumer`] packages the receive → extract → span → process → delete
+//! pattern so applications only provide the message handler. It is behind the
+//! `consumer` feature so the low-level carriers stay dependency-light.
+//!
+//! Applications that settle messages themselves use [`TracedSqsConsumer::ack`] and
+//! [`TracedSqsConsumer::nack`], which record the outcome as a span event, or
+//! [`TracedSqsConsumer::delete_batch`] for high-volume consumers.
+//!
+//! What happens to a message whose handler fails is governed by the consumer's
+//! [`FailurePolicy`].
+//!
+//! The consumer is generic over the [`SqsReceiveClient`] it calls, the SDK `Client`
+//! by default, so it can be tested against an in-memory fake.
+
+use super::client::SqsReceiveClient;
+use super::{allowlisted_span_attributes, extract_isolated, process_span_attributes};
+use crate::semconv;
+use aws_sdk_sqs::types::{DeleteMessageBatchRequestEntry, Message};
+use aws_sdk_sqs::Client;
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::trace::{FutureExt, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::future::Future;
+
+/// The maximum number of entries SQS accepts in one batch request.
+pub const MAX_BATCH_SIZE: usize = 10;
+
+/// What [`TracedSqsConsumer::process`] does with a message whose handler fails.
+///
+/// The chosen action is recorded on the message's span as a `messaging.failure`
+/// event with a `messaging.failure.action` attribute (`skip`, `dlq`, or `abort`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Leaves the message on the queue, to be redelivered after the visibility timeout
+    /// (or moved by the queue's redrive policy), and continues with the next message.
+    #[default]
+    Skip,
+    /// Sends the message, with its body and message attributes, to the queue at this
+    /// URL, deletes it from the source queue, and continues with the next message.
+    ///
+    /// If the message cannot be sent it is left on the source queue.
+    Dlq(String),
+    /// Leaves the message on the queue and stops processing the batch, returning
+    /// [`ProcessError::Aborted`]. The remaining messages of the batch are redelivered
+    /// after the visibility timeout.
+    Abort,
+}
+
+impl FailurePolicy {
+    fn action(&self) -> &'static str {
+        match self {
+            FailurePolicy::Skip => "skip",
+            FailurePolicy::Dlq(_) => "dlq",
+            FailurePolicy::Abort => "abort",
+        }
+    }
+}
+
+/// An error returned by [`TracedSqsConsumer::process`].
+#[derive(Debug)]
+pub enum ProcessError<E> {
+    /// Receiving the batch failed.
+    Sqs(aws_sdk_sqs::Error),
+    /// A handler failed under [`FailurePolicy::Abort`].
+    Aborted {
+        /// The id of the message whose handler failed.
+        message_id: Option<String>,
+        /// The handler's error.
+        error: E,
+    },
+}
+
+impl<E> From<aws_sdk_sqs::Error> for ProcessError<E> {
+    fn from(e: aws_sdk_sqs::Error) -> Self {
+        ProcessError::Sqs(e)
+    }
+}
+
+impl<E: Display> Display for ProcessError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::Sqs(e) => write!(f, "failed to receive messages: {e}"),
+            ProcessError::Aborted { message_id, error } => write!(
+                f,
+                "processing aborted at message {}: {error}",
+                message_id.as_deref().unwrap_or("<unknown>")
+            ),
+        }
+    }
+}
+
+impl<E: Display + Debug> std::error::Error for ProcessError<E> {}
+
+/// Wraps an SQS client and queue URL, running a handler for each received message
+/// inside a consumer span parented to the producer's context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::consumer::TracedSqsConsumer;
+///
+/// let consumer = TracedSqsConsumer::new(client, queue_url);
+/// loop {
+///     consumer
+///         .process(|msg| async move {
+///             println!("Received: {:?}", msg.body());
+///             Ok::<_, anyhow::Error>(())
+///         })
+///         .await?;
+/// }
+/// ```
+pub struct TracedSqsConsumer<C = Client> {
+    client: C,
+    queue_url: String,
+    tracer: BoxedTracer,
+    max_messages: i32,
+    wait_time_seconds: i32,
+    span_attributes: Vec<String>,
+    failure_policy: FailurePolicy,
+}
+
+impl<C: SqsReceiveClient> TracedSqsConsumer<C> {
+    /// Creates a consumer for `queue_url` using the global tracer provider.
+    pub fn new(client: C, queue_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            queue_url: queue_url.into(),
+            tracer: global::tracer(env!("CARGO_PKG_NAME")),
+            max_messages: 10,
+            wait_time_seconds: 20,
+            span_attributes: Vec::new(),
+            failure_policy: FailurePolicy::default(),
+        }
+    }
+
+    /// Returns the client messages are received through.
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Uses `tracer` instead of the global tracer to create consumer spans.
+    pub fn with_tracer(mut self, tracer: BoxedTracer) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Sets the maximum number of messages received per call (1-10, default 10).
+    pub fn with_max_messages(mut self, max_messages: i32) -> Self {
+        self.max_messages = max_messages;
+        self
+    }
+
+    /// Sets the long-polling wait time in seconds (0-20, default 20).
+    pub fn with_wait_time_seconds(mut self, wait_time_seconds: i32) -> Self {
+        self.wait_time_seconds = wait_time_seconds;
+        self
+    }
+
+    /// Copies the message attributes named in `allowlist` onto each consumer span as
+    /// `messaging.message.attributes.<name>` (none by default).
+    ///
+    /// See [`allowlisted_span_attributes`].
+    pub fn with_span_attributes<I, S>(mut self, allowlist: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.span_attributes = allowlist.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets what happens to a message whose handler fails (default
+    /// [`FailurePolicy::Skip`]).
+    pub fn with_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Receives one batch of messages and runs `f` for each of them.
+    ///
+    /// Each message is processed in its own `SpanKind::Consumer` span whose parent is
+    /// the context extracted from the message with [`extract_isolated`], and `f`'s
+    /// future runs with that span's context attached. The context attached around
+    /// `process` never leaks into a message. Messages are deleted, recording a
+    /// `messaging.ack` event, only when `f` succeeds; a failed message marks its span
+    /// as an error and is handled according to the [`FailurePolicy`]. A delay recorded
+    /// by [`inject_context_with_delay`](super::inject_context_with_delay) is set as
+    /// `messaging.aws_sqs.delay_seconds` on the span, and messages received more than
+    /// once are marked with `messaging.aws_sqs.is_redelivery`. The span carries the
+    /// [`semconv::sqs_consumer_attributes`] of the queue, including its region and
+    /// account as `cloud.region` and `cloud.account.id`. Allowlisted
+    /// message attributes (see [`with_span_attributes`](Self::with_span_attributes))
+    /// are copied onto the span.
+    ///
+    /// Returns the number of messages that were processed and deleted successfully.
+    pub async fn process<F, Fut, E>(&self, mut f: F) -> Result<usize, ProcessError<E>>
+    where
+        F: FnMut(Message) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: Display,
+    {
+        let messages = self
+            .client
+            .receive(&self.queue_url, self.max_messages, self.wait_time_seconds)
+            .await?;
+
+        let mut processed = 0;
+        for msg in messages {
+            let parent_cx = extract_isolated(&msg);
+
+            crate::guard::warn_if_leaked("sqs.process");
+            let mut attributes = semconv::sqs_consumer_attributes(&self.queue_url);
+            attributes.extend(process_span_attributes(&msg));
+            attributes.extend(allowlisted_span_attributes(&msg, &self.span_attributes));
+            let span = self
+                .tracer
+                .span_builder("sqs.process")
+                .with_kind(SpanKind::Consumer)
+                .with_attributes(attributes)
+                .start_with_context(&self.tracer, &parent_cx);
+            let cx = parent_cx.with_span(span);
+
+            let receipt_handle = msg.receipt_handle().map(str::to_string);
+            let message_id = msg.message_id().map(str::to_string);
+            let dead_letter = matches!(self.failure_policy, FailurePolicy::Dlq(_))
+                .then(|| (msg.body.clone(), msg.message_attributes.clone()));
+            match f(msg).with_context(cx.clone()).await {
+                Ok(()) => {
+                    if let Some(receipt_handle) = receipt_handle {
+                        match self.delete(&receipt_handle, &cx).await {
+                            Ok(()) => processed += 1,
+                            Err(e) => cx.span().set_status(Status::error(format!(
+                                "failed to delete message: {e}"
+                            ))),
+                        }
+                    }
+                }
+                Err(e) => {
+                    let span = cx.span();
+                    span.set_status(Status::error(e.to_string()));
+                    span.add_event(
+                        "messaging.failure",
+                        vec![KeyValue::new(
+                            "messaging.failure.action",
+                            self.failure_policy.action(),
+                        )],
+                    );
+                    match &self.failure_policy {
+                        FailurePolicy::Skip => {}
+                        FailurePolicy::Dlq(dlq_url) => {
+                            let (body, attributes) = dead_letter.unwrap_or_default();
+                            if let Err(e) = self
+                                .move_to(dlq_url, body, attributes, receipt_handle.as_deref())
+                                .await
+                            {
+                                span.set_status(Status::error(format!(
+                                    "failed to move message to the dead-letter queue: {e}"
+                                )));
+                            }
+                        }
+                        FailurePolicy::Abort => {
+                            span.end();
+                            return Err(ProcessError::Aborted {
+                                message_id,
+                                error: e,
+                            });
+                        }
+                    }
+                }
+            }
+            cx.span().end();
+        }
+
+        Ok(processed)
+    }
+
+    /// Sends a copy of a message to `queue_url` and deletes the original.
+    async fn move_to(
+        &self,
+        queue_url: &str,
+        body: Option<String>,
+        attributes: Option<HashMap<String, aws_sdk_sqs::types::MessageAttributeValue>>,
+        receipt_handle: Option<&str>,
+    ) -> Result<(), aws_sdk_sqs::Error> {
+        self.client.send(queue_url, body, attributes).await?;
+        if let Some(receipt_handle) = receipt_handle {
+            self.client.delete(&self.queue_url, receipt_handle).await?;
+        }
+        Ok(())
+    }
+
+    /// Acknowledges `msg` by deleting it from the queue.
+    ///
+    /// On success a `messaging.ack` event is added to the current span, so call this
+    /// while the message's consumer span is attached. A message without a receipt
+    /// handle is left untouched.
+    pub async fn ack(&self, msg: &Message) -> Result<(), aws_sdk_sqs::Error> {
+        match msg.receipt_handle() {
+            Some(receipt_handle) => self.delete(receipt_handle, &Context::current()).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Negatively acknowledges `msg` by setting its visibility timeout to zero, making
+    /// it immediately available for redelivery.
+    ///
+    /// On success a `messaging.nack` event is added to the current span, so call this
+    /// while the message's consumer span is attached. A message without a receipt
+    /// handle is left untouched.
+    pub async fn nack(&self, msg: &Message) -> Result<(), aws_sdk_sqs::Error> {
+        let Some(receipt_handle) = msg.receipt_handle() else {
+            return Ok(());
+        };
+        self.client
+            .change_visibility(&self.queue_url, receipt_handle, 0)
+            .await?;
+        Context::current()
+            .span()
+            .add_event("messaging.nack", vec![self.destination_attribute()]);
+        Ok(())
+    }
+
+    /// Deletes the messages with `receipt_handles` using `DeleteMessageBatch`.
+    ///
+    /// Each request of up to [`MAX_BATCH_SIZE`] handles is covered by one
+    /// `SpanKind::Client` `sqs.delete_batch` span, a child of the current context,
+    /// recording the `settle` operation type with a `messaging.delete.succeeded` or
+    /// `messaging.delete.failed` event per entry.
+    /// A batch with failed entries marks its span as an error. Returns the number of
+    /// messages deleted; a request that fails as a whole returns its error.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let handles: Vec<String> = processed
+    ///     .iter()
+    ///     .filter_map(|msg| msg.receipt_handle().map(str::to_string))
+    ///     .collect();
+    /// let deleted = consumer.delete_batch(&handles).await?;
+    /// ```
+    pub async fn delete_batch(
+        &self,
+        receipt_handles: &[String],
+    ) -> Result<usize, aws_sdk_sqs::Error> {
+        let mut deleted = 0;
+        for chunk in receipt_handles.chunks(MAX_BATCH_SIZE) {
+            let span = self
+                .tracer
+                .span_builder("sqs.delete_batch")
+                .with_kind(SpanKind::Client)
+                .with_attributes(
+                    semconv::sqs_attributes("settle", &self.queue_url)
+                        .into_iter()
+                        .chain([KeyValue::new(
+                            "messaging.batch.message_count",
+                            chunk.len() as i64,
+                        )]),
+                )
+                .start(&self.tracer);
+            let cx = Context::current_with_span(span);
+
+            let entries = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, receipt_handle)| {
+                    DeleteMessageBatchRequestEntry::builder()
+                        .id(i.to_string())
+                        .receipt_handle(receipt_handle)
+                        .build()
+                        .expect("id and receipt handle are set")
+                })
+                .collect();
+            let output = match self.client.delete_batch(&self.queue_url, entries).await {
+                Ok(output) => output,
+                Err(e) => {
+                    cx.span().set_status(Status::error(e.to_string()));
+                    cx.span().end();
+                    return Err(e);
+                }
+            };
+
+            let span = cx.span();
+            for entry in output.successful() {
+                span.add_event(
+                    "messaging.delete.succeeded",
+                    vec![KeyValue::new(
+                        "messaging.batch.entry_id",
+                        entry.id().to_string(),
+                    )],
+                );
+            }
+            for entry in output.failed() {
+                span.add_event(
+                    "messaging.delete.failed",
+                    vec![
+                        KeyValue::new("messaging.batch.entry_id", entry.id().to_string()),
+                        KeyValue::new("error.type", entry.code().to_string()),
+                    ],
+                );
+            }
+            if !output.failed().is_empty() {
+                span.set_status(Status::error(format!(
+                    "failed to delete {} of {} messages",
+                    output.failed().len(),
+                    chunk.len()
+                )));
+            }
+            span.end();
+            deleted += output.successful().len();
+        }
+        Ok(deleted)
+    }
+
+    /// Deletes the message with `receipt_handle` and records the ack on `cx`'s span.
+    async fn delete(&self, receipt_handle: &str, cx: &Context) -> Result<(), aws_sdk_sqs::Error> {
+        self.client.delete(&self.queue_url, receipt_handle).await?;
+        cx.span()
+            .add_event("messaging.ack", vec![self.destination_attribute()]);
+        Ok(())
+    }
+
+    fn destination_attribute(&self) -> KeyValue {
+        KeyValue::new(
+            "messaging.destination.name",
+            semconv::queue_name(&self.queue_url).to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sqs::client::InMemorySqsClient;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use aws_sdk_sqs::operation::delete_message::DeleteMessageOutput;
+    use aws_sdk_sqs::operation::delete_message_batch::DeleteMessageBatchOutput;
+    use aws_sdk_sqs::operation::receive_message::ReceiveMessageOutput;
+    use aws_sdk_sqs::operation::send_message::SendMessageOutput;
+    use aws_sdk_sqs::types::{
+        BatchResultErrorEntry, DeleteMessageBatchResultEntry, MessageAttributeValue,
+    };
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry::Context;
+    use opentelemetry_sdk::trace::{SdkTracerProvider, SpanData};
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const QUEUE_URL: &str = "https://sqs.us-east-1.amazonaws.com/123456789012/orders";
+    const DLQ_URL: &str = "https://sqs.us-east-1.amazonaws.com/123456789012/orders-dlq";
+
+    fn message(id: &str, traceparent: Option<String>) -> Message {
+        let mut builder = Message::builder()
+            .message_id(id)
+            .receipt_handle(format!("handle-{id}"))
+            .body("hello");
+        if let Some(traceparent) = traceparent {
+            builder = builder.message_attributes(
+                "traceparent",
+                MessageAttributeValue::builder()
+                    .data_type("String")
+                    .string_value(traceparent)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        builder.build()
+    }
+
+    #[tokio::test]
+    async fn test_process_traces_and_deletes_successful_messages() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let receive = mock!(Client::receive_message).then_output(|| {
+            ReceiveMessageOutput::builder()
+                .messages(message(
+                    "ok",
+                    Some(traceparent(TRACE_ID, "00f067aa0ba902b7")),
+                ))
+                .messages(message("fail", None))
+                .build()
+        });
+        let delete = mock!(Client::delete_message)
+            .match_requests(|req| req.receipt_handle() == Some("handle-ok"))
+            .then_output(|| {
+                aws_sdk_sqs::operation::delete_message::DeleteMessageOutput::builder().build()
+            });
+        let client = mock_client!(aws_sdk_sqs, RuleMode::MatchAny, [&receive, &delete]);
+        let consumer = TracedSqsConsumer::new(
+            client,
+            "https://sqs.us-east-1.amazonaws.com/123456789012/orders",
+        )
+        .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+
+        let processed = consumer
+            .process(|msg| async move {
+                assert!(Context::current().span().span_context().is_valid());
+                match msg.message_id() {
+                    Some("ok") => Ok(()),
+                    _ => Err("handler failed"),
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(processed, 1);
+        assert_eq!(delete.num_calls(), 1);
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].span_kind, SpanKind::Consumer);
+        assert_eq!(spans[0].span_context.trace_id().to_string(), TRACE_ID);
+        assert_eq!(spans[0].parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("cloud.region", "us-east-1")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.operation.type", "process")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.destination.name", "orders")));
+        assert_eq!(spans[0].status, Status::Unset);
+        assert_eq!(spans[0].events.events[0].name, "messaging.ack");
+        assert_eq!(spans[1].status, Status::error("handler failed"));
+        assert_eq!(failure_action(&spans[1]), Some(&"skip".into()));
+    }
+
+    #[tokio::test]
+    async fn test_process_ignores_the_ambient_context() {
+        use opentelemetry::baggage::BaggageExt;
+
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let client = InMemorySqsClient::default();
+        client.push(QUEUE_URL, message("untraced", None));
+        let consumer = TracedSqsConsumer::new(client, QUEUE_URL)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+        let ambient =
+            crate::test_util::sampled_context().with_baggage(vec![KeyValue::new("tenant", "acme")]);
+
+        let _guard = ambient.attach();
+        consumer
+            .process(|_| async {
+                assert_eq!(Context::current().baggage().len(), 0);
+                Ok::<_, &str>(())
+            })
+            .await
+            .unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            spans[0].parent_span_id,
+            opentelemetry::trace::SpanId::INVALID
+        );
+        assert_ne!(spans[0].span_context.trace_id().to_string(), TRACE_ID);
+    }
+
+    fn consumer_for(client: Client, provider: &SdkTracerProvider) -> TracedSqsConsumer {
+        TracedSqsConsumer::new(client, QUEUE_URL)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))))
+    }
+
+    fn failure_action(span: &SpanData) -> Option<&opentelemetry::Value> {
+        span.events
+            .iter()
+            .find(|e| e.name == "messaging.failure")?
+            .attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == "messaging.failure.action")
+            .map(|kv| &kv.value)
+    }
+
+    #[tokio::test]
+    async fn test_dlq_policy_moves_failed_message() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let receive = mock!(Client::receive_message).then_output(|| {
+            ReceiveMessageOutput::builder()
+                .messages(message("fail", None))
+                .build()
+        });
+        let send = mock!(Client::send_message)
+            .match_requests(|req| {
+                req.queue_url() == Some(DLQ_URL) && req.message_body() == Some("hello")
+            })
+            .then_output(|| SendMessageOutput::builder().build());
+        let delete = mock!(Client::delete_message)
+            .match_requests(|req| req.receipt_handle() == Some("handle-fail"))
+            .then_output(|| DeleteMessageOutput::builder().build());
+        let client = mock_client!(aws_sdk_sqs, RuleMode::MatchAny, [&receive, &send, &delete]);
+        let consumer = consumer_for(client, &provider)
+            .with_failure_policy(FailurePolicy::Dlq(DLQ_URL.to_string()));
+
+        let processed = consumer
+            .process(|_| async { Err("handler failed") })
+            .await
+            .unwrap();
+
+        assert_eq!(processed, 0);
+        assert_eq!(send.num_calls(), 1);
+        assert_eq!(delete.num_calls(), 1);
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(failure_action(&spans[0]), Some(&"dlq".into()));
+    }
+
+    #[tokio::test]
+    async fn test_dlq_policy_with_in_memory_client() {
+        init_propagator();
+        let (provider, _exporter) = test_tracer();
+        let client = InMemorySqsClient::default();
+        client.push(QUEUE_URL, message("ok", None));
+        client.push(QUEUE_URL, message("fail", None));
+        let consumer = TracedSqsConsumer::new(client, QUEUE_URL)
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))))
+            .with_failure_policy(FailurePolicy::Dlq(DLQ_URL.to_string()));
+
+        let processed = consumer
+            .process(|msg| async move {
+                match msg.message_id() {
+                    Some("ok") => Ok(()),
+                    _ => Err("handler failed"),
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(processed, 1);
+        let client = consumer.client();
+        assert_eq!(client.deleted(), ["handle-ok", "handle-fail"]);
+        assert!(client.messages(QUEUE_URL).is_empty());
+        let dead_letters = client.messages(DLQ_URL);
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].body(), Some("hello"));
+    }
+
+    #[tokio::test]
+    async fn test_abort_policy_stops_the_batch() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let receive = mock!(Client::receive_message).then_output(|| {
+            ReceiveMessageOutput::builder()
+                .messages(message("first", None))
+                .messages(message("second", None))
+                .build()
+        });
+        let client = mock_client!(aws_sdk_sqs, [&receive]);
+        let consumer = consumer_for(client, &provider).with_failure_policy(FailurePolicy::Abort);
+
+        let result = consumer.process(|_| async { Err("handler failed") }).await;
+
+        match result {
+            Err(ProcessError::Aborted { message_id, error }) => {
+                assert_eq!(message_id.as_deref(), Some("first"));
+                assert_eq!(error, "handler failed");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(failure_action(&spans[0]), Some(&"abort".into()));
+    }
+
+    #[tokio::test]
+    async fn test_delete_batch_records_one_span_per_request() {
+        let (provider, exporter) = test_tracer();
+        let delete_batch = mock!(Client::delete_message_batch)
+            .match_requests(|req| req.entries().len() == 2)
+            .then_output(|| {
+                DeleteMessageBatchOutput::builder()
+                    .successful(
+                        DeleteMessageBatchResultEntry::builder()
+                            .id("0")
+                            .build()
+                            .unwrap(),
+                    )
+                    .failed(
+                        BatchResultErrorEntry::builder()
+                            .id("1")
+                            .code("ReceiptHandleIsInvalid")
+                            .sender_fault(true)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+                    .unwrap()
+            });
+        let client = mock_client!(aws_sdk_sqs, [&delete_batch]);
+        let consumer = TracedSqsConsumer::new(
+            client,
+            "https://sqs.us-east-1.amazonaws.com/123456789012/orders",
+        )
+        .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+
+        let deleted = consumer
+            .delete_batch(&["handle-a".to_string(), "handle-b".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 1);
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "sqs.delete_batch");
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.operation.type", "settle")));
+        let events: Vec<_> = spans[0].events.iter().map(|e| e.name.as_ref()).collect();
+        assert_eq!(
+            events,
+            vec!["messaging.delete.succeeded", "messaging.delete.failed"]
+        );
+        assert!(matches!(spans[0].status, Status::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_nack_resets_visibility_and_records_event() {
+        let (provider, exporter) = test_tracer();
+        let change_visibility = mock!(Client::change_message_visibility)
+            .match_requests(|req| {
+                req.receipt_handle() == Some("handle-retry") && req.visibility_timeout() == Some(0)
+            })
+            .then_output(|| {
+                aws_sdk_sqs::operation::change_message_visibility::ChangeMessageVisibilityOutput::builder()
+                    .build()
+            });
+        let client = mock_client!(aws_sdk_sqs, [&change_visibility]);
+        let consumer = TracedSqsConsumer::new(
+            client,
+            "https://sqs.us-east-1.amazonaws.com/123456789012/orders",
+        );
+        let tracer = provider.tracer("test");
+
+        let span = tracer.start("sqs.process");
+        let cx = Context::current_with_span(span);
+        consumer
+            .nack(&message("retry", None))
+            .with_context(cx.clone())
+            .await
+            .unwrap();
+        cx.span().end();
+
+        assert_eq!(change_visibility.num_calls(), 1);
+        let spans = exporter.get_finished_spans().unwrap();
+        let event = &spans[0].events.events[0];
+        assert_eq!(event.name, "messaging.nack");
+        assert_eq!(
+            event.attributes,
+            vec![KeyValue::new("messaging.destination.name", "orders")]
+        );
+    }
+}
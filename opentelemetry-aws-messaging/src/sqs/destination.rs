@@ -0,0 +1,149 @@
+//! Extraction from Lambda destination records delivered to SQS.
+//!
+//! A Lambda function invoked asynchronously can send the outcome of each invocation
+//! to an SQS queue (an on-success or on-failure destination). The message body is
+//! then a destination record that nests the original event one level deeper:
+//!
+//! ```text
+//! {
+//!   "version": "1.0",
+//!   "requestContext": {"requestId": "...", "condition": "Success", ...},
+//!   "requestPayload": {"Records": [{"messageAttributes": {"traceparent": {...}}}]},
+//!   "responsePayload": {"status": "ok"}
+//! }
+//! ```
+//!
+//! The trace context is looked up in `requestPayload` first, then in
+//! `responsePayload`. Within each payload, the following shapes are recognized:
+//!
+//! - an SQS event record: `Records[0].messageAttributes.<field>.stringValue`
+//! - an SNS event record: `Records[0].Sns.MessageAttributes.<field>.Value`
+//! - a body carrier: the object under `_otel`, as written by the `body` module
+
+use crate::body::CONTEXT_FIELD;
+use crate::config::DEFAULT_MAX_BODY_SIZE;
+use opentelemetry::propagation::Extractor;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// The destination record fields holding a nested payload, in lookup order.
+const PAYLOAD_FIELDS: [&str; 2] = ["requestPayload", "responsePayload"];
+
+/// An [`Extractor`] over the trace fields nested in a Lambda destination record.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::destination::LambdaDestinationExtractor;
+///
+/// if let Some(extractor) = LambdaDestinationExtractor::parse(msg.body().unwrap_or_default()) {
+///     let parent_cx = global::get_text_map_propagator(|propagator| propagator.extract(&extractor));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct LambdaDestinationExtractor {
+    fields: HashMap<String, String>,
+}
+
+impl LambdaDestinationExtractor {
+    /// Parses a Lambda destination record out of `body`.
+    ///
+    /// Returns `None` when the body is not a destination record: not a JSON object,
+    /// larger than [`DEFAULT_MAX_BODY_SIZE`], or without `requestContext` and a
+    /// payload field. A destination record without trace fields yields no keys.
+    pub fn parse(body: &str) -> Option<Self> {
+        if body.len() > DEFAULT_MAX_BODY_SIZE {
+            return None;
+        }
+        let Ok(Value::Object(record)) = serde_json::from_str::<Value>(body) else {
+            return None;
+        };
+        if !record.contains_key("requestContext")
+            || !PAYLOAD_FIELDS.iter().any(|f| record.contains_key(*f))
+        {
+            return None;
+        }
+
+        let fields = PAYLOAD_FIELDS
+            .iter()
+            .filter_map(|f| record.get(*f))
+            .map(payload_fields)
+            .find(|fields| !fields.is_empty())
+            .unwrap_or_default();
+        Some(Self { fields })
+    }
+}
+
+/// Collects the trace fields from one nested payload.
+fn payload_fields(payload: &Value) -> HashMap<String, String> {
+    let record = payload.pointer("/Records/0");
+    if let Some(attributes) = record.and_then(|r| r.get("messageAttributes")) {
+        return string_fields(attributes, "stringValue");
+    }
+    if let Some(attributes) = record.and_then(|r| r.pointer("/Sns/MessageAttributes")) {
+        return string_fields(attributes, "Value");
+    }
+    match payload.get(CONTEXT_FIELD) {
+        Some(Value::Object(fields)) => fields
+            .iter()
+            .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// Reads the string value stored under `value_key` of each attribute.
+fn string_fields(attributes: &Value, value_key: &str) -> HashMap<String, String> {
+    attributes
+        .as_object()
+        .map(Map::iter)
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, attr)| {
+            let value = attr.get(value_key)?.as_str()?;
+            Some((name.clone(), value.to_string()))
+        })
+        .collect()
+}
+
+impl Extractor for LambdaDestinationExtractor {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.fields.keys().map(String::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reads_sns_record_and_body_carrier() {
+        let sns = r#"{"requestContext":{},"requestPayload":{"Records":[{"Sns":{
+            "MessageAttributes":{"traceparent":{"Type":"String","Value":"00-a-b-01"}}}}]}}"#;
+        let body = r#"{"requestContext":{},"requestPayload":{"id":1},
+            "responsePayload":{"_otel":{"traceparent":"00-c-d-01"}}}"#;
+
+        assert_eq!(
+            LambdaDestinationExtractor::parse(sns)
+                .unwrap()
+                .get("traceparent"),
+            Some("00-a-b-01")
+        );
+        assert_eq!(
+            LambdaDestinationExtractor::parse(body)
+                .unwrap()
+                .get("traceparent"),
+            Some("00-c-d-01")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_other_bodies() {
+        assert!(LambdaDestinationExtractor::parse(r#"{"requestPayload":{}}"#).is_none());
+        assert!(LambdaDestinationExtractor::parse("plain text").is_none());
+    }
+}
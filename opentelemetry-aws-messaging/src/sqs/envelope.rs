@@ -0,0 +1,519 @@
+//! Extraction from the SNS JSON envelope delivered to SQS.
+//!
+//! When raw message delivery is disabled on the subscription, SNS wraps every message
+//! in a JSON envelope and the message attributes end up inside the body under
+//! `MessageAttributes`, each entry shaped as `{"Type": "String", "Value": "..."}`:
+//!
+//! ```text
+//! {
+//!   "Type": "Notification",
+//!   "MessageId": "...",
+//!   "Message": "...",
+//!   "MessageAttributes": {
+//!     "traceparent": {"Type": "String", "Value": "00-...-...-01"}
+//!   }
+//! }
+//! ```
+//!
+//! A body may also hold a JSON array of such envelopes when a relay batches several
+//! notifications into one message; see [`SnsEnvelopeExtractor::extract_all`].
+//!
+//! SNS also delivers control messages (`SubscriptionConfirmation`,
+//! `UnsubscribeConfirmation`) through the same envelope. These are not business
+//! messages and should be neither traced nor processed.
+//!
+//! With the `gzip` feature, envelopes that a relay compressed and base64-encoded into
+//! the body are decompressed transparently before parsing. Decompression is bounded
+//! by [`MAX_DECOMPRESSED_SIZE`] so a hostile body cannot exhaust memory.
+
+use crate::config::{CarrierConfig, DEFAULT_MAX_BODY_SIZE};
+use opentelemetry::propagation::Extractor;
+use opentelemetry::{global, Context};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The largest decompressed envelope, in bytes, accepted from a gzip-compressed body.
+#[cfg(feature = "gzip")]
+pub const MAX_DECOMPRESSED_SIZE: u64 = 1024 * 1024;
+
+/// The SNS control message types that share the notification envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlMessageType {
+    /// Sent when a subscription is created and must be confirmed.
+    SubscriptionConfirmation,
+    /// Sent when a subscription is deleted.
+    UnsubscribeConfirmation,
+}
+
+/// The result of classifying an SQS message body.
+#[derive(Debug)]
+pub enum SnsEnvelope {
+    /// An SNS `Notification`; extract the trace context from the wrapped extractor.
+    Notification(SnsEnvelopeExtractor),
+    /// An SNS control message; skip extraction and processing.
+    Control(ControlMessageType),
+    /// The body is not an SNS envelope (e.g. raw delivery is enabled).
+    NotEnvelope,
+}
+
+/// A typed SNS `Notification` envelope.
+///
+/// Fields missing from the envelope are left empty rather than rejected, so trace
+/// extraction keeps working on trimmed-down test payloads.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct SnsNotification {
+    /// The SNS message id.
+    pub message_id: String,
+    /// The ARN of the topic the message was published to.
+    pub topic_arn: String,
+    /// The published message body.
+    pub message: String,
+    /// The publish time, as an ISO 8601 string.
+    pub timestamp: String,
+    /// The message attributes, keyed by name.
+    pub message_attributes: HashMap<String, SnsMessageAttribute>,
+}
+
+/// A message attribute embedded in an SNS envelope.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SnsMessageAttribute {
+    /// The attribute data type (`String`, `String.Array`, `Number`, or `Binary`).
+    #[serde(rename = "Type")]
+    pub data_type: String,
+    /// The attribute value; base64-encoded for `Binary` attributes.
+    #[serde(rename = "Value")]
+    pub value: String,
+}
+
+/// An [`Extractor`] over the `MessageAttributes` embedded in an SNS envelope.
+///
+/// The notification is parsed out of the body up front, so the extractor owns its
+/// data and does not borrow the body. Only `String`-typed attributes are visible.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_aws_messaging::sqs::{SnsEnvelope, SnsEnvelopeExtractor};
+///
+/// match SnsEnvelopeExtractor::parse(msg.body().unwrap_or_default()) {
+///     SnsEnvelope::Notification(extractor) => {
+///         let parent_cx = global::get_text_map_propagator(|propagator| {
+///             propagator.extract(&extractor)
+///         });
+///         // Process the message under `parent_cx`
+///     }
+///     SnsEnvelope::Control(_) => { /* delete and move on */ }
+///     SnsEnvelope::NotEnvelope => { /* fall back to message attributes */ }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct SnsEnvelopeExtractor {
+    notification: SnsNotification,
+}
+
+impl SnsEnvelopeExtractor {
+    /// Parses `body` as an SNS envelope and classifies it.
+    ///
+    /// A body larger than [`DEFAULT_MAX_BODY_SIZE`] is not parsed and is reported as
+    /// not an envelope; use [`parse_with_config`](Self::parse_with_config) to tune the
+    /// limit. With the `gzip` feature, a body that is not JSON is also tried as a
+    /// base64-encoded gzip stream; a body that does not decode, lacks the gzip magic
+    /// number, or exceeds `MAX_DECOMPRESSED_SIZE` is not an envelope.
+    pub fn parse(body: &str) -> SnsEnvelope {
+        Self::parse_limited(body, DEFAULT_MAX_BODY_SIZE)
+    }
+
+    /// Like [`parse`](Self::parse), with the body size limit taken from
+    /// [`CarrierConfig::max_body_size`].
+    pub fn parse_with_config(body: &str, config: &CarrierConfig) -> SnsEnvelope {
+        Self::parse_limited(body, config.max_body_size)
+    }
+
+    fn parse_limited(body: &str, max_body_size: usize) -> SnsEnvelope {
+        match parse_body(body, max_body_size) {
+            Some(envelope @ Value::Object(_)) => Self::classify(&envelope),
+            _ => SnsEnvelope::NotEnvelope,
+        }
+    }
+
+    /// Parses `body` as an SNS notification, returning an extractor without keys when
+    /// it is a control message or not an envelope at all.
+    ///
+    /// This suits fallback chains that do not need to tell the cases apart: the empty
+    /// extractor yields `None` for every key, so the consumer moves on to the next
+    /// source. Use [`parse`](Self::parse) to skip control messages.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use opentelemetry_aws_messaging::sqs::SnsEnvelopeExtractor;
+    ///
+    /// let extractor = SnsEnvelopeExtractor::from_body(msg.body().unwrap_or_default());
+    /// let parent_cx = global::get_text_map_propagator(|propagator| propagator.extract(&extractor));
+    /// ```
+    pub fn from_body(body: &str) -> Self {
+        match Self::parse(body) {
+            SnsEnvelope::Notification(extractor) => extractor,
+            _ => Self::default(),
+        }
+    }
+
+    /// Extracts a parent [`Context`] per notification in `body` using the global
+    /// propagator.
+    ///
+    /// Some relays batch several notifications into one SQS message as a JSON array of
+    /// envelopes; each element gets its own context, in order, with an empty context
+    /// for elements that are not notifications. A body holding a single envelope object
+    /// yields one context if it is a notification. Anything else yields no contexts.
+    /// Contexts are extracted from an empty context, never from the one attached on the
+    /// calling thread. The body size limit and `gzip` handling are those of
+    /// [`parse`](Self::parse).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use opentelemetry_aws_messaging::sqs::SnsEnvelopeExtractor;
+    ///
+    /// for parent_cx in SnsEnvelopeExtractor::extract_all(msg.body().unwrap_or_default()) {
+    ///     // Process each batched notification under its own `parent_cx`
+    /// }
+    /// ```
+    pub fn extract_all(body: &str) -> Vec<Context> {
+        Self::extract_all_limited(body, DEFAULT_MAX_BODY_SIZE)
+    }
+
+    /// Like [`extract_all`](Self::extract_all), with the body size limit taken from
+    /// [`CarrierConfig::max_body_size`].
+    pub fn extract_all_with_config(body: &str, config: &CarrierConfig) -> Vec<Context> {
+        Self::extract_all_limited(body, config.max_body_size)
+    }
+
+    fn extract_all_limited(body: &str, max_body_size: usize) -> Vec<Context> {
+        let extract = |envelope: &Value| match Self::classify(envelope) {
+            SnsEnvelope::Notification(extractor) => global::get_text_map_propagator(|propagator| {
+                propagator.extract_with_context(&Context::new(), &extractor)
+            }),
+            _ => Context::new(),
+        };
+        match parse_body(body, max_body_size) {
+            Some(Value::Array(envelopes)) => envelopes.iter().map(extract).collect(),
+            Some(envelope @ Value::Object(_)) => match Self::classify(&envelope) {
+                SnsEnvelope::Notification(_) => vec![extract(&envelope)],
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the typed notification.
+    pub fn notification(&self) -> &SnsNotification {
+        &self.notification
+    }
+
+    /// Consumes the extractor, returning the typed notification.
+    pub fn into_notification(self) -> SnsNotification {
+        self.notification
+    }
+
+    fn classify(envelope: &Value) -> SnsEnvelope {
+        match envelope.get("Type").and_then(Value::as_str) {
+            Some("Notification") => match SnsNotification::deserialize(envelope) {
+                Ok(notification) => SnsEnvelope::Notification(Self { notification }),
+                Err(_) => SnsEnvelope::NotEnvelope,
+            },
+            Some("SubscriptionConfirmation") => {
+                SnsEnvelope::Control(ControlMessageType::SubscriptionConfirmation)
+            }
+            Some("UnsubscribeConfirmation") => {
+                SnsEnvelope::Control(ControlMessageType::UnsubscribeConfirmation)
+            }
+            _ => SnsEnvelope::NotEnvelope,
+        }
+    }
+}
+
+/// Parses `body` as JSON, decompressing it first with the `gzip` feature when it is
+/// not JSON. Returns `None` for bodies over `max_body_size` or that do not parse.
+fn parse_body(body: &str, max_body_size: usize) -> Option<Value> {
+    if body.len() > max_body_size {
+        return None;
+    }
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) => Some(value),
+        #[cfg(feature = "gzip")]
+        Err(_) => serde_json::from_str(&decompress(body)?).ok(),
+        #[cfg(not(feature = "gzip"))]
+        Err(_) => None,
+    }
+}
+
+/// Decodes a base64 gzip stream, returning `None` if `body` is not one or its
+/// decompressed size exceeds [`MAX_DECOMPRESSED_SIZE`].
+#[cfg(feature = "gzip")]
+fn decompress(body: &str) -> Option<String> {
+    use base64::Engine;
+    use std::io::Read;
+
+    let compressed = base64::engine::general_purpose::STANDARD
+        .decode(body.trim())
+        .ok()?;
+    if !compressed.starts_with(&[0x1f, 0x8b]) {
+        return None;
+    }
+    let mut json = String::new();
+    flate2::read::GzDecoder::new(compressed.as_slice())
+        .take(MAX_DECOMPRESSED_SIZE + 1)
+        .read_to_string(&mut json)
+        .ok()?;
+    (json.len() as u64 <= MAX_DECOMPRESSED_SIZE).then_some(json)
+}
+
+impl Extractor for SnsEnvelopeExtractor {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.notification
+            .message_attributes
+            .get(key)
+            .filter(|attr| attr.data_type == "String")
+            .map(|attr| attr.value.as_str())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.notification
+            .message_attributes
+            .iter()
+            .filter(|(_, attr)| attr.data_type == "String")
+            .map(|(k, _)| k.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTIFICATION: &str = r#"{
+        "Type": "Notification",
+        "MessageId": "22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324",
+        "TopicArn": "arn:aws:sns:us-east-1:123456789012:orders",
+        "Message": "{\"id\":1}",
+        "MessageAttributes": {
+            "traceparent": {"Type": "String", "Value": "00-abc123-def456-01"}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_notification_extracts_attributes() {
+        let SnsEnvelope::Notification(extractor) = SnsEnvelopeExtractor::parse(NOTIFICATION) else {
+            panic!("expected a notification");
+        };
+
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+        assert_eq!(extractor.keys(), vec!["traceparent"]);
+    }
+
+    #[test]
+    fn test_extract_all_returns_a_context_per_array_element() {
+        use crate::test_util::{init_propagator, traceparent};
+        use opentelemetry::trace::TraceContextExt;
+
+        init_propagator();
+        let envelope = |trace_id: &str| {
+            serde_json::json!({
+                "Type": "Notification",
+                "Message": "{}",
+                "MessageAttributes": {
+                    "traceparent": {"Type": "String", "Value": traceparent(trace_id, "00f067aa0ba902b7")}
+                }
+            })
+        };
+        let body = serde_json::json!([
+            envelope("4bf92f3577b34da6a3ce929d0e0e4736"),
+            envelope("0af7651916cd43dd8448eb211c80319c"),
+        ])
+        .to_string();
+
+        let trace_ids: Vec<_> = SnsEnvelopeExtractor::extract_all(&body)
+            .iter()
+            .map(|cx| cx.span().span_context().trace_id().to_string())
+            .collect();
+
+        assert_eq!(
+            trace_ids,
+            vec![
+                "4bf92f3577b34da6a3ce929d0e0e4736",
+                "0af7651916cd43dd8448eb211c80319c"
+            ]
+        );
+        assert_eq!(SnsEnvelopeExtractor::extract_all(NOTIFICATION).len(), 1);
+        assert!(SnsEnvelopeExtractor::extract_all("plain text").is_empty());
+    }
+
+    #[test]
+    fn test_extract_all_ignores_the_ambient_context() {
+        use crate::test_util::{init_propagator, sampled_context};
+        use opentelemetry::trace::TraceContextExt;
+
+        init_propagator();
+        let _guard = sampled_context().attach();
+        let body = serde_json::json!([
+            {"Type": "Notification", "Message": "{}"},
+            {"Type": "SubscriptionConfirmation"},
+        ])
+        .to_string();
+
+        let contexts = SnsEnvelopeExtractor::extract_all(&body);
+
+        assert_eq!(contexts.len(), 2);
+        assert!(contexts
+            .iter()
+            .all(|cx| !cx.span().span_context().is_valid()));
+    }
+
+    #[test]
+    fn test_extract_all_honors_the_configured_limit() {
+        let config = CarrierConfig {
+            max_body_size: 64,
+            ..Default::default()
+        };
+
+        assert!(SnsEnvelopeExtractor::extract_all_with_config(NOTIFICATION, &config).is_empty());
+        assert_eq!(
+            SnsEnvelopeExtractor::extract_all_with_config(NOTIFICATION, &CarrierConfig::default())
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_notification_exposes_typed_fields() {
+        let SnsEnvelope::Notification(extractor) = SnsEnvelopeExtractor::parse(NOTIFICATION) else {
+            panic!("expected a notification");
+        };
+        let notification = extractor.into_notification();
+
+        assert_eq!(
+            notification.message_id,
+            "22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324"
+        );
+        assert_eq!(
+            notification.topic_arn,
+            "arn:aws:sns:us-east-1:123456789012:orders"
+        );
+        assert_eq!(notification.message, r#"{"id":1}"#);
+        assert_eq!(notification.timestamp, "");
+        assert_eq!(
+            notification.message_attributes["traceparent"].data_type,
+            "String"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_body_above_configured_limit() {
+        let config = CarrierConfig {
+            max_body_size: 64,
+            ..Default::default()
+        };
+
+        assert!(NOTIFICATION.len() > config.max_body_size);
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse_with_config(NOTIFICATION, &config),
+            SnsEnvelope::NotEnvelope
+        ));
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse(NOTIFICATION),
+            SnsEnvelope::Notification(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_subscription_confirmation_is_control() {
+        let body = r#"{"Type": "SubscriptionConfirmation", "Token": "abc", "SubscribeURL": "https://example.com"}"#;
+
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse(body),
+            SnsEnvelope::Control(ControlMessageType::SubscriptionConfirmation)
+        ));
+    }
+
+    #[test]
+    fn test_parse_unsubscribe_confirmation_is_control() {
+        let body = r#"{"Type": "UnsubscribeConfirmation", "Token": "abc"}"#;
+
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse(body),
+            SnsEnvelope::Control(ControlMessageType::UnsubscribeConfirmation)
+        ));
+    }
+
+    #[test]
+    fn test_parse_raw_body_is_not_envelope() {
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse(r#"{"id": 1, "content": "hello"}"#),
+            SnsEnvelope::NotEnvelope
+        ));
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse("plain text"),
+            SnsEnvelope::NotEnvelope
+        ));
+    }
+
+    #[test]
+    fn test_from_body_falls_back_to_empty_extractor() {
+        let extractor = SnsEnvelopeExtractor::from_body(NOTIFICATION);
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+
+        for body in [
+            r#"{"id": 1, "content": "hello"}"#,
+            r#"{"Type": "SubscriptionConfirmation", "SubscribeURL": "https://example.com"}"#,
+            "plain text",
+        ] {
+            let extractor = SnsEnvelopeExtractor::from_body(body);
+            assert_eq!(extractor.get("traceparent"), None);
+            assert!(extractor.keys().is_empty());
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    fn compress(data: &[u8]) -> String {
+        use base64::Engine;
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        base64::engine::general_purpose::STANDARD.encode(encoder.finish().unwrap())
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_parse_gzip_compressed_notification() {
+        let SnsEnvelope::Notification(extractor) =
+            SnsEnvelopeExtractor::parse(&compress(NOTIFICATION.as_bytes()))
+        else {
+            panic!("expected a notification");
+        };
+
+        assert_eq!(extractor.get("traceparent"), Some("00-abc123-def456-01"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_parse_rejects_oversized_or_non_gzip_bodies() {
+        use base64::Engine;
+
+        let bomb = vec![b' '; MAX_DECOMPRESSED_SIZE as usize + 1];
+        let not_gzip = base64::engine::general_purpose::STANDARD.encode(NOTIFICATION);
+
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse(&compress(&bomb)),
+            SnsEnvelope::NotEnvelope
+        ));
+        assert!(matches!(
+            SnsEnvelopeExtractor::parse(&not_gzip),
+            SnsEnvelope::NotEnvelope
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+//! Consumer spans for FIFO queues that record the message ordering.
+//!
+//! Ordering bugs, such as a message handled before its predecessor in the same group,
+//! only show up in traces when the spans carry the ordering keys. SQS reports them as
+//! system attributes, which are only returned when requested on receive:
+//!
+//! ```ignore
+//! client
+//!     .receive_message()
+//!     .queue_url(&queue_url)
+//!     .message_system_attribute_names(MessageSystemAttributeName::MessageGroupId)
+//!     .message_system_attribute_names(MessageSystemAttributeName::SequenceNumber)
+//!     .message_attribute_names("All")
+//!     .send()
+//!     .await?;
+//! ```
+//!
+//! (`MessageSystemAttributeName::All` works as well.) [`extract_fifo`] reads them
+//! along with the trace context, and [`start_fifo_span`] records them on the consumer
+//! span as [`MESSAGE_GROUP_ID_ATTRIBUTE`] and [`SEQUENCE_NUMBER_ATTRIBUTE`].
+
+use super::{extract_isolated, process_span_attributes};
+use aws_sdk_sqs::types::{Message, MessageSystemAttributeName};
+use opentelemetry::trace::{SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::borrow::Cow;
+
+/// The span attribute recording the FIFO message group id.
+pub const MESSAGE_GROUP_ID_ATTRIBUTE: &str = "messaging.aws_sqs.message_group_id";
+
+/// The span attribute recording the FIFO sequence number.
+pub const SEQUENCE_NUMBER_ATTRIBUTE: &str = "messaging.aws_sqs.sequence_number";
+
+/// The ordering keys of a message received from a FIFO queue.
+///
+/// Both are `None` for messages from standard queues and when the system attributes
+/// were not requested on receive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderingInfo {
+    /// The message group the message belongs to; messages of a group are delivered in
+    /// order.
+    pub message_group_id: Option<String>,
+    /// The sequence number SQS assigned to the message, increasing within a group.
+    ///
+    /// Kept as a string: sequence numbers are 128-bit decimal values.
+    pub sequence_number: Option<String>,
+}
+
+impl OrderingInfo {
+    /// Reads the ordering keys from the system attributes of `msg`.
+    pub fn from_message(msg: &Message) -> Self {
+        let attribute = |name: &MessageSystemAttributeName| msg.attributes()?.get(name).cloned();
+        Self {
+            message_group_id: attribute(&MessageSystemAttributeName::MessageGroupId),
+            sequence_number: attribute(&MessageSystemAttributeName::SequenceNumber),
+        }
+    }
+
+    /// Returns the keys that are present as span attributes.
+    pub fn span_attributes(&self) -> Vec<KeyValue> {
+        [
+            (MESSAGE_GROUP_ID_ATTRIBUTE, &self.message_group_id),
+            (SEQUENCE_NUMBER_ATTRIBUTE, &self.sequence_number),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some(KeyValue::new(key, value.clone()?)))
+        .collect()
+    }
+}
+
+/// Extracts the context of `msg` (see [`extract_isolated`]) along with its ordering
+/// keys.
+pub fn extract_fifo(msg: &Message) -> (Context, OrderingInfo) {
+    (extract_isolated(msg), OrderingInfo::from_message(msg))
+}
+
+/// Starts a `SpanKind::Consumer` span for `msg` from a FIFO queue, as a child of the
+/// context extracted from it, with the ordering keys as span attributes.
+///
+/// The returned context holds the span; the caller ends it once the message is
+/// handled.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::{FutureExt, TraceContextExt};
+/// use opentelemetry_aws_messaging::sqs::fifo::start_fifo_span;
+///
+/// for msg in output.messages.unwrap_or_default() {
+///     let (cx, ordering) = start_fifo_span(&tracer, "orders.process", &msg);
+///     handle(&msg, ordering.sequence_number).with_context(cx.clone()).await?;
+///     cx.span().end();
+/// }
+/// ```
+pub fn start_fifo_span<T>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    msg: &Message,
+) -> (Context, OrderingInfo)
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let (parent_cx, ordering) = extract_fifo(msg);
+    let mut attributes = process_span_attributes(msg);
+    attributes.extend(ordering.span_attributes());
+    let span = tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Consumer)
+        .with_attributes(attributes)
+        .start_with_context(tracer, &parent_cx);
+    (parent_cx.with_span(span), ordering)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use aws_sdk_sqs::types::MessageAttributeValue;
+    use opentelemetry::trace::TracerProvider;
+
+    const SEQUENCE_NUMBER: &str = "18849496460467696128";
+
+    #[test]
+    fn test_start_fifo_span_records_ordering() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let msg = Message::builder()
+            .message_attributes(
+                "traceparent",
+                MessageAttributeValue::builder()
+                    .data_type("String")
+                    .string_value(traceparent(
+                        "4bf92f3577b34da6a3ce929d0e0e4736",
+                        "00f067aa0ba902b7",
+                    ))
+                    .build()
+                    .unwrap(),
+            )
+            .attributes(MessageSystemAttributeName::MessageGroupId, "customer-7")
+            .attributes(MessageSystemAttributeName::SequenceNumber, SEQUENCE_NUMBER)
+            .build();
+
+        let (cx, ordering) = start_fifo_span(&provider.tracer("test"), "process", &msg);
+        cx.span().end();
+
+        assert_eq!(ordering.message_group_id.as_deref(), Some("customer-7"));
+        assert_eq!(ordering.sequence_number.as_deref(), Some(SEQUENCE_NUMBER));
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new(MESSAGE_GROUP_ID_ATTRIBUTE, "customer-7")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new(SEQUENCE_NUMBER_ATTRIBUTE, SEQUENCE_NUMBER)));
+    }
+
+    #[test]
+    fn test_ordering_info_of_standard_queue_message_is_empty() {
+        let ordering = OrderingInfo::from_message(&Message::builder().build());
+
+        assert_eq!(ordering, OrderingInfo::default());
+        assert!(ordering.span_attributes().is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+//! Context extraction for Kinesis Data Firehose aggregated records.
+//!
+//! Firehose can aggregate several logical records into a single delivery, so one SQS
+//! message body may carry multiple messages, each produced under its own trace.
+//!
+//! # Aggregation format
+//!
+//! The body is expected to be newline-delimited JSON, as written by Firehose when a
+//! newline delimiter is configured: one JSON object per line, with blank lines ignored.
+//! Each record carries its trace context as top-level string members named after the
+//! propagator fields (e.g. `traceparent`, `tracestate`), the same shape a
+//! `HashMap<String, String>` injector would produce:
+//!
+//! ```text
+//! {"traceparent":"00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01","order":1}
+//! {"traceparent":"00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01","order":2}
+//! ```
+//!
+//! Lines that are not JSON objects are skipped.
+
+use opentelemetry::global;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::Context;
+use serde_json::{Map, Value};
+
+/// An [`Extractor`] over the top-level string members of a JSON object.
+struct RecordExtractor<'a>(&'a Map<String, Value>);
+
+impl Extractor for RecordExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.as_str())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|s| s.as_str()).collect()
+    }
+}
+
+/// Splits a Firehose-aggregated body into its logical records and extracts the trace
+/// context embedded in each one using the global propagator.
+///
+/// Records without trace fields are still returned, paired with an empty context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::firehose;
+///
+/// for (record, parent_cx) in firehose::extract_records(msg.body().unwrap_or_default()) {
+///     let _guard = parent_cx.attach();
+///     // Process `record` within its own trace
+/// }
+/// ```
+pub fn extract_records(body: &str) -> Vec<(Value, Context)> {
+    body.lines()
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(Value::Object(fields)) => Some(fields),
+            _ => None,
+        })
+        .map(|fields| {
+            let cx = global::get_text_map_propagator(|propagator| {
+                propagator.extract_with_context(&Context::new(), &RecordExtractor(&fields))
+            });
+            (Value::Object(fields), cx)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, traceparent};
+    use opentelemetry::trace::TraceContextExt;
+
+    const TRACE_ID_1: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const TRACE_ID_2: &str = "0af7651916cd43dd8448eb211c80319c";
+
+    #[test]
+    fn test_extract_records_two_record_aggregate() {
+        init_propagator();
+        let body = format!(
+            "{{\"traceparent\":\"{}\",\"order\":1}}\n{{\"traceparent\":\"{}\",\"order\":2}}\n",
+            traceparent(TRACE_ID_1, "00f067aa0ba902b7"),
+            traceparent(TRACE_ID_2, "b7ad6b7169203331"),
+        );
+
+        let records = extract_records(&body);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0["order"], 1);
+        assert_eq!(
+            records[0].1.span().span_context().trace_id().to_string(),
+            TRACE_ID_1
+        );
+        assert_eq!(records[1].0["order"], 2);
+        assert_eq!(
+            records[1].1.span().span_context().trace_id().to_string(),
+            TRACE_ID_2
+        );
+    }
+
+    #[test]
+    fn test_extract_records_without_context_yields_empty_context() {
+        init_propagator();
+
+        let records = extract_records("{\"order\":1}");
+
+        assert_eq!(records.len(), 1);
+        assert!(!records[0].1.span().span_context().is_valid());
+
+        let _guard = crate::test_util::sampled_context().attach();
+        let records = extract_records("{\"order\":1}");
+        assert!(!records[0].1.span().span_context().is_valid());
+    }
+
+    #[test]
+    fn test_extract_records_skips_blank_and_non_object_lines() {
+        init_propagator();
+
+        let records = extract_records("\n{\"order\":1}\nnot json\n[1,2]\n\n");
+
+        assert_eq!(records.len(), 1);
+    }
+}
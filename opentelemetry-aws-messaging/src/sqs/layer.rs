@@ -0,0 +1,200 @@
+//! A `tower` layer that traces message handlers written as services.
+//!
+//! [`TraceExtractionLayer`] wraps a `Service<Message>` so each call runs inside a
+//! consumer span parented to the producer's context, the same span the
+//! `TracedSqsConsumer` creates. It is behind the `tower` feature.
+
+use super::{extract_isolated, process_span_attributes};
+use aws_sdk_sqs::types::Message;
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::trace::{FutureExt, SpanKind, Status, TraceContextExt, Tracer};
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// A [`Layer`] that extracts the trace context from each [`Message`] and runs the
+/// inner service inside a `SpanKind::Consumer` span.
+///
+/// Context is read with [`extract_isolated`], so both raw and enveloped SNS
+/// deliveries are handled and a context attached on the calling thread is never
+/// inherited. A failed call marks the span as an error.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::layer::TraceExtractionLayer;
+/// use tower::ServiceBuilder;
+///
+/// let mut handler = ServiceBuilder::new()
+///     .layer(TraceExtractionLayer::new())
+///     .service_fn(handle_message);
+/// handler.ready().await?.call(msg).await?;
+/// ```
+#[derive(Clone)]
+pub struct TraceExtractionLayer {
+    tracer: Arc<BoxedTracer>,
+}
+
+impl TraceExtractionLayer {
+    /// Creates a layer using the global tracer provider.
+    pub fn new() -> Self {
+        Self {
+            tracer: Arc::new(global::tracer(env!("CARGO_PKG_NAME"))),
+        }
+    }
+
+    /// Uses `tracer` instead of the global tracer to create consumer spans.
+    pub fn with_tracer(mut self, tracer: BoxedTracer) -> Self {
+        self.tracer = Arc::new(tracer);
+        self
+    }
+}
+
+impl Default for TraceExtractionLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for TraceExtractionLayer {
+    type Service = TraceExtractionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceExtractionService {
+            inner,
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+/// The service produced by [`TraceExtractionLayer`].
+#[derive(Clone)]
+pub struct TraceExtractionService<S> {
+    inner: S,
+    tracer: Arc<BoxedTracer>,
+}
+
+impl<S> Service<Message> for TraceExtractionService<S>
+where
+    S: Service<Message>,
+    S::Future: Send + 'static,
+    S::Error: Display,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, msg: Message) -> Self::Future {
+        let parent_cx = extract_isolated(&msg);
+        crate::guard::warn_if_leaked("sqs.process");
+        let span = self
+            .tracer
+            .span_builder("sqs.process")
+            .with_kind(SpanKind::Consumer)
+            .with_attributes(process_span_attributes(&msg))
+            .start_with_context(self.tracer.as_ref(), &parent_cx);
+        let cx = parent_cx.with_span(span);
+
+        let future = self.inner.call(msg).with_context(cx.clone());
+        Box::pin(async move {
+            let result = future.await;
+            if let Err(e) = &result {
+                cx.span().set_status(Status::error(e.to_string()));
+            }
+            cx.span().end();
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use aws_sdk_sqs::types::MessageAttributeValue;
+    use opentelemetry::trace::TracerProvider;
+    use tower::{service_fn, ServiceExt};
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    #[tokio::test]
+    async fn test_layer_runs_service_in_consumer_span() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let layer = TraceExtractionLayer::new()
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+        let service = layer.layer(service_fn(|msg: Message| async move {
+            let span = opentelemetry::Context::current()
+                .span()
+                .span_context()
+                .clone();
+            match msg.body() {
+                Some("ok") => Ok(span.trace_id().to_string()),
+                _ => Err("handler failed"),
+            }
+        }));
+        let msg = |body: &str| {
+            Message::builder()
+                .message_id(body)
+                .body(body)
+                .message_attributes(
+                    "traceparent",
+                    MessageAttributeValue::builder()
+                        .data_type("String")
+                        .string_value(traceparent(TRACE_ID, "00f067aa0ba902b7"))
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+        };
+
+        let trace_id = service.clone().oneshot(msg("ok")).await.unwrap();
+        assert!(service.oneshot(msg("fail")).await.is_err());
+
+        assert_eq!(trace_id, TRACE_ID);
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].span_kind, SpanKind::Consumer);
+        assert_eq!(spans[0].parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert_eq!(spans[0].status, Status::Unset);
+        assert_eq!(spans[1].status, Status::error("handler failed"));
+    }
+
+    #[tokio::test]
+    async fn test_layer_ignores_the_ambient_context() {
+        use opentelemetry::baggage::BaggageExt;
+        use opentelemetry::KeyValue;
+
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let layer = TraceExtractionLayer::new()
+            .with_tracer(BoxedTracer::new(Box::new(provider.tracer("test"))));
+        let service = layer.layer(service_fn(|_: Message| async {
+            assert_eq!(opentelemetry::Context::current().baggage().len(), 0);
+            Ok::<_, &str>(())
+        }));
+        let ambient =
+            crate::test_util::sampled_context().with_baggage(vec![KeyValue::new("tenant", "acme")]);
+
+        let _guard = ambient.attach();
+        service
+            .oneshot(Message::builder().message_id("untraced").build())
+            .await
+            .unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            spans[0].parent_span_id,
+            opentelemetry::trace::SpanId::INVALID
+        );
+        assert_ne!(spans[0].span_context.trace_id().to_string(), TRACE_ID);
+    }
+}
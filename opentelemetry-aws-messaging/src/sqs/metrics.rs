@@ -0,0 +1,123 @@
+//! Queue dwell time as an OpenTelemetry histogram (`metrics` feature).
+//!
+//! [`start_span_at_sent_time`](super::start_span_at_sent_time) shows how long one
+//! message waited in the queue; alerting on queue latency needs the same timing as an
+//! aggregatable metric. [`DwellTimeRecorder`] records the time between a message's
+//! `SentTimestamp` and its receipt in the [`DWELL_TIME_METRIC`] histogram, in seconds.
+
+use super::sent_timestamp;
+use aws_sdk_sqs::types::Message;
+use opentelemetry::metrics::{Histogram, Meter};
+use opentelemetry::KeyValue;
+use std::time::{Duration, SystemTime};
+
+/// The histogram recording how long messages waited in the queue, in seconds.
+pub const DWELL_TIME_METRIC: &str = "messaging.sqs.dwell_time";
+
+/// Records queue dwell time into the [`DWELL_TIME_METRIC`] histogram.
+///
+/// Create one recorder per meter and share it across consumers; the histogram is
+/// registered once, in [`new`](Self::new).
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::{global, KeyValue};
+/// use opentelemetry_aws_messaging::sqs::metrics::DwellTimeRecorder;
+///
+/// let dwell_time = DwellTimeRecorder::new(&global::meter("orders-consumer"));
+/// let output = client
+///     .receive_message()
+///     .queue_url(&queue_url)
+///     .message_system_attribute_names(MessageSystemAttributeName::SentTimestamp)
+///     .send()
+///     .await?;
+/// let received_at = SystemTime::now();
+/// for msg in output.messages.unwrap_or_default() {
+///     dwell_time.record(&msg, received_at, &[KeyValue::new("messaging.destination.name", "orders")]);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DwellTimeRecorder {
+    histogram: Histogram<f64>,
+}
+
+impl DwellTimeRecorder {
+    /// Registers the [`DWELL_TIME_METRIC`] histogram with `meter`.
+    pub fn new(meter: &Meter) -> Self {
+        let histogram = meter
+            .f64_histogram(DWELL_TIME_METRIC)
+            .with_unit("s")
+            .with_description("Time between a message being sent to the queue and received")
+            .build();
+        Self { histogram }
+    }
+
+    /// Records the dwell time of `msg`, received at `received_at`, with `attributes`.
+    ///
+    /// `SentTimestamp` must be requested through `message_system_attribute_names`;
+    /// messages without it are not recorded and yield `None`. The timestamp comes from
+    /// the SQS clock, so a `received_at` that appears earlier because of clock skew is
+    /// recorded as zero. Returns the recorded duration.
+    pub fn record(
+        &self,
+        msg: &Message,
+        received_at: SystemTime,
+        attributes: &[KeyValue],
+    ) -> Option<Duration> {
+        let dwell = received_at
+            .duration_since(sent_timestamp(msg)?)
+            .unwrap_or_default();
+        self.histogram.record(dwell.as_secs_f64(), attributes);
+        Some(dwell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_sqs::types::MessageSystemAttributeName;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+    use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn test_record_adds_dwell_time_to_histogram() {
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(exporter.clone()).build())
+            .build();
+        let recorder = DwellTimeRecorder::new(&provider.meter("test"));
+        let msg = Message::builder()
+            .attributes(MessageSystemAttributeName::SentTimestamp, "1705314600000")
+            .build();
+        let received_at = UNIX_EPOCH + Duration::from_millis(1_705_314_602_500);
+
+        assert_eq!(
+            recorder.record(&msg, received_at, &[]),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(
+            recorder.record(&Message::builder().build(), received_at, &[]),
+            None
+        );
+        provider.force_flush().unwrap();
+
+        let metrics = exporter.get_finished_metrics().unwrap();
+        let metric = metrics[0]
+            .scope_metrics()
+            .next()
+            .unwrap()
+            .metrics()
+            .next()
+            .unwrap();
+        assert_eq!(metric.name(), DWELL_TIME_METRIC);
+        let AggregatedMetrics::F64(MetricData::Histogram(histogram)) = metric.data() else {
+            panic!("expected an f64 histogram");
+        };
+        let point = histogram.data_points().next().unwrap();
+        assert_eq!(point.count(), 1);
+        assert_eq!(point.sum(), 2.5);
+    }
+}
@@ -0,0 +1,217 @@
+//! Copying messages between queues without touching their trace context.
+//!
+//! Queue migrations move messages from an old queue to a new one. The consumers of
+//! the new queue should continue the producer's trace, not the migration tool's, so
+//! [`copy_message`] sends each message with its body and message attributes exactly
+//! as received: the trace fields are never re-extracted or re-injected. The copy
+//! itself is recorded as a span in the tool's own trace, linked to the producer's
+//! span so the hop can still be found from the message's trace.
+//!
+//! Only message attributes are copied. The X-Ray `AWSTraceHeader` system attribute
+//! is not requested on receive nor set on the copy, so a context carried only there
+//! is dropped; producers that rely on it should also propagate through message
+//! attributes.
+
+use super::client::SqsReceiveClient;
+use super::extract_isolated;
+use crate::semconv::{queue_name, sqs_producer_attributes};
+use aws_sdk_sqs::types::Message;
+use opentelemetry::trace::{Link, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+
+/// The span attribute recording the name of the queue a message was copied from.
+pub const SOURCE_QUEUE_ATTRIBUTE: &str = "messaging.source.name";
+
+/// How long [`copy_message`] long-polls the source queue, the SQS maximum.
+const WAIT_TIME_SECONDS: i32 = 20;
+
+/// Moves one message from `source_queue_url` to `target_queue_url`, returning the
+/// message as received or `None` when the source queue stayed empty.
+///
+/// The source queue is long-polled for up to 20 seconds, so `None` means no message
+/// arrived in that time rather than that a short poll happened to sample only empty
+/// servers.
+///
+/// The message is sent with its original body and message attributes, trace
+/// context included, and deleted from the source queue once the copy is sent, so
+/// repeated calls drain the old queue. If the send fails the message is left on the
+/// source queue and redelivered after its visibility timeout.
+///
+/// Each copy is recorded as a `SpanKind::Producer` `copy <target queue>` span, a
+/// child of the current context, linked to the context carried by the message and
+/// with the source queue name as [`SOURCE_QUEUE_ATTRIBUTE`].
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::migration::copy_message;
+///
+/// while copy_message(&client, &tracer, &old_queue_url, &new_queue_url)
+///     .await?
+///     .is_some()
+/// {}
+/// ```
+pub async fn copy_message<C, T>(
+    client: &C,
+    tracer: &T,
+    source_queue_url: &str,
+    target_queue_url: &str,
+) -> Result<Option<Message>, aws_sdk_sqs::Error>
+where
+    C: SqsReceiveClient,
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let Some(msg) = client
+        .receive(source_queue_url, 1, WAIT_TIME_SECONDS)
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+
+    let producer_span_cx = extract_isolated(&msg).span().span_context().clone();
+    let mut attributes = sqs_producer_attributes(target_queue_url);
+    attributes.push(KeyValue::new(
+        SOURCE_QUEUE_ATTRIBUTE,
+        queue_name(source_queue_url).to_string(),
+    ));
+    if let Some(id) = msg.message_id() {
+        attributes.push(KeyValue::new("messaging.message.id", id.to_string()));
+    }
+    let mut builder = tracer
+        .span_builder(format!("copy {}", queue_name(target_queue_url)))
+        .with_kind(SpanKind::Producer)
+        .with_attributes(attributes);
+    if producer_span_cx.is_valid() {
+        builder = builder.with_links(vec![Link::new(producer_span_cx, Vec::new(), 0)]);
+    }
+    let parent_cx = Context::current();
+    let cx = parent_cx.with_span(builder.start_with_context(tracer, &parent_cx));
+
+    let result = copy(client, &msg, source_queue_url, target_queue_url).await;
+    if let Err(e) = &result {
+        cx.span().set_status(Status::error(e.to_string()));
+    }
+    cx.span().end();
+    result.map(|()| Some(msg))
+}
+
+async fn copy<C: SqsReceiveClient>(
+    client: &C,
+    msg: &Message,
+    source_queue_url: &str,
+    target_queue_url: &str,
+) -> Result<(), aws_sdk_sqs::Error> {
+    client
+        .send(
+            target_queue_url,
+            msg.body().map(str::to_string),
+            msg.message_attributes().cloned(),
+        )
+        .await?;
+    if let Some(receipt_handle) = msg.receipt_handle() {
+        client.delete(source_queue_url, receipt_handle).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sqs::client::InMemorySqsClient;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use aws_sdk_sqs::types::MessageAttributeValue;
+    use opentelemetry::trace::TracerProvider;
+
+    const OLD_QUEUE_URL: &str = "https://sqs.us-east-1.amazonaws.com/123456789012/orders";
+    const NEW_QUEUE_URL: &str = "https://sqs.us-east-1.amazonaws.com/123456789012/orders-v2";
+
+    fn attribute(value: &str) -> MessageAttributeValue {
+        MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_copy_message_preserves_trace_attributes() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let tracer = provider.tracer("test");
+        let client = InMemorySqsClient::default();
+        let traceparent = traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7");
+        let original = Message::builder()
+            .message_id("m-1")
+            .body("order-42")
+            .message_attributes("traceparent", attribute(&traceparent))
+            .message_attributes("tracestate", attribute("dd=s:1;o:rum"))
+            .message_attributes("baggage", attribute("tenant=acme"))
+            .build();
+        client.push(OLD_QUEUE_URL, original.clone());
+
+        let copied = copy_message(&client, &tracer, OLD_QUEUE_URL, NEW_QUEUE_URL)
+            .await
+            .unwrap();
+        let empty = copy_message(&client, &tracer, OLD_QUEUE_URL, NEW_QUEUE_URL)
+            .await
+            .unwrap();
+
+        assert_eq!(copied.unwrap().message_id(), Some("m-1"));
+        assert!(empty.is_none());
+        assert!(client.messages(OLD_QUEUE_URL).is_empty());
+        let moved = client.messages(NEW_QUEUE_URL);
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].body(), Some("order-42"));
+        assert_eq!(moved[0].message_attributes(), original.message_attributes());
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "copy orders-v2");
+        assert_eq!(spans[0].span_kind, SpanKind::Producer);
+        assert_ne!(
+            spans[0].span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(
+            spans[0].links.links[0].span_context.span_id().to_string(),
+            "00f067aa0ba902b7"
+        );
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new(SOURCE_QUEUE_ATTRIBUTE, "orders")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("messaging.operation.type", "send")));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("cloud.account.id", "123456789012")));
+    }
+
+    #[tokio::test]
+    async fn test_copy_message_long_polls_the_source_queue() {
+        use aws_sdk_sqs::operation::receive_message::ReceiveMessageOutput;
+        use aws_sdk_sqs::Client;
+        use aws_smithy_mocks::{mock, mock_client};
+
+        let (provider, _exporter) = test_tracer();
+        let receive = mock!(Client::receive_message)
+            .match_requests(|req| req.wait_time_seconds() == Some(20))
+            .then_output(|| ReceiveMessageOutput::builder().build());
+        let client = mock_client!(aws_sdk_sqs, [&receive]);
+
+        let copied = copy_message(
+            &client,
+            &provider.tracer("test"),
+            OLD_QUEUE_URL,
+            NEW_QUEUE_URL,
+        )
+        .await
+        .unwrap();
+
+        assert!(copied.is_none());
+        assert_eq!(receive.num_calls(), 1);
+    }
+}
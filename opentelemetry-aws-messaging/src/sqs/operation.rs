@@ -0,0 +1,214 @@
+//! Separate `receive` and `process` spans, following the messaging semantic
+//! conventions.
+//!
+//! The conventions distinguish getting messages from the queue from handling them.
+//! Recording both in one span makes a poll that waited 20 seconds look like a slow
+//! handler, so this module models them as distinct spans:
+//!
+//! ```text
+//! producer trace:  publish ─────────────┐ (parent)
+//!                                       ▼
+//! consumer:        receive orders    process orders   (one per message)
+//!                        ▲                 │
+//!                        └──── link ───────┘
+//! ```
+//!
+//! - A short-lived `SpanKind::Client` **receive** span around each `ReceiveMessage`
+//!   poll, started with [`start_receive_span`]. It belongs to the consumer's own
+//!   trace and records how many messages the poll returned.
+//! - A `SpanKind::Consumer` **process** span per message, started with
+//!   [`start_process_span`]. It is a child of the producer's context, so the
+//!   end-to-end trace stays connected, and links to both the receive span and the
+//!   producer's span. A message without trace context is parented to the receive
+//!   span instead.
+//!
+//! Both spans carry `messaging.operation.type` (`receive` or `process`), the older
+//! `messaging.operation` name that some dashboards still group by, and the queue
+//! name as `messaging.destination.name`.
+
+use super::{extract_isolated, process_span_attributes};
+use crate::semconv::queue_name;
+use aws_sdk_sqs::types::Message;
+use opentelemetry::trace::{Link, SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+
+pub use crate::semconv::{LEGACY_OPERATION_ATTRIBUTE, OPERATION_TYPE_ATTRIBUTE};
+
+/// The span attribute recording how many messages a poll returned.
+pub const BATCH_MESSAGE_COUNT_ATTRIBUTE: &str = "messaging.batch.message_count";
+
+/// Starts a `SpanKind::Client` `receive <queue>` span for a poll of `queue_url`, as a
+/// child of the current context.
+///
+/// The returned context holds the span; the caller records the number of received
+/// messages as [`BATCH_MESSAGE_COUNT_ATTRIBUTE`] and ends it as soon as the poll
+/// returns, before processing the messages.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::TraceContextExt;
+/// use opentelemetry::KeyValue;
+/// use opentelemetry_aws_messaging::sqs::operation::{self, BATCH_MESSAGE_COUNT_ATTRIBUTE};
+///
+/// let receive_cx = operation::start_receive_span(&tracer, &queue_url);
+/// let output = client.receive_message().queue_url(&queue_url).send().await?;
+/// let messages = output.messages.unwrap_or_default();
+/// receive_cx
+///     .span()
+///     .set_attribute(KeyValue::new(BATCH_MESSAGE_COUNT_ATTRIBUTE, messages.len() as i64));
+/// receive_cx.span().end();
+///
+/// for msg in &messages {
+///     let cx = operation::start_process_span(&tracer, &queue_url, msg, &receive_cx);
+///     handle(msg).with_context(cx.clone()).await?;
+///     cx.span().end();
+/// }
+/// ```
+pub fn start_receive_span<T>(tracer: &T, queue_url: &str) -> Context
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let cx = Context::current();
+    let span = tracer
+        .span_builder(format!("receive {}", queue_name(queue_url)))
+        .with_kind(SpanKind::Client)
+        .with_attributes(operation_attributes("receive", queue_url))
+        .start_with_context(tracer, &cx);
+    cx.with_span(span)
+}
+
+/// Starts a `SpanKind::Consumer` `process <queue>` span for `msg`, received by the
+/// poll in `receive_cx`.
+///
+/// The span is a child of the context extracted from `msg` (see
+/// [`extract_isolated`]) and links to the receive span and to the producer's span.
+/// When `msg` carries no valid context, the span is a child of the receive span. The
+/// returned context holds the span; the caller ends it once the message is handled.
+pub fn start_process_span<T>(
+    tracer: &T,
+    queue_url: &str,
+    msg: &Message,
+    receive_cx: &Context,
+) -> Context
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let producer_cx = extract_isolated(msg);
+    let producer_span_cx = producer_cx.span().span_context().clone();
+    let receive_span_cx = receive_cx.span().span_context().clone();
+
+    let links = [
+        (receive_span_cx, "receive"),
+        (producer_span_cx.clone(), "producer"),
+    ]
+    .into_iter()
+    .filter(|(span_cx, _)| span_cx.is_valid())
+    .map(|(span_cx, source)| {
+        Link::new(
+            span_cx,
+            vec![KeyValue::new("messaging.link.source", source)],
+            0,
+        )
+    })
+    .collect();
+    let parent_cx = if producer_span_cx.is_valid() {
+        producer_cx
+    } else {
+        receive_cx.clone()
+    };
+
+    let mut attributes = operation_attributes("process", queue_url);
+    attributes.extend(process_span_attributes(msg));
+    let span = tracer
+        .span_builder(format!("process {}", queue_name(queue_url)))
+        .with_kind(SpanKind::Consumer)
+        .with_attributes(attributes)
+        .with_links(links)
+        .start_with_context(tracer, &parent_cx);
+    parent_cx.with_span(span)
+}
+
+fn operation_attributes(operation: &'static str, queue_url: &str) -> Vec<KeyValue> {
+    crate::semconv::sqs_attributes(operation, queue_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use aws_sdk_sqs::types::MessageAttributeValue;
+    use opentelemetry::trace::TracerProvider;
+
+    const QUEUE_URL: &str = "https://sqs.us-east-1.amazonaws.com/123456789012/orders";
+
+    fn operation_type(span: &opentelemetry_sdk::trace::SpanData) -> Option<String> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == OPERATION_TYPE_ATTRIBUTE)
+            .map(|kv| kv.value.to_string())
+    }
+
+    #[test]
+    fn test_process_span_is_child_of_producer_and_links_receive() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let tracer = provider.tracer("test");
+        let msg = Message::builder()
+            .message_attributes(
+                "traceparent",
+                MessageAttributeValue::builder()
+                    .data_type("String")
+                    .string_value(traceparent(
+                        "4bf92f3577b34da6a3ce929d0e0e4736",
+                        "00f067aa0ba902b7",
+                    ))
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+
+        let receive_cx = start_receive_span(&tracer, QUEUE_URL);
+        receive_cx.span().end();
+        let cx = start_process_span(&tracer, QUEUE_URL, &msg, &receive_cx);
+        cx.span().end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let (receive, process) = (&spans[0], &spans[1]);
+        assert_eq!(receive.name, "receive orders");
+        assert_eq!(receive.span_kind, SpanKind::Client);
+        assert_eq!(operation_type(receive).as_deref(), Some("receive"));
+        assert_eq!(process.name, "process orders");
+        assert_eq!(process.span_kind, SpanKind::Consumer);
+        assert_eq!(operation_type(process).as_deref(), Some("process"));
+        assert_eq!(process.parent_span_id.to_string(), "00f067aa0ba902b7");
+        let linked: Vec<_> = process
+            .links
+            .links
+            .iter()
+            .map(|link| link.span_context.span_id())
+            .collect();
+        assert_eq!(
+            linked,
+            vec![receive.span_context.span_id(), process.parent_span_id]
+        );
+    }
+
+    #[test]
+    fn test_process_span_without_context_is_child_of_receive() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let tracer = provider.tracer("test");
+
+        let receive_cx = start_receive_span(&tracer, QUEUE_URL);
+        let cx = start_process_span(&tracer, QUEUE_URL, &Message::builder().build(), &receive_cx);
+        cx.span().end();
+        receive_cx.span().end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].parent_span_id, spans[1].span_context.span_id());
+        assert_eq!(spans[0].links.links.len(), 1);
+    }
+}
@@ -0,0 +1,201 @@
+//! Consumer spans for the SQS request-response pattern.
+//!
+//! With temporary queues, a requester sends a message naming the queue to reply to
+//! and an id that pairs the reply with the request. The responder continues the
+//! requester's trace and needs both values to answer. [`RequestReplyConfig`] reads
+//! them from message attributes along with the trace context, by default:
+//!
+//! - `ResponseQueueUrl`, the reply-to queue set by the AWS temporary queue client,
+//!   recorded on the span as [`REPLY_TO_SPAN_ATTRIBUTE`]
+//! - `CorrelationId`, recorded on the span as [`CORRELATION_ID_SPAN_ATTRIBUTE`]
+//!
+//! Both names are configurable for clients that use other conventions.
+
+use super::{extract_isolated, process_span_attributes};
+use aws_sdk_sqs::types::Message;
+use opentelemetry::trace::{SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::borrow::Cow;
+
+/// The message attribute holding the reply-to queue URL by default.
+pub const DEFAULT_REPLY_TO_ATTRIBUTE: &str = "ResponseQueueUrl";
+
+/// The message attribute holding the correlation id by default.
+pub const DEFAULT_CORRELATION_ID_ATTRIBUTE: &str = "CorrelationId";
+
+/// The span attribute recording the reply-to queue URL.
+pub const REPLY_TO_SPAN_ATTRIBUTE: &str = "messaging.aws_sqs.reply_to";
+
+/// The span attribute recording the correlation id.
+pub const CORRELATION_ID_SPAN_ATTRIBUTE: &str = "messaging.message.conversation_id";
+
+/// The message attribute names of a request-response protocol.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::{FutureExt, TraceContextExt};
+/// use opentelemetry_aws_messaging::core::AttributeInjector;
+/// use opentelemetry_aws_messaging::sqs::request_reply::RequestReplyConfig;
+///
+/// let request = RequestReplyConfig::default().start_span(&tracer, "orders.quote", &msg);
+/// let reply = quote(&msg).with_context(request.cx.clone()).await?;
+/// if let Some(reply_to) = &request.reply_to {
+///     let mut attributes = HashMap::new();
+///     global::get_text_map_propagator(|propagator| {
+///         propagator.inject_context(&request.cx, &mut AttributeInjector(&mut attributes))
+///     });
+///     if let Some(correlation_id) = &request.correlation_id {
+///         AttributeInjector(&mut attributes).set("CorrelationId", correlation_id.clone());
+///     }
+///     client
+///         .send_message()
+///         .queue_url(reply_to)
+///         .message_body(reply)
+///         .set_message_attributes(Some(attributes))
+///         .send()
+///         .await?;
+/// }
+/// request.cx.span().end();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestReplyConfig {
+    reply_to_attribute: String,
+    correlation_id_attribute: String,
+}
+
+impl Default for RequestReplyConfig {
+    fn default() -> Self {
+        Self {
+            reply_to_attribute: DEFAULT_REPLY_TO_ATTRIBUTE.to_string(),
+            correlation_id_attribute: DEFAULT_CORRELATION_ID_ATTRIBUTE.to_string(),
+        }
+    }
+}
+
+/// A request being processed: the consumer span and the reply routing.
+#[derive(Debug)]
+pub struct RequestReplySpan {
+    /// The context holding the consumer span; the caller ends it once replied.
+    pub cx: Context,
+    /// The queue URL to send the reply to, if the request named one.
+    pub reply_to: Option<String>,
+    /// The id to copy onto the reply, if the request carried one.
+    pub correlation_id: Option<String>,
+}
+
+impl RequestReplyConfig {
+    /// Reads the reply-to queue URL from `attribute` instead of
+    /// [`DEFAULT_REPLY_TO_ATTRIBUTE`].
+    pub fn with_reply_to_attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.reply_to_attribute = attribute.into();
+        self
+    }
+
+    /// Reads the correlation id from `attribute` instead of
+    /// [`DEFAULT_CORRELATION_ID_ATTRIBUTE`].
+    pub fn with_correlation_id_attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.correlation_id_attribute = attribute.into();
+        self
+    }
+
+    /// Starts a `SpanKind::Consumer` span for the request `msg`.
+    ///
+    /// The span is a child of the context extracted from `msg` (see
+    /// [`extract_isolated`]) and records the reply-to queue URL and correlation id,
+    /// when present, as [`REPLY_TO_SPAN_ATTRIBUTE`] and
+    /// [`CORRELATION_ID_SPAN_ATTRIBUTE`]. Only `String` and `Number` attributes are
+    /// read.
+    pub fn start_span<T>(
+        &self,
+        tracer: &T,
+        name: impl Into<Cow<'static, str>>,
+        msg: &Message,
+    ) -> RequestReplySpan
+    where
+        T: Tracer,
+        T::Span: Send + Sync + 'static,
+    {
+        let attribute = |name: &str| {
+            msg.message_attributes()?
+                .get(name)?
+                .string_value()
+                .map(str::to_string)
+        };
+        let reply_to = attribute(&self.reply_to_attribute);
+        let correlation_id = attribute(&self.correlation_id_attribute);
+
+        let mut attributes = process_span_attributes(msg);
+        if let Some(reply_to) = &reply_to {
+            attributes.push(KeyValue::new(REPLY_TO_SPAN_ATTRIBUTE, reply_to.clone()));
+        }
+        if let Some(correlation_id) = &correlation_id {
+            attributes.push(KeyValue::new(
+                CORRELATION_ID_SPAN_ATTRIBUTE,
+                correlation_id.clone(),
+            ));
+        }
+        let parent_cx = extract_isolated(msg);
+        let span = tracer
+            .span_builder(name)
+            .with_kind(SpanKind::Consumer)
+            .with_attributes(attributes)
+            .start_with_context(tracer, &parent_cx);
+        RequestReplySpan {
+            cx: parent_cx.with_span(span),
+            reply_to,
+            correlation_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use aws_sdk_sqs::types::MessageAttributeValue;
+    use opentelemetry::trace::TracerProvider;
+
+    const REPLY_TO: &str = "https://sqs.us-east-1.amazonaws.com/123456789012/quotes-reply-7f3a";
+
+    fn attribute(value: &str) -> MessageAttributeValue {
+        MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_start_span_records_reply_routing_with_custom_names() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let msg = Message::builder()
+            .message_attributes(
+                "traceparent",
+                attribute(&traceparent(
+                    "4bf92f3577b34da6a3ce929d0e0e4736",
+                    "00f067aa0ba902b7",
+                )),
+            )
+            .message_attributes("ReplyTo", attribute(REPLY_TO))
+            .message_attributes("CorrelationId", attribute("quote-42"))
+            .build();
+
+        let request = RequestReplyConfig::default()
+            .with_reply_to_attribute("ReplyTo")
+            .start_span(&provider.tracer("test"), "quote", &msg);
+        request.cx.span().end();
+
+        assert_eq!(request.reply_to.as_deref(), Some(REPLY_TO));
+        assert_eq!(request.correlation_id.as_deref(), Some("quote-42"));
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans[0].parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new(REPLY_TO_SPAN_ATTRIBUTE, REPLY_TO)));
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new(CORRELATION_ID_SPAN_ATTRIBUTE, "quote-42")));
+    }
+}
@@ -0,0 +1,131 @@
+//! Extraction from Amazon SES email events delivered to SQS through SNS.
+//!
+//! SES publishes sending events (`Send`, `Delivery`, `Bounce`, ...) and receipt
+//! notifications to an SNS topic, so the SQS body is an SNS envelope whose `Message` is
+//! the SES event (or the event itself with raw message delivery on). SES sets no
+//! message attributes, so the trace context can only come from the email that caused
+//! the event:
+//!
+//! ```text
+//! {
+//!   "eventType": "Delivery",
+//!   "mail": {
+//!     "messageId": "...",
+//!     "headers": [{"name": "traceparent", "value": "00-...-01"}, ...],
+//!     "tags": {"traceparent": ["00-...-01"], ...}
+//!   },
+//!   "delivery": {...}
+//! }
+//! ```
+//!
+//! The trace fields are looked up in `mail.headers`, where SES copies the original
+//! email headers when the configuration set or identity includes them, and then in
+//! `mail.tags`, which hold the message tags of `SendEmail`. Header names are matched
+//! case-insensitively, as email headers are. Tag values only allow alphanumerics,
+//! `_`, `-`, `.`, and `@`, which fits a `traceparent` but not a `tracestate`, so the
+//! sender should inject the context as email headers when it can.
+
+use super::{SnsEnvelope, SnsEnvelopeExtractor};
+use crate::config::DEFAULT_MAX_BODY_SIZE;
+use opentelemetry::propagation::Extractor;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// An [`Extractor`] over the trace fields of an SES event's `mail` object.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::ses::SesEventExtractor;
+///
+/// if let Some(extractor) = SesEventExtractor::parse(msg.body().unwrap_or_default()) {
+///     let parent_cx = global::get_text_map_propagator(|propagator| propagator.extract(&extractor));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct SesEventExtractor {
+    fields: HashMap<String, String>,
+}
+
+impl SesEventExtractor {
+    /// Parses an SES event out of `body`, either wrapped in an SNS envelope or as is.
+    ///
+    /// Returns `None` when the body is not an SES event: not a JSON object, larger
+    /// than [`DEFAULT_MAX_BODY_SIZE`], or without a `mail` object and an `eventType`
+    /// or `notificationType`. An event without trace fields yields no keys.
+    pub fn parse(body: &str) -> Option<Self> {
+        if body.len() > DEFAULT_MAX_BODY_SIZE {
+            return None;
+        }
+        let event = match SnsEnvelopeExtractor::parse(body) {
+            SnsEnvelope::Notification(envelope) => {
+                serde_json::from_str::<Value>(&envelope.notification().message).ok()?
+            }
+            _ => serde_json::from_str::<Value>(body).ok()?,
+        };
+        if event.get("eventType").is_none() && event.get("notificationType").is_none() {
+            return None;
+        }
+        let mail = event.get("mail").filter(|mail| mail.is_object())?;
+
+        let mut fields: HashMap<String, String> = mail
+            .get("tags")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, values)| {
+                let value = values.get(0)?.as_str()?;
+                Some((name.to_ascii_lowercase(), value.to_string()))
+            })
+            .collect();
+        // Headers take precedence over tags.
+        fields.extend(
+            mail.get("headers")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|header| {
+                    let name = header.get("name")?.as_str()?;
+                    let value = header.get("value")?.as_str()?;
+                    Some((name.to_ascii_lowercase(), value.to_string()))
+                }),
+        );
+        Some(Self { fields })
+    }
+}
+
+impl Extractor for SesEventExtractor {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .get(&key.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.fields.keys().map(String::as_str).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prefers_headers_over_tags() {
+        let event = r#"{"eventType":"Send","mail":{
+            "headers":[{"name":"Traceparent","value":"00-a-b-01"}],
+            "tags":{"traceparent":["00-c-d-01"],"campaign":["spring"]}}}"#;
+
+        let extractor = SesEventExtractor::parse(event).unwrap();
+
+        assert_eq!(extractor.get("traceparent"), Some("00-a-b-01"));
+        assert_eq!(extractor.get("campaign"), Some("spring"));
+    }
+
+    #[test]
+    fn test_parse_rejects_other_bodies() {
+        assert!(SesEventExtractor::parse(r#"{"mail":{}}"#).is_none());
+        assert!(SesEventExtractor::parse(r#"{"eventType":"Send"}"#).is_none());
+        assert!(SesEventExtractor::parse("plain text").is_none());
+    }
+}
@@ -0,0 +1,100 @@
+//! Context propagation for Step Functions tasks that read from SQS.
+//!
+//! A worker orchestrated by a Step Functions state machine has two sources of
+//! lineage: the SQS message it pulled, and the state-machine execution it runs in.
+//! The message's trace context stays the source of the span's parent; the execution
+//! id (`$$.Execution.Id`, passed in the task input) is recorded as a span attribute so
+//! the trace can also be correlated with the orchestration.
+
+use super::{extract_isolated, process_span_attributes};
+use aws_sdk_sqs::types::Message;
+use opentelemetry::trace::{SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::borrow::Cow;
+
+/// The span attribute recording the Step Functions execution id.
+pub const EXECUTION_ID_ATTRIBUTE: &str = "aws.step_functions.execution.id";
+
+/// Starts a `SpanKind::Consumer` span for a Step Functions task processing `msg`.
+///
+/// The span is a child of the context extracted from `msg` (see
+/// [`extract_isolated`]) and carries `execution_id` as [`EXECUTION_ID_ATTRIBUTE`]. A
+/// message without trace context starts a new trace, still tagged with the execution.
+/// The returned context holds the span; the caller ends it once the task is done.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry::trace::TraceContextExt;
+/// use opentelemetry_aws_messaging::sqs::step_functions::start_task_span;
+///
+/// // `execution_id` comes from the task input, e.g. `"ExecutionId.$": "$$.Execution.Id"`
+/// let cx = start_task_span(&tracer, "orders.fulfil", &msg, &input.execution_id);
+/// handle(&msg).with_context(cx.clone()).await?;
+/// cx.span().end();
+/// ```
+pub fn start_task_span<T>(
+    tracer: &T,
+    name: impl Into<Cow<'static, str>>,
+    msg: &Message,
+    execution_id: &str,
+) -> Context
+where
+    T: Tracer,
+    T::Span: Send + Sync + 'static,
+{
+    let parent_cx = extract_isolated(msg);
+    let mut attributes = process_span_attributes(msg);
+    attributes.push(KeyValue::new(
+        EXECUTION_ID_ATTRIBUTE,
+        execution_id.to_string(),
+    ));
+    let span = tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Consumer)
+        .with_attributes(attributes)
+        .start_with_context(tracer, &parent_cx);
+    parent_cx.with_span(span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, test_tracer, traceparent};
+    use aws_sdk_sqs::types::MessageAttributeValue;
+    use opentelemetry::trace::TracerProvider;
+
+    const EXECUTION_ID: &str = "arn:aws:states:us-east-1:123456789012:execution:orders:run-1";
+
+    #[test]
+    fn test_task_span_continues_message_trace_and_records_execution() {
+        init_propagator();
+        let (provider, exporter) = test_tracer();
+        let msg = Message::builder()
+            .message_attributes(
+                "traceparent",
+                MessageAttributeValue::builder()
+                    .data_type("String")
+                    .string_value(traceparent(
+                        "4bf92f3577b34da6a3ce929d0e0e4736",
+                        "00f067aa0ba902b7",
+                    ))
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+
+        let cx = start_task_span(&provider.tracer("test"), "task", &msg, EXECUTION_ID);
+        cx.span().end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(
+            spans[0].span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(spans[0].parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new(EXECUTION_ID_ATTRIBUTE, EXECUTION_ID)));
+    }
+}
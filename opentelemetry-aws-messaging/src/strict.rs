@@ -0,0 +1,132 @@
+//! Strict extraction for pipelines where every message must be traced.
+//!
+//! The extractors in this crate are best-effort: a message without trace fields
+//! yields an empty context and processing continues in a new trace. Extracting
+//! through [`require_valid`] opts out of that behavior, so untraced messages can be
+//! routed to an error flow (e.g. a dead-letter queue) instead.
+
+use crate::error::PropagationError;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::{global, Context};
+
+/// Extracts a parent [`Context`] from `extractor` using the global propagator,
+/// failing when it does not carry a valid span context.
+///
+/// # Errors
+///
+/// Returns [`PropagationError::MissingContext`] when the extracted span context is
+/// not valid, i.e. the trace fields are absent or malformed. A context attached on the
+/// calling thread is never returned in their place.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::strict::require_valid;
+/// use opentelemetry_aws_messaging::sqs::CompositeExtractor;
+///
+/// match require_valid(&CompositeExtractor::from_message(&msg)) {
+///     Ok(parent_cx) => process(msg).with_context(parent_cx).await?,
+///     Err(e) => send_to_dead_letter_queue(msg, e).await?,
+/// }
+/// ```
+pub fn require_valid(extractor: &dyn Extractor) -> Result<Context, PropagationError> {
+    let cx = global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), extractor)
+    });
+    if cx.span().span_context().is_valid() {
+        Ok(cx)
+    } else {
+        Err(PropagationError::MissingContext)
+    }
+}
+
+/// Returns `true` when `extractor` carries a valid parent context for the global
+/// propagator, i.e. processing the message continues an existing trace, and `false`
+/// when it would start a new one.
+///
+/// This is [`require_valid`] as a predicate, for consumers that branch on it, e.g. to
+/// log trace roots differently or sample them at another rate.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::strict::is_trace_continuation;
+///
+/// let extractor = CompositeExtractor::from_message(&msg);
+/// if !is_trace_continuation(&extractor) {
+///     tracing::info!(message_id = msg.message_id(), "message starts a new trace");
+/// }
+/// ```
+pub fn is_trace_continuation(extractor: &dyn Extractor) -> bool {
+    require_valid(extractor).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, traceparent};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_require_valid_returns_extracted_context() {
+        init_propagator();
+        let headers = HashMap::from([(
+            "traceparent".to_string(),
+            traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+        )]);
+
+        let cx = require_valid(&headers).unwrap();
+
+        assert_eq!(
+            cx.span().span_context().trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+
+    #[test]
+    fn test_require_valid_rejects_missing_or_malformed_context() {
+        init_propagator();
+        let malformed = HashMap::from([("traceparent".to_string(), "garbage".to_string())]);
+
+        assert_eq!(
+            require_valid(&HashMap::<String, String>::new()).unwrap_err(),
+            PropagationError::MissingContext
+        );
+        assert_eq!(
+            require_valid(&malformed).unwrap_err(),
+            PropagationError::MissingContext
+        );
+    }
+
+    #[test]
+    fn test_require_valid_ignores_the_ambient_context() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+
+        assert_eq!(
+            require_valid(&HashMap::<String, String>::new()).unwrap_err(),
+            PropagationError::MissingContext
+        );
+    }
+
+    #[test]
+    fn test_is_trace_continuation() {
+        init_propagator();
+        let headers = HashMap::from([(
+            "traceparent".to_string(),
+            traceparent("4bf92f3577b34da6a3ce929d0e0e4736", "00f067aa0ba902b7"),
+        )]);
+
+        assert!(is_trace_continuation(&headers));
+        assert!(!is_trace_continuation(&HashMap::<String, String>::new()));
+    }
+
+    #[test]
+    fn test_is_trace_continuation_ignores_the_ambient_context() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+
+        assert!(!is_trace_continuation(&HashMap::<String, String>::new()));
+    }
+}
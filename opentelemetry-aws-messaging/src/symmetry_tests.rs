@@ -0,0 +1,176 @@
+//! The same logical cases run against the SNS and SQS carriers, which must behave
+//! identically. Both are thin wrappers over the generic carriers in
+//! [`core`](crate::core), parameterized by [`AttributeValue`](crate::core::AttributeValue).
+
+macro_rules! extractor_cases {
+    ($name:ident, $module:ident, $sdk:ident, $list_attr:expr) => {
+        mod $name {
+            use crate::config::{CarrierConfig, Encoding};
+            use crate::$module::{ConfiguredExtractor, MessageAttributesExtractor};
+            use opentelemetry::propagation::Extractor;
+            use std::collections::HashMap;
+            use $sdk::primitives::Blob;
+            use $sdk::types::MessageAttributeValue;
+
+            const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+            fn string_attr(value: &str) -> MessageAttributeValue {
+                MessageAttributeValue::builder()
+                    .data_type("String")
+                    .string_value(value)
+                    .build()
+                    .unwrap()
+            }
+
+            fn binary_attr(value: &[u8]) -> MessageAttributeValue {
+                MessageAttributeValue::builder()
+                    .data_type("Binary")
+                    .binary_value(Blob::new(value))
+                    .build()
+                    .unwrap()
+            }
+
+            fn attrs(value: MessageAttributeValue) -> HashMap<String, MessageAttributeValue> {
+                HashMap::from([("traceparent".to_string(), value)])
+            }
+
+            fn binary_config() -> CarrierConfig {
+                CarrierConfig {
+                    encoding: Encoding::Binary,
+                    ..Default::default()
+                }
+            }
+
+            #[test]
+            fn test_string_value_is_read() {
+                let attrs = attrs(string_attr(TRACEPARENT));
+                let config = CarrierConfig::default();
+
+                assert_eq!(
+                    MessageAttributesExtractor(&attrs).get("traceparent"),
+                    Some(TRACEPARENT)
+                );
+                assert_eq!(
+                    ConfiguredExtractor::new(&attrs, &config).get("traceparent"),
+                    Some(TRACEPARENT)
+                );
+                assert_eq!(
+                    MessageAttributesExtractor(&attrs).keys(),
+                    vec!["traceparent"]
+                );
+            }
+
+            #[test]
+            fn test_binary_value_is_read_as_fallback() {
+                let attrs = attrs(binary_attr(TRACEPARENT.as_bytes()));
+                let config = binary_config();
+
+                assert_eq!(
+                    MessageAttributesExtractor(&attrs).get("traceparent"),
+                    Some(TRACEPARENT)
+                );
+                assert_eq!(
+                    ConfiguredExtractor::new(&attrs, &config).get("traceparent"),
+                    Some(TRACEPARENT)
+                );
+                assert_eq!(
+                    ConfiguredExtractor::new(&attrs, &CarrierConfig::default()).get("traceparent"),
+                    None
+                );
+            }
+
+            #[test]
+            fn test_invalid_utf8_binary_value_is_ignored() {
+                let attrs = attrs(binary_attr(&[0xff, 0xfe]));
+                let config = binary_config();
+
+                assert_eq!(MessageAttributesExtractor(&attrs).get("traceparent"), None);
+                assert_eq!(
+                    ConfiguredExtractor::new(&attrs, &config).get("traceparent"),
+                    None
+                );
+            }
+
+            #[test]
+            fn test_list_value_is_ignored() {
+                let attrs = attrs($list_attr);
+                let config = CarrierConfig::default();
+
+                assert_eq!(MessageAttributesExtractor(&attrs).get("traceparent"), None);
+                assert_eq!(
+                    ConfiguredExtractor::new(&attrs, &config).get("traceparent"),
+                    None
+                );
+            }
+        }
+    };
+}
+
+extractor_cases!(
+    sns,
+    sns,
+    aws_sdk_sns,
+    aws_sdk_sns::types::MessageAttributeValue::builder()
+        .data_type("String.Array")
+        .string_value(format!("[\"{TRACEPARENT}\"]"))
+        .build()
+        .unwrap()
+);
+
+extractor_cases!(
+    sqs,
+    sqs,
+    aws_sdk_sqs,
+    aws_sdk_sqs::types::MessageAttributeValue::builder()
+        .data_type("String")
+        .string_list_values(TRACEPARENT)
+        .build()
+        .unwrap()
+);
+
+macro_rules! injector_cases {
+    ($name:ident, $module:ident) => {
+        mod $name {
+            use crate::test_util::sampled_context;
+            use crate::$module::{MessageAttributesExtractor, MessageAttributesInjector};
+            use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+            use opentelemetry_sdk::propagation::TraceContextPropagator;
+            use std::collections::HashMap;
+
+            #[test]
+            fn test_injected_fields_are_string_attributes() {
+                let mut attrs = HashMap::new();
+
+                TraceContextPropagator::new().inject_context(
+                    &sampled_context(),
+                    &mut MessageAttributesInjector(&mut attrs),
+                );
+
+                assert_eq!(attrs.len(), 1);
+                assert_eq!(attrs["traceparent"].data_type(), "String");
+                assert_eq!(
+                    MessageAttributesExtractor(&attrs).get("traceparent"),
+                    Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+                );
+            }
+
+            #[test]
+            fn test_injected_field_replaces_existing_attribute() {
+                let mut attrs = HashMap::new();
+                let mut injector = MessageAttributesInjector(&mut attrs);
+
+                injector.set("traceparent", "first".to_string());
+                injector.set("traceparent", "second".to_string());
+
+                assert_eq!(attrs.len(), 1);
+                assert_eq!(
+                    MessageAttributesExtractor(&attrs).get("traceparent"),
+                    Some("second")
+                );
+            }
+        }
+    };
+}
+
+injector_cases!(sns_injector, sns);
+injector_cases!(sqs_injector, sqs);
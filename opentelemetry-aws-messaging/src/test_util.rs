@@ -0,0 +1,49 @@
+//! Shared fixtures for unit tests.
+
+use opentelemetry::global;
+use opentelemetry::propagation::TextMapCompositePropagator;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+use std::sync::Once;
+
+/// Installs the W3C trace context and baggage propagators as the global propagator.
+///
+/// Every test that relies on the global propagator goes through this function so
+/// that parallel tests never observe a different propagator configuration.
+pub fn init_propagator() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        global::set_text_map_propagator(TextMapCompositePropagator::new(vec![
+            Box::new(TraceContextPropagator::new()),
+            Box::new(BaggagePropagator::new()),
+        ]));
+    });
+}
+
+/// Builds a `traceparent` header value for the given hex trace and span ids.
+pub fn traceparent(trace_id: &str, span_id: &str) -> String {
+    format!("00-{}-{}-01", trace_id, span_id)
+}
+
+/// Builds a tracer provider that records finished spans in memory.
+pub fn test_tracer() -> (SdkTracerProvider, InMemorySpanExporter) {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    (provider, exporter)
+}
+
+/// Returns a context holding a sampled remote span
+/// (`4bf92f3577b34da6a3ce929d0e0e4736` / `00f067aa0ba902b7`).
+pub fn sampled_context() -> Context {
+    Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    ))
+}
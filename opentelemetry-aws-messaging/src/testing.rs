@@ -0,0 +1,109 @@
+//! Assertions for end-to-end propagation tests (`testing` feature).
+//!
+//! Integration tests that publish a message under a known trace and consume it again
+//! typically only care whether the trace made it across. [`assert_context_matches`]
+//! replaces the extract/compare boilerplate with a single call that reports what was
+//! actually received on failure.
+
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::{global, Context};
+
+/// Extracts a context from `extractor` using the global propagator and asserts that
+/// its trace id is `expected_trace_id` (32 lowercase hex characters).
+///
+/// # Panics
+///
+/// Panics when the extracted context has no valid span or a different trace id. The
+/// message names the expected and received trace ids along with the carrier keys, so
+/// a missing attribute is easy to tell from a broken trace. A span active in the test
+/// itself never stands in for the carrier's context.
+///
+/// # Example
+///
+/// ```ignore
+/// use opentelemetry_aws_messaging::sqs::CompositeExtractor;
+/// use opentelemetry_aws_messaging::testing::assert_context_matches;
+///
+/// let msg = receive_one(&queue_url).await;
+/// assert_context_matches(&CompositeExtractor::from_message(&msg), &expected_trace_id);
+/// ```
+#[track_caller]
+pub fn assert_context_matches(extractor: &dyn Extractor, expected_trace_id: &str) {
+    let cx = global::get_text_map_propagator(|propagator| {
+        propagator.extract_with_context(&Context::new(), extractor)
+    });
+    let span = cx.span();
+    let span_cx = span.span_context();
+    let mut keys = extractor.keys();
+    keys.sort_unstable();
+
+    assert!(
+        span_cx.is_valid(),
+        "expected trace id {expected_trace_id}, but the carrier has no valid trace context \
+         (keys: {keys:?})"
+    );
+    let trace_id = span_cx.trace_id().to_string();
+    assert!(
+        trace_id == expected_trace_id,
+        "expected trace id {expected_trace_id}, but the carrier continues trace {trace_id} \
+         (keys: {keys:?})"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{init_propagator, sampled_context, traceparent};
+    use std::collections::HashMap;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    fn headers() -> HashMap<String, String> {
+        HashMap::from([(
+            "traceparent".to_string(),
+            traceparent(TRACE_ID, "00f067aa0ba902b7"),
+        )])
+    }
+
+    #[test]
+    fn test_assert_context_matches_accepts_expected_trace() {
+        init_propagator();
+
+        assert_context_matches(&headers(), TRACE_ID);
+    }
+
+    #[test]
+    #[should_panic(expected = "but the carrier continues trace 4bf92f3577b34da6a3ce929d0e0e4736")]
+    fn test_assert_context_matches_reports_other_trace() {
+        init_propagator();
+
+        assert_context_matches(&headers(), "0af7651916cd43dd8448eb211c80319c");
+    }
+
+    #[test]
+    #[should_panic(expected = "no valid trace context (keys: [\"priority\"])")]
+    fn test_assert_context_matches_reports_missing_context() {
+        init_propagator();
+        let headers = HashMap::from([("priority".to_string(), "high".to_string())]);
+
+        assert_context_matches(&headers, TRACE_ID);
+    }
+
+    #[test]
+    fn test_assert_context_matches_accepts_expected_trace_under_an_active_span() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+
+        assert_context_matches(&headers(), TRACE_ID);
+    }
+
+    #[test]
+    #[should_panic(expected = "no valid trace context (keys: [])")]
+    fn test_assert_context_matches_ignores_the_ambient_context() {
+        init_propagator();
+        let _guard = sampled_context().attach();
+
+        assert_context_matches(&HashMap::<String, String>::new(), TRACE_ID);
+    }
+}
@@ -0,0 +1,130 @@
+//! Parsing of the AWS X-Ray trace header.
+//!
+//! X-Ray propagates context as a single header value of the form
+//!
+//! ```text
+//! Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1
+//! ```
+//!
+//! where `Root` is the trace id (a version, an 8 hex digit epoch, and 24 hex digits),
+//! `Parent` the span id, and `Sampled` the sampling decision. The X-Ray trace id maps
+//! onto a W3C trace id by concatenating the epoch and the random part. This module is
+//! SDK-independent; the transport-specific lookups live in the `sqs` module.
+
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+
+/// The header (and user message attribute) name used by the X-Ray SDKs.
+pub const TRACE_HEADER: &str = "X-Amzn-Trace-Id";
+
+/// Parses an X-Ray trace header into a remote [`SpanContext`].
+///
+/// Returns `None` when `Root` or `Parent` is missing or malformed, or the resulting
+/// ids are invalid. Unknown fields (e.g. `Lineage`) are ignored.
+pub fn parse_trace_header(value: &str) -> Option<SpanContext> {
+    let mut root = None;
+    let mut parent = None;
+    let mut sampled = false;
+    for field in value.split(';') {
+        match field.trim().split_once('=') {
+            Some(("Root", v)) => root = Some(v),
+            Some(("Parent", v)) => parent = Some(v),
+            Some(("Sampled", v)) => sampled = v == "1",
+            _ => {}
+        }
+    }
+
+    let trace_id = match root?.split('-').collect::<Vec<_>>().as_slice() {
+        ["1", epoch, random] if epoch.len() == 8 && random.len() == 24 => {
+            TraceId::from_hex(&format!("{epoch}{random}")).ok()?
+        }
+        _ => return None,
+    };
+    let parent = parent?;
+    if parent.len() != 16 {
+        return None;
+    }
+    let span_id = SpanId::from_hex(parent).ok()?;
+
+    let flags = if sampled {
+        TraceFlags::SAMPLED
+    } else {
+        TraceFlags::default()
+    };
+    let span_cx = SpanContext::new(trace_id, span_id, flags, true, TraceState::default());
+    span_cx.is_valid().then_some(span_cx)
+}
+
+/// Formats `span_cx` as an X-Ray trace header, the inverse of [`parse_trace_header`].
+///
+/// The first 8 hex digits of the trace id become the `Root` epoch, so a trace id
+/// that did not originate in X-Ray round-trips unchanged.
+pub fn format_trace_header(span_cx: &SpanContext) -> String {
+    let trace_id = span_cx.trace_id().to_string();
+    format!(
+        "Root=1-{}-{};Parent={};Sampled={}",
+        &trace_id[..8],
+        &trace_id[8..],
+        span_cx.span_id(),
+        u8::from(span_cx.is_sampled())
+    )
+}
+
+/// Returns a parent [`Context`] for an X-Ray trace header, or `None` if it does not
+/// parse.
+pub fn context_from_trace_header(value: &str) -> Option<Context> {
+    parse_trace_header(value).map(|span_cx| Context::new().with_remote_span_context(span_cx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str =
+        "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1";
+
+    #[test]
+    fn test_parse_trace_header() {
+        let span_cx = parse_trace_header(HEADER).unwrap();
+
+        assert_eq!(
+            span_cx.trace_id().to_string(),
+            "5759e988bd862e3fe1be46a994272793"
+        );
+        assert_eq!(span_cx.span_id().to_string(), "53995c3f42cd8ad8");
+        assert!(span_cx.is_sampled());
+        assert!(span_cx.is_remote());
+    }
+
+    #[test]
+    fn test_parse_trace_header_ignores_order_and_unknown_fields() {
+        let span_cx = parse_trace_header(
+            "Sampled=0; Lineage=a87bd80c:1|68fd508a:5; Parent=53995c3f42cd8ad8; Root=1-5759e988-bd862e3fe1be46a994272793",
+        )
+        .unwrap();
+
+        assert_eq!(span_cx.span_id().to_string(), "53995c3f42cd8ad8");
+        assert!(!span_cx.is_sampled());
+    }
+
+    #[test]
+    fn test_format_trace_header_round_trips() {
+        let span_cx = parse_trace_header(HEADER).unwrap();
+
+        assert_eq!(format_trace_header(&span_cx), HEADER);
+    }
+
+    #[test]
+    fn test_parse_malformed_trace_header_is_none() {
+        for input in [
+            "",
+            "Root=1-5759e988-bd862e3fe1be46a994272793",
+            "Root=2-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8",
+            "Root=1-5759e988bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8",
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=0000000000000000",
+            "Root=1-5759e988-bd862e3fe1be46a99427279z;Parent=53995c3f42cd8ad8",
+        ] {
+            assert!(parse_trace_header(input).is_none(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Extraction against SQS `ReceiveMessage` payloads in the shape AWS delivers them.
+//!
+//! The fixtures in `tests/fixtures` are synthetic: they were written by hand from the
+//! documented SQS JSON protocol response format, not captured from live deliveries,
+//! and their ids, receipt handles, digests and signatures are placeholders. They
+//! cover an SNS subscription with raw message delivery on and off, with and without
+//! non-`String` message attributes alongside the trace context, a Lambda destination
+//! record delivered to a queue, and an SES delivery event published through SNS.
+
+#![cfg(feature = "sqs")]
+
+use aws_sdk_sqs::primitives::Blob;
+use aws_sdk_sqs::types::{Message, MessageAttributeValue};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry_aws_messaging::sqs::destination::LambdaDestinationExtractor;
+use opentelemetry_aws_messaging::sqs::ses::SesEventExtractor;
+use opentelemetry_aws_messaging::sqs::CompositeExtractor;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use serde_json::Value;
+
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+/// Builds the SDK `Message` the SQS client would deserialize from a fixture response.
+fn load_message(fixture: &str) -> Message {
+    let response: Value = serde_json::from_str(fixture).unwrap();
+    let raw = &response["Messages"][0];
+
+    let mut builder = Message::builder()
+        .message_id(raw["MessageId"].as_str().unwrap())
+        .receipt_handle(raw["ReceiptHandle"].as_str().unwrap())
+        .body(raw["Body"].as_str().unwrap());
+    if let Some(attrs) = raw["MessageAttributes"].as_object() {
+        for (name, attr) in attrs {
+            let mut value =
+                MessageAttributeValue::builder().data_type(attr["DataType"].as_str().unwrap());
+            if let Some(s) = attr["StringValue"].as_str() {
+                value = value.string_value(s);
+            }
+            // The JSON protocol carries binary values base64-encoded; the SDK hands
+            // out the decoded bytes.
+            if let Some(b) = attr["BinaryValue"].as_str() {
+                value = value.binary_value(Blob::new(STANDARD.decode(b).unwrap()));
+            }
+            builder = builder.message_attributes(name, value.build().unwrap());
+        }
+    }
+    builder.build()
+}
+
+fn extracted_trace_id(msg: &Message) -> String {
+    let cx = TraceContextPropagator::new().extract(&CompositeExtractor::from_message(msg));
+    cx.span().span_context().trace_id().to_string()
+}
+
+#[test]
+fn test_raw_delivery_extracts_from_message_attributes() {
+    let msg = load_message(include_str!("fixtures/sqs_raw_delivery.json"));
+    let extractor = CompositeExtractor::from_message(&msg);
+
+    assert_eq!(extractor.get("traceparent"), Some(TRACEPARENT));
+    assert_eq!(extractor.get("tracestate"), Some("dd=s:1;t.dm:-0"));
+    assert_eq!(extracted_trace_id(&msg), "4bf92f3577b34da6a3ce929d0e0e4736");
+}
+
+#[test]
+fn test_raw_delivery_with_binary_attributes() {
+    let msg = load_message(include_str!("fixtures/sqs_raw_delivery_binary.json"));
+    let extractor = CompositeExtractor::from_message(&msg);
+
+    assert_eq!(extractor.get("traceparent"), Some(TRACEPARENT));
+    assert_eq!(extractor.get("checksum"), None);
+    assert_eq!(
+        msg.message_attributes().unwrap()["checksum"]
+            .binary_value()
+            .map(|b| b.as_ref()),
+        Some([0xde, 0xad, 0xbe, 0xef].as_slice())
+    );
+    assert_eq!(extracted_trace_id(&msg), "4bf92f3577b34da6a3ce929d0e0e4736");
+}
+
+#[test]
+fn test_envelope_delivery_extracts_from_escaped_body() {
+    let msg = load_message(include_str!("fixtures/sqs_sns_envelope.json"));
+    let extractor = CompositeExtractor::from_message(&msg);
+
+    assert!(msg.message_attributes().is_none());
+    assert_eq!(extractor.get("traceparent"), Some(TRACEPARENT));
+    assert_eq!(extractor.get("tracestate"), Some("dd=s:1;t.dm:-0"));
+    assert_eq!(extracted_trace_id(&msg), "4bf92f3577b34da6a3ce929d0e0e4736");
+}
+
+#[test]
+fn test_envelope_delivery_with_binary_attributes() {
+    let msg = load_message(include_str!("fixtures/sqs_sns_envelope_binary.json"));
+    let extractor = CompositeExtractor::from_message(&msg);
+
+    assert_eq!(extractor.get("traceparent"), Some(TRACEPARENT));
+    assert_eq!(extractor.get("checksum"), None);
+    assert_eq!(extractor.get("priority"), None);
+    assert_eq!(extracted_trace_id(&msg), "4bf92f3577b34da6a3ce929d0e0e4736");
+}
+
+#[test]
+fn test_lambda_destination_extracts_from_request_payload() {
+    let msg = load_message(include_str!("fixtures/lambda_destination.json"));
+    let extractor = LambdaDestinationExtractor::parse(msg.body().unwrap()).unwrap();
+
+    assert_eq!(extractor.get("traceparent"), Some(TRACEPARENT));
+    assert_eq!(extractor.get("tracestate"), Some("dd=s:1;t.dm:-0"));
+    let cx = TraceContextPropagator::new().extract(&extractor);
+    assert_eq!(
+        cx.span().span_context().trace_id().to_string(),
+        "4bf92f3577b34da6a3ce929d0e0e4736"
+    );
+}
+
+#[test]
+fn test_ses_event_extracts_from_email_headers() {
+    let msg = load_message(include_str!("fixtures/sqs_ses_event.json"));
+    let extractor = SesEventExtractor::parse(msg.body().unwrap()).unwrap();
+
+    assert_eq!(extractor.get("traceparent"), Some(TRACEPARENT));
+    assert_eq!(extractor.get("tracestate"), Some("dd=s:1;t.dm:-0"));
+    let cx = TraceContextPropagator::new().extract(&extractor);
+    assert_eq!(
+        cx.span().span_context().trace_id().to_string(),
+        "4bf92f3577b34da6a3ce929d0e0e4736"
+    );
+}
@@ -0,0 +1,87 @@
+//! The attribute format guarantee for consumers instrumented with other OpenTelemetry
+//! SDKs.
+//!
+//! The OpenTelemetry Python `botocore` and Java AWS SDK instrumentations read the
+//! trace context from message attributes named exactly after the W3C fields
+//! (`traceparent`, `tracestate`), typed `String`, with the value in `StringValue`.
+//! On the wire, each attribute must therefore look like:
+//!
+//! ```text
+//! "traceparent": {"DataType": "String", "StringValue": "00-<trace>-<span>-01"}
+//! ```
+//!
+//! These tests pin that format for every injection path the crate provides.
+
+#![cfg(all(feature = "sns", feature = "sqs"))]
+
+use opentelemetry::propagation::{Injector, TextMapCompositePropagator, TextMapPropagator};
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use opentelemetry_aws_messaging::config::CarrierConfig;
+use opentelemetry_aws_messaging::core::AttributeInjector;
+use opentelemetry_aws_messaging::sns::{self, ConfiguredInjector};
+use opentelemetry_aws_messaging::SnsMessageAttributesInjector;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use std::collections::HashMap;
+
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+fn context() -> Context {
+    Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::from_key_value([("dd", "s:1")]).unwrap(),
+    ))
+}
+
+fn propagator() -> TextMapCompositePropagator {
+    TextMapCompositePropagator::new(vec![Box::new(TraceContextPropagator::new())])
+}
+
+#[test]
+fn test_sns_injection_matches_other_sdks() {
+    let mut attrs = HashMap::new();
+    propagator().inject_context(&context(), &mut SnsMessageAttributesInjector(&mut attrs));
+
+    let mut names: Vec<_> = attrs.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["traceparent", "tracestate"]);
+    assert_eq!(attrs["traceparent"].string_value(), Some(TRACEPARENT));
+    assert_eq!(attrs["tracestate"].string_value(), Some("dd=s:1"));
+    for (name, value) in &attrs {
+        assert_eq!(value.data_type(), "String", "{name}");
+        assert!(value.string_value().is_some(), "{name}");
+        assert!(value.binary_value().is_none(), "{name}");
+    }
+}
+
+#[test]
+fn test_sqs_injection_matches_other_sdks() {
+    let mut attrs: HashMap<String, aws_sdk_sqs::types::MessageAttributeValue> = HashMap::new();
+    propagator().inject_context(&context(), &mut AttributeInjector(&mut attrs));
+
+    assert_eq!(attrs["traceparent"].string_value(), Some(TRACEPARENT));
+    for (name, value) in &attrs {
+        assert_eq!(value.data_type(), "String", "{name}");
+        assert!(value.string_value().is_some(), "{name}");
+        assert!(value.binary_value().is_none(), "{name}");
+    }
+}
+
+#[test]
+fn test_default_configured_and_traceparent_only_injection_match_other_sdks() {
+    let config = CarrierConfig::default();
+    let mut configured = HashMap::new();
+    ConfiguredInjector::new(&mut configured, &config).set("traceparent", TRACEPARENT.to_string());
+    let mut minimal = HashMap::new();
+    sns::inject_traceparent_only(&context(), &mut minimal);
+
+    for attrs in [configured, minimal] {
+        let value = &attrs["traceparent"];
+        assert_eq!(value.data_type(), "String");
+        assert_eq!(value.string_value(), Some(TRACEPARENT));
+        assert!(value.binary_value().is_none());
+    }
+}
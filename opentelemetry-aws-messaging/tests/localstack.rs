@@ -0,0 +1,148 @@
+//! End-to-end propagation through a real SNS subscription filter policy.
+//!
+//! Subscription filter policies are evaluated against message attributes, the same
+//! attributes that carry the trace context, so a policy mistake can silently drop
+//! messages or a trace field. These tests run against LocalStack and are ignored by
+//! default:
+//!
+//! ```text
+//! docker run --rm -p 4566:4566 localstack/localstack
+//! cargo test -p opentelemetry-aws-messaging --test localstack -- --ignored
+//! ```
+//!
+//! The endpoint defaults to `http://localhost:4566` and can be overridden with
+//! `LOCALSTACK_ENDPOINT`.
+
+#![cfg(all(feature = "sns", feature = "sqs"))]
+
+use aws_config::{BehaviorVersion, Region, SdkConfig};
+use aws_sdk_sns::config::Credentials;
+use aws_sdk_sns::types::MessageAttributeValue;
+use aws_sdk_sqs::types::QueueAttributeName;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use opentelemetry_aws_messaging::sqs::CompositeExtractor;
+use opentelemetry_aws_messaging::SnsMessageAttributesInjector;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use std::collections::HashMap;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const SPAN_ID: &str = "00f067aa0ba902b7";
+
+async fn localstack_config() -> SdkConfig {
+    let endpoint = std::env::var("LOCALSTACK_ENDPOINT")
+        .unwrap_or_else(|_| "http://localhost:4566".to_string());
+    aws_config::defaults(BehaviorVersion::latest())
+        .endpoint_url(endpoint)
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("test", "test", None, None, "localstack"))
+        .load()
+        .await
+}
+
+fn publish_attributes(event_type: &str) -> HashMap<String, MessageAttributeValue> {
+    let cx = Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_hex(TRACE_ID).unwrap(),
+        SpanId::from_hex(SPAN_ID).unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    ));
+    let mut attributes = HashMap::new();
+    attributes.insert(
+        "event_type".to_string(),
+        MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(event_type)
+            .build()
+            .unwrap(),
+    );
+    TraceContextPropagator::new()
+        .inject_context(&cx, &mut SnsMessageAttributesInjector(&mut attributes));
+    attributes
+}
+
+#[tokio::test]
+#[ignore = "requires LocalStack"]
+async fn test_filter_policy_delivers_matching_messages_with_context() {
+    let config = localstack_config().await;
+    let sns = aws_sdk_sns::Client::new(&config);
+    let sqs = aws_sdk_sqs::Client::new(&config);
+
+    let topic_arn = sns
+        .create_topic()
+        .name("otel-filter-policy-test")
+        .send()
+        .await
+        .unwrap()
+        .topic_arn
+        .unwrap();
+    let queue_url = sqs
+        .create_queue()
+        .queue_name("otel-filter-policy-test")
+        .send()
+        .await
+        .unwrap()
+        .queue_url
+        .unwrap();
+    sqs.purge_queue().queue_url(&queue_url).send().await.ok();
+    let queue_arn = sqs
+        .get_queue_attributes()
+        .queue_url(&queue_url)
+        .attribute_names(QueueAttributeName::QueueArn)
+        .send()
+        .await
+        .unwrap()
+        .attributes
+        .unwrap()[&QueueAttributeName::QueueArn]
+        .clone();
+
+    let subscription_arn = sns
+        .subscribe()
+        .topic_arn(&topic_arn)
+        .protocol("sqs")
+        .endpoint(&queue_arn)
+        .attributes("RawMessageDelivery", "true")
+        .attributes("FilterPolicy", r#"{"event_type": ["order.created"]}"#)
+        .send()
+        .await
+        .unwrap()
+        .subscription_arn
+        .unwrap();
+
+    for (event_type, body) in [("order.deleted", "dropped"), ("order.created", "kept")] {
+        sns.publish()
+            .topic_arn(&topic_arn)
+            .message(body)
+            .set_message_attributes(Some(publish_attributes(event_type)))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let messages = sqs
+        .receive_message()
+        .queue_url(&queue_url)
+        .max_number_of_messages(10)
+        .wait_time_seconds(5)
+        .message_attribute_names("All")
+        .send()
+        .await
+        .unwrap()
+        .messages
+        .unwrap_or_default();
+
+    sns.unsubscribe()
+        .subscription_arn(subscription_arn)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].body(), Some("kept"));
+    let cx = TraceContextPropagator::new().extract(&CompositeExtractor::from_message(&messages[0]));
+    let span_cx = cx.span().span_context().clone();
+    assert_eq!(span_cx.trace_id().to_string(), TRACE_ID);
+    assert_eq!(span_cx.span_id().to_string(), SPAN_ID);
+}
@@ -0,0 +1,90 @@
+//! Round trips through the SNS and SQS carriers with non-W3C propagators (B3 and Jaeger).
+//!
+//! The carriers are propagator-agnostic: any `TextMapPropagator` works, as long as
+//! the producer and consumer are configured with the same one. These tests call the
+//! propagators directly instead of installing them globally.
+
+#![cfg(all(feature = "sns", feature = "sqs"))]
+
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use opentelemetry_aws_messaging::core::FilteredInjector;
+use opentelemetry_aws_messaging::{SnsMessageAttributesInjector, SqsMessageAttributesExtractor};
+use opentelemetry_jaeger_propagator::Propagator as JaegerPropagator;
+use opentelemetry_zipkin::{B3Encoding, Propagator as B3Propagator};
+use std::collections::HashMap;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const SPAN_ID: &str = "00f067aa0ba902b7";
+
+fn sampled_context() -> Context {
+    Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_hex(TRACE_ID).unwrap(),
+        SpanId::from_hex(SPAN_ID).unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    ))
+}
+
+/// Publishes through SNS with `propagator`, writing only `fields`, and returns the
+/// SQS attributes a raw delivery subscription would receive.
+fn publish_and_deliver(
+    propagator: &dyn TextMapPropagator,
+    cx: &Context,
+    fields: &[&str],
+) -> HashMap<String, aws_sdk_sqs::types::MessageAttributeValue> {
+    let mut published = HashMap::new();
+    propagator.inject_context(
+        cx,
+        &mut FilteredInjector::new(&mut SnsMessageAttributesInjector(&mut published), fields),
+    );
+
+    published
+        .into_iter()
+        .map(|(name, value)| {
+            let delivered = aws_sdk_sqs::types::MessageAttributeValue::builder()
+                .data_type(value.data_type())
+                .set_string_value(value.string_value().map(str::to_string))
+                .build()
+                .unwrap();
+            (name, delivered)
+        })
+        .collect()
+}
+
+#[test]
+fn test_b3_single_header_round_trips_as_one_attribute() {
+    let propagator = B3Propagator::with_encoding(B3Encoding::SingleHeader);
+
+    let received = publish_and_deliver(&propagator, &sampled_context(), &["b3"]);
+    let cx = propagator.extract(&SqsMessageAttributesExtractor(&received));
+
+    assert_eq!(received.len(), 1);
+    assert_eq!(
+        received["b3"].string_value(),
+        Some(format!("{TRACE_ID}-{SPAN_ID}-1").as_str())
+    );
+    let span_cx = cx.span().span_context().clone();
+    assert_eq!(span_cx.trace_id().to_string(), TRACE_ID);
+    assert_eq!(span_cx.span_id().to_string(), SPAN_ID);
+    assert!(span_cx.is_sampled());
+}
+
+#[test]
+fn test_jaeger_uber_trace_id_round_trips() {
+    let propagator = JaegerPropagator::new();
+
+    let received = publish_and_deliver(&propagator, &sampled_context(), &["uber-trace-id"]);
+    let cx = propagator.extract(&SqsMessageAttributesExtractor(&received));
+
+    assert_eq!(
+        received["uber-trace-id"].string_value(),
+        Some(format!("{TRACE_ID}:{SPAN_ID}:0:1").as_str())
+    );
+    let span_cx = cx.span().span_context().clone();
+    assert_eq!(span_cx.trace_id().to_string(), TRACE_ID);
+    assert_eq!(span_cx.span_id().to_string(), SPAN_ID);
+    assert!(span_cx.is_sampled());
+}
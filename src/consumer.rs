@@ -1,10 +1,9 @@
 use anyhow::{Context as AnyhowContext, Result};
 use aws_sdk_sqs::Client as SqsClient;
-use opentelemetry::global;
 use opentelemetry::trace::{TraceContextExt, TracerProvider};
-use opentelemetry_aws_messaging::SqsMessageAttributesExtractor;
+use opentelemetry_aws_messaging::correlation::trace_id_hex;
+use opentelemetry_aws_messaging::sqs;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::env;
 use std::io::{self, Write};
 use std::process;
@@ -71,18 +70,18 @@ async fn main() -> Result<()> {
                             println!("   [debug] msg.message_attributes().is_some(): {}", msg.message_attributes().is_some());
 
                             // Extract trace context from SQS message attributes
-                            let empty = HashMap::new();
-                            let attrs = msg.message_attributes().unwrap_or(&empty);
-
-                            let parent_cx = global::get_text_map_propagator(|propagator| {
-                                propagator.extract(&SqsMessageAttributesExtractor(attrs))
-                            });
+                            let parent_cx = sqs::extract_context(&msg);
 
                             let parent_span_ctx = parent_cx.span().span_context().clone();
                             println!("   [debug] Parent context valid: {}", parent_span_ctx.is_valid());
 
-                            // Create span using tracing and set parent context from SQS message
-                            let span = tracing::info_span!("sqs.process");
+                            // Create span using tracing and set parent context from SQS message.
+                            // The trace_id field correlates every log line emitted under the
+                            // span with the propagated trace.
+                            let span = tracing::info_span!(
+                                "sqs.process",
+                                trace_id = trace_id_hex(&parent_cx).unwrap_or_default()
+                            );
                             let _ = span.set_parent(parent_cx);
                             let _guard = span.enter();                         
 